@@ -0,0 +1,277 @@
+use crate::error::EditError;
+use crate::id::{NoteId, TrackId};
+use crate::note::NoteEvent;
+use crate::project::{Clip, Project};
+
+/// One reversible change to a project's notes. Carries the full before
+/// and after state so it can be inverted without looking anything up.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    AddNote {
+        track: TrackId,
+        id: NoteId,
+        note: NoteEvent,
+    },
+    RemoveNote {
+        track: TrackId,
+        id: NoteId,
+        note: NoteEvent,
+    },
+    ChangeNote {
+        track: TrackId,
+        id: NoteId,
+        before: NoteEvent,
+        after: NoteEvent,
+    },
+}
+
+impl Edit {
+    pub fn apply(&self, project: &mut Project) -> Result<(), EditError> {
+        match self {
+            Edit::AddNote { track, id, note } => {
+                let clip = clip_mut(project, *track)?;
+
+                if clip.notes.contains_key(id) {
+                    return Err(EditError::DuplicateNote(id.0));
+                }
+
+                clip.notes.insert(*id, *note);
+            }
+            Edit::RemoveNote { track, id, .. } => {
+                clip_mut(project, *track)?
+                    .notes
+                    .remove(id)
+                    .ok_or(EditError::MissingNote(id.0))?;
+            }
+            Edit::ChangeNote {
+                track, id, after, ..
+            } => {
+                *clip_mut(project, *track)?
+                    .notes
+                    .get_mut(id)
+                    .ok_or(EditError::MissingNote(id.0))? = *after;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn inverse(&self) -> Edit {
+        match self.clone() {
+            Edit::AddNote { track, id, note } => Edit::RemoveNote { track, id, note },
+            Edit::RemoveNote { track, id, note } => Edit::AddNote { track, id, note },
+            Edit::ChangeNote {
+                track,
+                id,
+                before,
+                after,
+            } => Edit::ChangeNote {
+                track,
+                id,
+                before: after,
+                after: before,
+            },
+        }
+    }
+}
+
+fn clip_mut(project: &mut Project, track: TrackId) -> Result<&mut Clip, EditError> {
+    project
+        .track_mut(track)
+        .map(|track| &mut track.clip)
+        .ok_or(EditError::MissingTrack(track.0))
+}
+
+/// Applied edits, grouped so one user action undoes in one step.
+#[derive(Debug, Default)]
+pub struct UndoStack {
+    undo: Vec<Vec<Edit>>,
+    redo: Vec<Vec<Edit>>,
+}
+
+impl UndoStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a group of edits as one undo step. All or nothing: if any
+    /// edit fails, the ones before it are reverted.
+    pub fn apply(&mut self, project: &mut Project, edits: Vec<Edit>) -> Result<(), EditError> {
+        if edits.is_empty() {
+            return Ok(());
+        }
+
+        apply_all(project, &edits)?;
+
+        self.undo.push(edits);
+        self.redo.clear();
+
+        Ok(())
+    }
+
+    /// Revert the most recent group. `false` when there is nothing to undo.
+    pub fn undo(&mut self, project: &mut Project) -> Result<bool, EditError> {
+        let Some(edits) = self.undo.pop() else {
+            return Ok(false);
+        };
+
+        let inverse: Vec<Edit> = edits.iter().rev().map(Edit::inverse).collect();
+
+        if let Err(error) = apply_all(project, &inverse) {
+            self.undo.push(edits);
+            return Err(error);
+        }
+
+        self.redo.push(edits);
+        Ok(true)
+    }
+
+    /// Reapply the most recently undone group.
+    pub fn redo(&mut self, project: &mut Project) -> Result<bool, EditError> {
+        let Some(edits) = self.redo.pop() else {
+            return Ok(false);
+        };
+
+        if let Err(error) = apply_all(project, &edits) {
+            self.redo.push(edits);
+            return Err(error);
+        }
+
+        self.undo.push(edits);
+        Ok(true)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+fn apply_all(project: &mut Project, edits: &[Edit]) -> Result<(), EditError> {
+    for (index, edit) in edits.iter().enumerate() {
+        if let Err(error) = edit.apply(project) {
+            for applied in edits[..index].iter().rev() {
+                // UNWRAP SAFETY: each inverse undoes an edit that just
+                // succeeded on the same project.
+                applied.inverse().apply(project).unwrap();
+            }
+
+            return Err(error);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use wmidi::{Note, Velocity};
+
+    use super::*;
+    use crate::tick::Tick;
+
+    fn note(start: u64) -> NoteEvent {
+        NoteEvent {
+            start_tick: Tick::from_raw(start),
+            length_ticks: 120,
+            note: Note::C4,
+            velocity: Velocity::MAX,
+        }
+    }
+
+    fn project() -> (Project, TrackId) {
+        let mut project = Project::new();
+        let track = project.add_track("pulse");
+
+        (project, track)
+    }
+
+    fn notes(project: &Project, track: TrackId) -> Vec<(NoteId, NoteEvent)> {
+        let clip = &project.track(track).unwrap().clip;
+
+        clip.notes.iter().map(|(id, note)| (*id, *note)).collect()
+    }
+
+    #[test]
+    fn undo_and_redo_a_group() {
+        let (mut project, track) = project();
+        let mut stack = UndoStack::new();
+
+        stack
+            .apply(
+                &mut project,
+                vec![
+                    Edit::AddNote {
+                        track,
+                        id: NoteId(0),
+                        note: note(0),
+                    },
+                    Edit::ChangeNote {
+                        track,
+                        id: NoteId(0),
+                        before: note(0),
+                        after: note(240),
+                    },
+                ],
+            )
+            .unwrap();
+        assert_eq!(notes(&project, track), [(NoteId(0), note(240))]);
+
+        assert!(stack.undo(&mut project).unwrap());
+        assert!(notes(&project, track).is_empty());
+        assert!(!stack.undo(&mut project).unwrap());
+
+        assert!(stack.redo(&mut project).unwrap());
+        assert_eq!(notes(&project, track), [(NoteId(0), note(240))]);
+        assert!(!stack.can_redo());
+    }
+
+    #[test]
+    fn failed_group_leaves_project_unchanged() {
+        let (mut project, track) = project();
+        let mut stack = UndoStack::new();
+
+        let result = stack.apply(
+            &mut project,
+            vec![
+                Edit::AddNote {
+                    track,
+                    id: NoteId(0),
+                    note: note(0),
+                },
+                Edit::RemoveNote {
+                    track,
+                    id: NoteId(7),
+                    note: note(0),
+                },
+            ],
+        );
+
+        assert_eq!(result, Err(EditError::MissingNote(7)));
+        assert!(notes(&project, track).is_empty());
+        assert!(!stack.can_undo());
+    }
+
+    #[test]
+    fn new_edit_clears_redo() {
+        let (mut project, track) = project();
+        let mut stack = UndoStack::new();
+        let add = |id| {
+            vec![Edit::AddNote {
+                track,
+                id: NoteId(id),
+                note: note(0),
+            }]
+        };
+
+        stack.apply(&mut project, add(0)).unwrap();
+        stack.undo(&mut project).unwrap();
+        stack.apply(&mut project, add(1)).unwrap();
+
+        assert!(!stack.can_redo());
+        assert_eq!(notes(&project, track), [(NoteId(1), note(0))]);
+    }
+}
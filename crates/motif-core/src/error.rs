@@ -0,0 +1,37 @@
+#[derive(Debug, thiserror::Error)]
+pub enum TuningError {
+    #[error("Tuning file error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("Scale has no degrees")]
+    EmptyScale,
+    #[error("Reference note {0} is not mapped to a scale degree")]
+    UnmappedReference(u8),
+    #[error("Reference frequency {0} Hz is not a positive, finite number")]
+    InvalidReferenceFrequency(f64),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProjectError {
+    #[error("Project file error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid project: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("Failed to serialize project: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("Invalid note {id}: {message}")]
+    InvalidNote { id: u64, message: String },
+    #[error("Tempo {0} is outside 20–999 BPM")]
+    InvalidBpm(f64),
+}
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum EditError {
+    #[error("No track {0}")]
+    MissingTrack(u64),
+    #[error("No note {0}")]
+    MissingNote(u64),
+    #[error("Note {0} already exists")]
+    DuplicateNote(u64),
+}
@@ -1,5 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 /// Global, monotonic ID allocation. IDs are never reused within a project.
-#[derive(Debug, Default)]
+/// Saved with the project so reloading doesn't hand out old IDs again.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct IdAllocator {
     next_track: u64,
     next_note: u64,
@@ -21,14 +24,24 @@ impl IdAllocator {
 
         note
     }
+
+    /// Make sure `id`, already in use, is never handed out again.
+    pub fn claim_track_id(&mut self, id: TrackId) {
+        self.next_track = self.next_track.max(id.0.saturating_add(1));
+    }
+
+    /// Make sure `id`, already in use, is never handed out again.
+    pub fn claim_note_id(&mut self, id: NoteId) {
+        self.next_note = self.next_note.max(id.0.saturating_add(1));
+    }
 }
 
 /// Stable note identifier. Never reused, so undo references remain valid across edits.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct NoteId(pub u64);
 
 /// Stable track identifier. Never reused, so undo references remain valid across edits.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct TrackId(pub u64);
 
 #[cfg(test)]
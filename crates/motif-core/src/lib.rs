@@ -1,3 +1,7 @@
+pub mod edit;
+pub mod error;
 pub mod id;
 pub mod note;
+pub mod project;
 pub mod tick;
+pub mod tuning;
@@ -5,7 +5,7 @@ use crate::tick::Tick;
 /// A note placed in time within a clip. Positions are relative to the
 /// clip's start, not absolute in the arrangement. Baking resolves
 /// to absolute positions later.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct NoteEvent {
     /// Offset from the clip's start.
     pub start_tick: Tick,
@@ -14,3 +14,10 @@ pub struct NoteEvent {
     pub note: Note,
     pub velocity: Velocity,
 }
+
+impl NoteEvent {
+    /// First tick after the note.
+    pub fn end_tick(&self) -> Tick {
+        self.start_tick + Tick::from_raw(self.length_ticks)
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use wmidi::{Note, Velocity};
+
+use crate::error::ProjectError;
+use crate::id::{IdAllocator, NoteId, TrackId};
+use crate::note::NoteEvent;
+use crate::tick::Tick;
+
+pub const DEFAULT_BPM: f64 = 120.0;
+
+/// Tempo range accepted by `:bpm` and when loading.
+pub const MIN_BPM: f64 = 20.0;
+pub const MAX_BPM: f64 = 999.0;
+
+/// A song: tempo plus the tracks that play in it. Saved as RON in a
+/// `.motif` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Project {
+    pub bpm: f64,
+    pub tracks: Vec<Track>,
+    pub ids: IdAllocator,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    pub id: TrackId,
+    pub name: String,
+    /// Instrument name, e.g. `pulse` or `fm`.
+    pub instrument: String,
+    pub clip: Clip,
+}
+
+/// Notes on one track, keyed by ID so edits can refer to them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Clip {
+    pub notes: BTreeMap<NoteId, NoteEvent>,
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Project {
+    pub fn new() -> Self {
+        Self {
+            bpm: DEFAULT_BPM,
+            tracks: Vec::new(),
+            ids: IdAllocator::default(),
+        }
+    }
+
+    /// Append an empty track, named after its instrument and numbered
+    /// among tracks of the same instrument ("Pulse 2").
+    pub fn add_track(&mut self, instrument: &str) -> TrackId {
+        let id = self.ids.next_track_id();
+        let number = self
+            .tracks
+            .iter()
+            .filter(|track| track.instrument == instrument)
+            .count()
+            + 1;
+
+        let mut chars = instrument.chars();
+        let title: String = chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default();
+
+        self.tracks.push(Track {
+            id,
+            name: format!("{title} {number}"),
+            instrument: instrument.to_string(),
+            clip: Clip::default(),
+        });
+
+        id
+    }
+
+    pub fn track(&self, id: TrackId) -> Option<&Track> {
+        self.tracks.iter().find(|track| track.id == id)
+    }
+
+    pub fn track_mut(&mut self, id: TrackId) -> Option<&mut Track> {
+        self.tracks.iter_mut().find(|track| track.id == id)
+    }
+
+    pub fn from_ron(src: &str) -> Result<Self, ProjectError> {
+        ron::from_str::<ProjectFile>(src)?.try_into()
+    }
+
+    pub fn to_ron(&self) -> Result<String, ProjectError> {
+        let file = ProjectFile::from(self);
+
+        Ok(ron::ser::to_string_pretty(
+            &file,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ProjectError> {
+        Self::from_ron(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ProjectError> {
+        fs::write(path, self.to_ron()?)?;
+
+        Ok(())
+    }
+}
+
+impl Clip {
+    /// Notes ordered by start, then pitch.
+    pub fn sorted(&self) -> Vec<(NoteId, &NoteEvent)> {
+        let mut notes: Vec<_> = self.notes.iter().map(|(id, note)| (*id, note)).collect();
+
+        notes.sort_by_key(|(id, note)| (note.start_tick, note.note, *id));
+        notes
+    }
+}
+
+// On-disk shape. wmidi types have no serde support, so notes are stored as
+// plain numbers and checked on the way back in.
+
+#[derive(Serialize, Deserialize)]
+struct ProjectFile {
+    bpm: f64,
+    ids: IdAllocator,
+    tracks: Vec<TrackFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TrackFile {
+    id: TrackId,
+    name: String,
+    instrument: String,
+    #[serde(default)]
+    notes: Vec<NoteFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct NoteFile {
+    id: NoteId,
+    start: Tick,
+    length: u64,
+    note: u8,
+    velocity: u8,
+}
+
+impl From<&Project> for ProjectFile {
+    fn from(project: &Project) -> Self {
+        let tracks = project
+            .tracks
+            .iter()
+            .map(|track| TrackFile {
+                id: track.id,
+                name: track.name.clone(),
+                instrument: track.instrument.clone(),
+                notes: track
+                    .clip
+                    .notes
+                    .iter()
+                    .map(|(id, event)| NoteFile {
+                        id: *id,
+                        start: event.start_tick,
+                        length: event.length_ticks,
+                        note: u8::from(event.note),
+                        velocity: u8::from(event.velocity),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            bpm: project.bpm,
+            ids: project.ids.clone(),
+            tracks,
+        }
+    }
+}
+
+impl TryFrom<ProjectFile> for Project {
+    type Error = ProjectError;
+
+    fn try_from(file: ProjectFile) -> Result<Self, ProjectError> {
+        if !(MIN_BPM..=MAX_BPM).contains(&file.bpm) {
+            return Err(ProjectError::InvalidBpm(file.bpm));
+        }
+
+        // A hand-edited or older file may hold IDs the allocator would
+        // hand out again.
+        let mut ids = file.ids;
+        let mut tracks = Vec::with_capacity(file.tracks.len());
+
+        for track in file.tracks {
+            ids.claim_track_id(track.id);
+            let mut clip = Clip::default();
+
+            for note in track.notes {
+                let invalid = |message: &str| ProjectError::InvalidNote {
+                    id: note.id.0,
+                    message: message.to_string(),
+                };
+
+                if note.note > 127 {
+                    return Err(invalid("pitch must be 0–127"));
+                }
+
+                let event = NoteEvent {
+                    start_tick: note.start,
+                    length_ticks: note.length,
+                    note: Note::from_u8_lossy(note.note),
+                    velocity: Velocity::try_from(note.velocity)
+                        .map_err(|_| invalid("velocity must be 0–127"))?,
+                };
+
+                ids.claim_note_id(note.id);
+
+                if clip.notes.insert(note.id, event).is_some() {
+                    return Err(invalid("duplicate note ID"));
+                }
+            }
+
+            tracks.push(Track {
+                id: track.id,
+                name: track.name,
+                instrument: track.instrument,
+                clip,
+            });
+        }
+
+        Ok(Self {
+            bpm: file.bpm,
+            tracks,
+            ids,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(start: u64, length: u64, pitch: u8) -> NoteEvent {
+        NoteEvent {
+            start_tick: Tick::from_raw(start),
+            length_ticks: length,
+            note: Note::from_u8_lossy(pitch),
+            velocity: Velocity::MAX,
+        }
+    }
+
+    #[test]
+    fn add_track_numbers_per_instrument() {
+        let mut project = Project::new();
+
+        project.add_track("pulse");
+        project.add_track("fm");
+        let id = project.add_track("pulse");
+
+        let names: Vec<_> = project.tracks.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["Pulse 1", "Fm 1", "Pulse 2"]);
+        assert_eq!(id, TrackId(2));
+    }
+
+    #[test]
+    fn round_trips_through_ron() {
+        let mut project = Project::new();
+        project.bpm = 140.0;
+
+        let track = project.add_track("pulse");
+        let id = project.ids.next_note_id();
+        project
+            .track_mut(track)
+            .unwrap()
+            .clip
+            .notes
+            .insert(id, note(480, 240, 64));
+
+        let loaded = Project::from_ron(&project.to_ron().unwrap()).unwrap();
+
+        assert_eq!(loaded, project);
+    }
+
+    #[test]
+    fn loaded_ids_continue_where_they_left_off() {
+        let mut project = Project::new();
+        project.add_track("pulse");
+        project.ids.next_note_id();
+
+        let mut loaded = Project::from_ron(&project.to_ron().unwrap()).unwrap();
+
+        assert_eq!(loaded.ids.next_note_id(), NoteId(1));
+        assert_eq!(loaded.ids.next_track_id(), TrackId(1));
+    }
+
+    #[test]
+    fn rejects_out_of_range_notes() {
+        let src = r#"(
+            bpm: 120.0,
+            ids: (next_track: 1, next_note: 1),
+            tracks: [(id: (0), name: "Pulse 1", instrument: "pulse", notes: [
+                (id: (0), start: (0), length: 480, note: 200, velocity: 100),
+            ])],
+        )"#;
+
+        assert!(matches!(
+            Project::from_ron(src),
+            Err(ProjectError::InvalidNote { id: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn loading_raises_ids_past_those_in_use() {
+        let src = r#"(
+            bpm: 120.0,
+            ids: (next_track: 0, next_note: 2),
+            tracks: [(id: (3), name: "Pulse 1", instrument: "pulse", notes: [
+                (id: (7), start: (0), length: 480, note: 60, velocity: 100),
+                (id: (1), start: (0), length: 480, note: 64, velocity: 100),
+            ])],
+        )"#;
+
+        let mut project = Project::from_ron(src).unwrap();
+
+        assert_eq!(project.ids.next_track_id(), TrackId(4));
+        assert_eq!(project.ids.next_note_id(), NoteId(8));
+    }
+
+    #[test]
+    fn rejects_out_of_range_bpm() {
+        for bpm in ["NaN", "0.0", "-120.0", "1000.0"] {
+            let src = format!("(bpm: {bpm}, ids: (next_track: 0, next_note: 0), tracks: [])");
+
+            assert!(
+                matches!(Project::from_ron(&src), Err(ProjectError::InvalidBpm(_))),
+                "{bpm}"
+            );
+        }
+    }
+
+    #[test]
+    fn sorted_orders_by_start_then_pitch() {
+        let mut clip = Clip::default();
+        clip.notes.insert(NoteId(0), note(480, 10, 60));
+        clip.notes.insert(NoteId(1), note(0, 10, 67));
+        clip.notes.insert(NoteId(2), note(0, 10, 60));
+
+        let order: Vec<_> = clip.sorted().into_iter().map(|(id, _)| id).collect();
+
+        assert_eq!(order, [NoteId(2), NoteId(1), NoteId(0)]);
+    }
+}
@@ -1,10 +1,12 @@
+use serde::{Deserialize, Serialize};
+
 /// 480 PPQ. Divides cleanly by 2, 3, 4, 5, 8, 16, 32, etc.
 /// Covers all standard musical subdivisions including triplets.
 pub const TICKS_PER_QUARTER: u64 = 480;
 
 /// Absolute position in musical time. Integer-only to avoid float drift.
 /// Durations use raw `u64` instead. Tick is specifically a position.
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Tick(u64);
 
 impl Tick {
@@ -14,6 +16,10 @@ impl Tick {
         Self(ticks)
     }
 
+    pub fn as_raw(self) -> u64 {
+        self.0
+    }
+
     pub fn from_quarters(quarters: u64) -> Self {
         Tick(quarters * TICKS_PER_QUARTER)
     }
@@ -0,0 +1,561 @@
+use std::path::Path;
+
+use wmidi::Note;
+
+use crate::error::TuningError;
+
+/// A Scala scale: pitches in cents above the 1/1, excluding the 1/1 itself.
+/// The last degree is the period (usually 1200.0, the octave).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scale {
+    pub description: String,
+    pub degrees: Vec<f64>,
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self::equal_division(12, 1200.0)
+    }
+}
+
+impl Scale {
+    /// `steps` equal divisions of `period` cents (12-TET is `(12, 1200.0)`).
+    pub fn equal_division(steps: usize, period: f64) -> Self {
+        Self {
+            description: format!("{steps} equal divisions of {period} cents"),
+            degrees: (1..=steps)
+                .map(|step| period * step as f64 / steps as f64)
+                .collect(),
+        }
+    }
+
+    /// Parse a Scala `.scl` file. Pitch lines containing a `.` are cents,
+    /// anything else is a ratio (`3/2`) or an integer (`2`).
+    pub fn parse_scl(source: &str) -> Result<Self, TuningError> {
+        let mut lines = source
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.starts_with('!'));
+
+        // The description is the first non-comment line and may be blank.
+        let (_, description) = lines
+            .next()
+            .ok_or_else(|| parse_error(0, "missing description"))?;
+
+        let mut lines = lines.filter(|(_, line)| !line.is_empty());
+
+        let (line, count) = lines
+            .next()
+            .ok_or_else(|| parse_error(0, "missing note count"))?;
+        let count: usize = first_token(count)
+            .parse()
+            .map_err(|_| parse_error(line, "note count is not a number"))?;
+
+        if count == 0 {
+            return Err(parse_error(line, "scale has no degrees"));
+        }
+
+        let degrees = (0..count)
+            .map(|_| {
+                let (line, pitch) = lines
+                    .next()
+                    .ok_or_else(|| parse_error(0, format!("expected {count} pitches")))?;
+
+                parse_pitch(first_token(pitch)).ok_or_else(|| parse_error(line, "invalid pitch"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            description: description.to_string(),
+            degrees,
+        })
+    }
+
+    /// Read and parse a `.scl` file. Blocking I/O — never call from the audio thread.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TuningError> {
+        Self::parse_scl(&std::fs::read_to_string(path)?)
+    }
+
+    /// Cents above the 1/1 for any degree, extending by whole periods in
+    /// both directions.
+    fn cents(&self, degree: i64) -> f64 {
+        let len = self.degrees.len() as i64;
+        let period = self.degrees[self.degrees.len() - 1];
+        let step = degree.rem_euclid(len);
+
+        let within = if step == 0 {
+            0.0
+        } else {
+            self.degrees[step as usize - 1]
+        };
+
+        degree.div_euclid(len) as f64 * period + within
+    }
+}
+
+/// Largest `.kbm` map size accepted. A pattern longer than the MIDI key
+/// range could never repeat.
+const MAX_MAP_SIZE: usize = 128;
+
+/// A Scala `.kbm` keyboard mapping: which MIDI keys get which scale degrees,
+/// and which key is tuned to the reference pitch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyboardMapping {
+    /// Keys in the repeating pattern. 0 maps keys linearly to degrees.
+    pub size: usize,
+    pub first_note: u8,
+    pub last_note: u8,
+    /// Key that plays scale degree 0.
+    pub middle_note: u8,
+    pub reference_note: u8,
+    /// Frequency of `reference_note` in Hz.
+    pub reference_frequency: f64,
+    /// Degree that counts as the pattern's "octave". 0 uses the scale's period.
+    pub octave_degree: usize,
+    /// Scale degree per pattern key. `None` ("x" in the file) is unmapped and silent.
+    pub mapping: Vec<Option<usize>>,
+}
+
+impl Default for KeyboardMapping {
+    fn default() -> Self {
+        Self {
+            size: 0,
+            first_note: 0,
+            last_note: 127,
+            middle_note: 60,
+            reference_note: 69,
+            reference_frequency: 440.0,
+            octave_degree: 0,
+            mapping: Vec::new(),
+        }
+    }
+}
+
+impl KeyboardMapping {
+    /// Parse a Scala `.kbm` file. Missing mapping entries are unmapped.
+    pub fn parse_kbm(source: &str) -> Result<Self, TuningError> {
+        let mut lines = source
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.starts_with('!') && !line.is_empty())
+            .map(|(line, text)| (line, first_token(text)));
+
+        let mut field = |name: &str| {
+            lines
+                .next()
+                .ok_or_else(|| parse_error(0, format!("missing {name}")))
+        };
+
+        let size_field = field("map size")?;
+        let size = parse_field(size_field, "map size")?;
+
+        if size > MAX_MAP_SIZE {
+            return Err(parse_error(
+                size_field.0,
+                format!("map size {size} is over {MAX_MAP_SIZE}"),
+            ));
+        }
+
+        let first_note = parse_note(field("first note")?, "first note")?;
+        let last_note = parse_note(field("last note")?, "last note")?;
+        let middle_note = parse_note(field("middle note")?, "middle note")?;
+        let reference_note = parse_note(field("reference note")?, "reference note")?;
+        let reference_field = field("reference frequency")?;
+        let reference_frequency: f64 = parse_field(reference_field, "frequency")?;
+        let octave_degree = parse_field(field("octave degree")?, "octave degree")?;
+
+        if !is_valid_frequency(reference_frequency) {
+            return Err(parse_error(
+                reference_field.0,
+                "reference frequency must be a positive, finite number",
+            ));
+        }
+
+        let mut mapping = Vec::with_capacity(size);
+
+        for (line, entry) in lines.take(size) {
+            mapping.push(match entry {
+                "x" | "X" => None,
+                degree => Some(parse_field((line, degree), "mapping entry")?),
+            });
+        }
+
+        mapping.resize(size, None);
+
+        Ok(Self {
+            size,
+            first_note,
+            last_note,
+            middle_note,
+            reference_note,
+            reference_frequency,
+            octave_degree,
+            mapping,
+        })
+    }
+
+    /// Read and parse a `.kbm` file. Blocking I/O — never call from the audio thread.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TuningError> {
+        Self::parse_kbm(&std::fs::read_to_string(path)?)
+    }
+
+    /// Scale degree played by a key, or `None` if the key is unmapped.
+    fn degree(&self, note: u8, scale_len: usize) -> Option<i64> {
+        if note < self.first_note || note > self.last_note {
+            return None;
+        }
+
+        let offset = note as i64 - self.middle_note as i64;
+
+        if self.size == 0 {
+            return Some(offset);
+        }
+
+        let size = self.size as i64;
+        let entry = (*self.mapping.get(offset.rem_euclid(size) as usize)?)? as i64;
+        let octave_degree = match self.octave_degree {
+            0 => scale_len,
+            degree => degree,
+        } as i64;
+
+        Some(entry + offset.div_euclid(size) * octave_degree)
+    }
+}
+
+/// Note → frequency lookup for instruments. Built from a scale and keyboard
+/// mapping off the audio thread; lookups are a table read, so they're
+/// real-time safe. Defaults to 12-TET at A440.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tuning {
+    frequencies: [Option<f64>; 128],
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        // UNWRAP SAFETY: 440 Hz is a valid reference.
+        Self::equal_temperament(440.0).unwrap()
+    }
+}
+
+impl Tuning {
+    pub fn new(scale: &Scale, mapping: &KeyboardMapping) -> Result<Self, TuningError> {
+        let len = scale.degrees.len();
+
+        if len == 0 {
+            return Err(TuningError::EmptyScale);
+        }
+
+        if !is_valid_frequency(mapping.reference_frequency) {
+            return Err(TuningError::InvalidReferenceFrequency(
+                mapping.reference_frequency,
+            ));
+        }
+
+        let reference_degree = mapping
+            .degree(mapping.reference_note, len)
+            .ok_or(TuningError::UnmappedReference(mapping.reference_note))?;
+        let reference_cents = scale.cents(reference_degree);
+
+        let frequencies = std::array::from_fn(|note| {
+            let degree = mapping.degree(note as u8, len)?;
+            let cents = scale.cents(degree) - reference_cents;
+
+            Some(mapping.reference_frequency * (cents / 1200.0).exp2())
+        });
+
+        Ok(Self { frequencies })
+    }
+
+    /// 12-TET with A4 tuned to `reference` Hz (e.g. 432.0 or 415.0).
+    pub fn equal_temperament(reference: f64) -> Result<Self, TuningError> {
+        let mapping = KeyboardMapping {
+            reference_frequency: reference,
+            ..KeyboardMapping::default()
+        };
+
+        // The default mapping is linear, so the reference is always mapped.
+        Self::new(&Scale::default(), &mapping)
+    }
+
+    /// Frequency in Hz, or `None` if the key is unmapped and should stay silent.
+    pub fn frequency(&self, note: Note) -> Option<f64> {
+        self.frequencies[u8::from(note) as usize]
+    }
+}
+
+/// Reference pitches feed straight into oscillator phase increments, so
+/// zero, negative and non-finite values are rejected.
+fn is_valid_frequency(frequency: f64) -> bool {
+    frequency.is_finite() && frequency > 0.0
+}
+
+fn first_token(line: &str) -> &str {
+    line.split_whitespace().next().unwrap_or("")
+}
+
+fn parse_pitch(token: &str) -> Option<f64> {
+    if token.contains('.') {
+        return token.parse().ok();
+    }
+
+    let (numerator, denominator) = token.split_once('/').unwrap_or((token, "1"));
+    let numerator: f64 = numerator.parse::<u64>().ok()? as f64;
+    let denominator: f64 = denominator.parse::<u64>().ok()? as f64;
+
+    if numerator == 0.0 || denominator == 0.0 {
+        return None;
+    }
+
+    Some(1200.0 * (numerator / denominator).log2())
+}
+
+fn parse_field<T: std::str::FromStr>(
+    (line, token): (usize, &str),
+    name: &str,
+) -> Result<T, TuningError> {
+    token
+        .parse()
+        .map_err(|_| parse_error(line, format!("invalid {name} `{token}`")))
+}
+
+fn parse_note(field: (usize, &str), name: &str) -> Result<u8, TuningError> {
+    let line = field.0;
+    let note: u8 = parse_field(field, name)?;
+
+    if note > 127 {
+        return Err(parse_error(
+            line,
+            format!("{name} {note} is not a MIDI note"),
+        ));
+    }
+
+    Ok(note)
+}
+
+fn parse_error(line: usize, message: impl Into<String>) -> TuningError {
+    TuningError::Parse {
+        line,
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEANTONE: &str = "\
+! meanquar.scl
+!
+1/4-comma meantone scale. Pietro Aaron's temperament (1523)
+ 12
+!
+ 76.04900
+ 193.15686
+ 310.26303
+ 5/4
+ 503.42157
+ 579.47057
+ 696.57843
+ 25/16
+ 889.73460
+ 1006.84314
+ 1082.89214
+ 2/1
+";
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{a} != {b}");
+    }
+
+    #[test]
+    fn default_matches_twelve_tet() {
+        let tuning = Tuning::default();
+
+        for note in [Note::CMinus1, Note::C4, Note::A4, Note::G9] {
+            assert_close(tuning.frequency(note).unwrap(), note.to_freq_f64());
+        }
+    }
+
+    #[test]
+    fn reference_pitch_moves_everything() {
+        let tuning = Tuning::equal_temperament(432.0).unwrap();
+
+        assert_close(tuning.frequency(Note::A4).unwrap(), 432.0);
+        assert_close(tuning.frequency(Note::A5).unwrap(), 864.0);
+    }
+
+    #[test]
+    fn parse_scl_cents_and_ratios() {
+        let scale = Scale::parse_scl(MEANTONE).unwrap();
+
+        assert_eq!(
+            scale.description,
+            "1/4-comma meantone scale. Pietro Aaron's temperament (1523)"
+        );
+        assert_eq!(scale.degrees.len(), 12);
+        assert_close(scale.degrees[0], 76.049);
+        assert_close(scale.degrees[3], 1200.0 * 1.25_f64.log2());
+        assert_close(scale.degrees[11], 1200.0);
+    }
+
+    #[test]
+    fn parse_scl_integer_pitch() {
+        let scale = Scale::parse_scl("Tritave\n2\n1200.0\n3\n").unwrap();
+
+        assert_close(scale.degrees[1], 1200.0 * 3.0_f64.log2());
+    }
+
+    #[test]
+    fn parse_scl_errors_report_line() {
+        let error = Scale::parse_scl("Bad\n2\n100.0\nfoo\n").unwrap_err();
+
+        assert!(matches!(error, TuningError::Parse { line: 4, .. }));
+        assert!(Scale::parse_scl("Short\n3\n100.0\n").is_err());
+        assert!(Scale::parse_scl("Empty\n0\n").is_err());
+    }
+
+    #[test]
+    fn meantone_major_third_is_pure() {
+        let scale = Scale::parse_scl(MEANTONE).unwrap();
+        let tuning = Tuning::new(&scale, &KeyboardMapping::default()).unwrap();
+
+        let c = tuning.frequency(Note::C4).unwrap();
+        let e = tuning.frequency(Note::E4).unwrap();
+
+        assert_close(e / c, 1.25);
+        assert_close(tuning.frequency(Note::A4).unwrap(), 440.0);
+    }
+
+    #[test]
+    fn nineteen_edo_octave_spans_nineteen_keys() {
+        let tuning = Tuning::new(
+            &Scale::equal_division(19, 1200.0),
+            &KeyboardMapping::default(),
+        )
+        .unwrap();
+
+        let a4 = u8::from(Note::A4);
+        let up = Note::try_from(a4 + 19).unwrap();
+
+        assert_close(tuning.frequency(up).unwrap(), 880.0);
+    }
+
+    #[test]
+    fn parse_kbm_with_unmapped_keys() {
+        // Seven white keys per octave mapped to a 7-note scale, black keys silent.
+        let kbm = "\
+! white keys only
+12
+0
+127
+60
+69
+440.0
+7
+0
+x
+1
+x
+2
+3
+x
+4
+x
+5
+x
+6
+";
+        let mapping = KeyboardMapping::parse_kbm(kbm).unwrap();
+        assert_eq!(mapping.size, 12);
+        assert_eq!(mapping.mapping[1], None);
+        assert_eq!(mapping.mapping[11], Some(6));
+
+        let scale = Scale::equal_division(7, 1200.0);
+        let tuning = Tuning::new(&scale, &mapping).unwrap();
+
+        assert_eq!(tuning.frequency(Note::CSharp4), None);
+        assert_close(tuning.frequency(Note::A4).unwrap(), 440.0);
+        assert_close(
+            tuning.frequency(Note::C5).unwrap(),
+            2.0 * tuning.frequency(Note::C4).unwrap(),
+        );
+    }
+
+    #[test]
+    fn kbm_range_limits_mapped_keys() {
+        let mapping = KeyboardMapping {
+            first_note: 36,
+            last_note: 96,
+            ..KeyboardMapping::default()
+        };
+        let tuning = Tuning::new(&Scale::default(), &mapping).unwrap();
+
+        assert_eq!(tuning.frequency(Note::C1), None);
+        assert!(tuning.frequency(Note::C2).is_some());
+    }
+
+    #[test]
+    fn unmapped_reference_is_an_error() {
+        let mapping = KeyboardMapping {
+            first_note: 70,
+            ..KeyboardMapping::default()
+        };
+
+        assert!(matches!(
+            Tuning::new(&Scale::default(), &mapping),
+            Err(TuningError::UnmappedReference(69))
+        ));
+    }
+
+    #[test]
+    fn parse_kbm_rejects_bad_fields() {
+        assert!(KeyboardMapping::parse_kbm("0\n0\n200\n60\n69\n440.0\n0\n").is_err());
+        assert!(KeyboardMapping::parse_kbm("0\n0\n127\n60\n69\n").is_err());
+    }
+
+    #[test]
+    fn parse_kbm_rejects_oversized_map() {
+        for size in ["129", "4000000000", "18446744073709551615"] {
+            let source = format!("{size}\n0\n127\n60\n69\n440.0\n0\n");
+
+            assert!(matches!(
+                KeyboardMapping::parse_kbm(&source),
+                Err(TuningError::Parse { line: 1, .. })
+            ));
+        }
+
+        let full = format!("128\n0\n127\n60\n69\n440.0\n0\n{}", "x\n".repeat(128));
+
+        assert_eq!(
+            KeyboardMapping::parse_kbm(&full).unwrap().mapping.len(),
+            128
+        );
+    }
+
+    #[test]
+    fn invalid_reference_frequency_is_an_error() {
+        for frequency in [0.0, -440.0, f64::NAN, f64::INFINITY] {
+            let mapping = KeyboardMapping {
+                reference_frequency: frequency,
+                ..KeyboardMapping::default()
+            };
+
+            assert!(matches!(
+                Tuning::new(&Scale::default(), &mapping),
+                Err(TuningError::InvalidReferenceFrequency(_))
+            ));
+            assert!(Tuning::equal_temperament(frequency).is_err());
+        }
+
+        for frequency in ["0.0", "-440.0", "NaN", "inf"] {
+            let source = format!("0\n0\n127\n60\n69\n{frequency}\n0\n");
+
+            assert!(matches!(
+                KeyboardMapping::parse_kbm(&source),
+                Err(TuningError::Parse { line: 6, .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use std::ops::Range;
+
+use motif_engine::{
+    buffer::AudioBuffer,
+    events::{Event, MidiEvent},
+    lfo::Lfo,
+    node::AudioNode,
+};
+
+use crate::{
+    line::DelayLine,
+    param::{self, Automatable, ParamInfo, Smoothed},
+    stereo,
+};
+
+/// Longest base delay plus depth, in seconds.
+const MAX_SECONDS: f64 = 0.05;
+
+/// LFO phase offset of the right side, in cycles. A quarter cycle keeps the
+/// two sides moving apart for the widest image.
+const STEREO_PHASE: f64 = 0.25;
+
+const PARAMS: &[ParamInfo] = &[
+    ParamInfo {
+        name: "rate",
+        min: 0.01,
+        max: 10.0,
+        default: 0.8,
+    },
+    ParamInfo {
+        name: "depth",
+        min: 0.0,
+        max: 0.02,
+        default: 0.003,
+    },
+    ParamInfo {
+        name: "delay",
+        min: 0.0005,
+        max: 0.03,
+        default: 0.012,
+    },
+    ParamInfo {
+        name: "feedback",
+        min: -0.95,
+        max: 0.95,
+        default: 0.0,
+    },
+    ParamInfo {
+        name: "mix",
+        min: 0.0,
+        max: 1.0,
+        default: 0.5,
+    },
+];
+
+/// Stereo chorus and flanger: a short delay per side swept by an LFO, the
+/// right side a quarter cycle behind. Long delays without feedback chorus;
+/// short delays with feedback flange. Consumes `inputs[0]` and writes
+/// channels 0 and 1.
+#[derive(Debug)]
+pub struct Chorus {
+    /// Sweep speed and shape. `lfo.rate` is automatable.
+    pub lfo: Lfo,
+    /// How far the sweep moves the delay, in seconds.
+    pub depth: f64,
+    /// Delay at the bottom of the sweep, in seconds.
+    pub delay: f64,
+    /// Delayed signal fed back into the line (-0.95–0.95). Negative values
+    /// flange with a hollower tone.
+    pub feedback: f64,
+    /// Dry/wet balance (0.0 = dry only).
+    pub mix: f64,
+    lines: [DelayLine; 2],
+    smoothed_depth: Smoothed,
+    smoothed_delay: Smoothed,
+    smoothed_mix: Smoothed,
+}
+
+impl Default for Chorus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Chorus {
+    /// Defaults set up as a chorus.
+    pub fn new() -> Self {
+        Self {
+            lfo: Lfo {
+                rate: PARAMS[0].default,
+                ..Lfo::default()
+            },
+            depth: PARAMS[1].default,
+            delay: PARAMS[2].default,
+            feedback: PARAMS[3].default,
+            mix: PARAMS[4].default,
+            lines: [
+                DelayLine::with_seconds(MAX_SECONDS),
+                DelayLine::with_seconds(MAX_SECONDS),
+            ],
+            smoothed_depth: Smoothed::new(),
+            smoothed_delay: Smoothed::new(),
+            smoothed_mix: Smoothed::new(),
+        }
+    }
+
+    /// A slow, resonant flanger.
+    pub fn flanger() -> Self {
+        Self {
+            lfo: Lfo {
+                rate: 0.2,
+                ..Lfo::default()
+            },
+            depth: 0.002,
+            delay: 0.001,
+            feedback: 0.7,
+            mix: 0.5,
+            ..Self::new()
+        }
+    }
+}
+
+impl AudioNode for Chorus {
+    fn render(
+        &mut self,
+        inputs: &[&AudioBuffer],
+        output: &mut AudioBuffer,
+        frame_range: Range<usize>,
+        sample_rate: f64,
+    ) {
+        let feedback = PARAMS[3].clamp(self.feedback);
+        let (left, right) = output.two_channels_mut(0, 1);
+
+        for frame in frame_range {
+            let dry = stereo::input_frame(inputs, frame);
+            let depth = self.smoothed_depth.next(self.depth, sample_rate);
+            let delay = self.smoothed_delay.next(self.delay, sample_rate);
+            let mix = self.smoothed_mix.next(self.mix, sample_rate);
+
+            let right_lfo = Lfo {
+                phase: (self.lfo.phase + STEREO_PHASE).rem_euclid(1.0),
+                ..self.lfo
+            };
+            let sweeps = [self.lfo.tick(sample_rate), right_lfo.value()];
+
+            let mut wet = [0.0; 2];
+
+            for (side, line) in self.lines.iter_mut().enumerate() {
+                // Sweep between `delay` and `delay + depth`.
+                let seconds = delay + depth * (sweeps[side] + 1.0) / 2.0;
+                let delayed = line.read(seconds * sample_rate);
+
+                line.push(dry[side] + delayed * feedback);
+                wet[side] = delayed;
+            }
+
+            left[frame] = stereo::blend(dry[0], wet[0], mix) as f32;
+            right[frame] = stereo::blend(dry[1], wet[1], mix) as f32;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Midi(MidiEvent::ControlChange { control, value }) = event {
+            param::apply_control(self, *control, *value);
+        }
+    }
+
+    fn reset(&mut self) {
+        for line in &mut self.lines {
+            line.clear();
+        }
+
+        self.lfo.reset();
+        self.smoothed_depth.reset();
+        self.smoothed_delay.reset();
+        self.smoothed_mix.reset();
+    }
+}
+
+impl Automatable for Chorus {
+    fn params(&self) -> &'static [ParamInfo] {
+        PARAMS
+    }
+
+    fn get(&self, index: usize) -> Option<f64> {
+        let value = match index {
+            0 => self.lfo.rate,
+            1 => self.depth,
+            2 => self.delay,
+            3 => self.feedback,
+            4 => self.mix,
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        let Some(info) = PARAMS.get(index) else {
+            return;
+        };
+
+        let value = info.clamp(value);
+
+        match index {
+            0 => self.lfo.rate = value,
+            1 => self.depth = value,
+            2 => self.delay = value,
+            3 => self.feedback = value,
+            4 => self.mix = value,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use motif_engine::graph::evaluate_node;
+
+    const SAMPLE_RATE: f64 = 48000.0;
+
+    /// One second of a 440 Hz sine on both sides.
+    fn tone() -> AudioBuffer {
+        let mut input = AudioBuffer::new(2, 48000);
+        input.prepare(48000);
+
+        for channel in 0..2 {
+            for (i, sample) in input.channel_mut(channel).iter_mut().enumerate() {
+                *sample = (std::f64::consts::TAU * 440.0 * i as f64 / SAMPLE_RATE).sin() as f32;
+            }
+        }
+
+        input
+    }
+
+    fn run(chorus: &mut Chorus, input: &AudioBuffer) -> AudioBuffer {
+        let mut output = AudioBuffer::new(2, input.frames());
+        output.prepare(input.frames());
+
+        evaluate_node(chorus, &[input], &mut output, &[], SAMPLE_RATE);
+
+        output
+    }
+
+    /// Peak level over each 10 ms window.
+    fn window_peaks(samples: &[f32]) -> Vec<f32> {
+        samples
+            .chunks(480)
+            .map(|w| w.iter().fold(0.0f32, |m, s| m.max(s.abs())))
+            .collect()
+    }
+
+    #[test]
+    fn sweep_makes_level_move() {
+        let mut chorus = Chorus::new();
+        let output = run(&mut chorus, &tone());
+
+        // Dry and a swept copy beat against each other, so the level
+        // wanders; a plain sine's wouldn't.
+        let peaks = window_peaks(&output.channel(0)[4800..]);
+        let (low, high) = peaks
+            .iter()
+            .fold((f32::MAX, 0.0f32), |(lo, hi), &p| (lo.min(p), hi.max(p)));
+
+        assert!(high - low > 0.2);
+    }
+
+    #[test]
+    fn sides_are_swept_apart() {
+        let mut chorus = Chorus::new();
+        let output = run(&mut chorus, &tone());
+
+        assert_ne!(output.channel(0), output.channel(1));
+    }
+
+    #[test]
+    fn zero_depth_is_a_fixed_delay() {
+        let mut chorus = Chorus {
+            depth: 0.0,
+            delay: 0.01,
+            mix: 1.0,
+            ..Chorus::new()
+        };
+        let input = tone();
+        let output = run(&mut chorus, &input);
+
+        for i in 1000..2000 {
+            assert!((output.channel(0)[i] - input.channel(0)[i - 480]).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn flanger_feedback_resonates() {
+        let mut impulse = AudioBuffer::new(2, 4800);
+        impulse.prepare(4800);
+        impulse.channel_mut(0)[0] = 1.0;
+
+        let late = |feedback: f64| {
+            let mut flanger = Chorus {
+                feedback,
+                mix: 1.0,
+                ..Chorus::flanger()
+            };
+            let output = run(&mut flanger, &impulse);
+
+            // Past the longest single trip through the line (3 ms).
+            output.channel(0)[200..]
+                .iter()
+                .map(|s| s.abs())
+                .sum::<f32>()
+        };
+
+        assert_eq!(late(0.0), 0.0);
+        assert!(late(0.7) > 0.1);
+    }
+
+    #[test]
+    fn dry_mix_passes_input() {
+        let mut chorus = Chorus {
+            mix: 0.0,
+            ..Chorus::new()
+        };
+        let input = tone();
+        let output = run(&mut chorus, &input);
+
+        assert_eq!(output.channel(0), input.channel(0));
+    }
+
+    #[test]
+    fn rate_is_automatable() {
+        let mut chorus = Chorus::new();
+        chorus.set(0, 100.0);
+
+        assert_eq!(chorus.get(0), Some(10.0));
+        assert_eq!(chorus.lfo.rate, 10.0);
+    }
+}
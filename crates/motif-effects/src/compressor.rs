@@ -0,0 +1,312 @@
+use std::ops::Range;
+
+use motif_engine::{
+    buffer::AudioBuffer,
+    events::{Event, MidiEvent},
+    node::AudioNode,
+};
+
+use crate::{
+    gain::{db_to_gain, gain_to_db, time_coefficient},
+    param::{self, Automatable, ParamInfo},
+    stereo,
+};
+
+const PARAMS: &[ParamInfo] = &[
+    ParamInfo {
+        name: "threshold",
+        min: -60.0,
+        max: 0.0,
+        default: -18.0,
+    },
+    ParamInfo {
+        name: "ratio",
+        min: 1.0,
+        max: 20.0,
+        default: 4.0,
+    },
+    ParamInfo {
+        name: "attack",
+        min: 0.0001,
+        max: 0.2,
+        default: 0.01,
+    },
+    ParamInfo {
+        name: "release",
+        min: 0.01,
+        max: 2.0,
+        default: 0.15,
+    },
+    ParamInfo {
+        name: "knee",
+        min: 0.0,
+        max: 24.0,
+        default: 6.0,
+    },
+    ParamInfo {
+        name: "makeup",
+        min: 0.0,
+        max: 24.0,
+        default: 0.0,
+    },
+];
+
+/// Stereo-linked feed-forward compressor. Compresses `inputs[0]` and
+/// writes channels 0 and 1. When `inputs[1]` is connected it keys the
+/// detector instead, so another track can duck this one.
+#[derive(Debug)]
+pub struct Compressor {
+    /// Level in dB where compression starts.
+    pub threshold: f64,
+    /// Input dB over the threshold per output dB over it.
+    pub ratio: f64,
+    /// Seconds for gain reduction to move in.
+    pub attack: f64,
+    /// Seconds for gain reduction to let go.
+    pub release: f64,
+    /// Width in dB of the soft knee around the threshold.
+    pub knee: f64,
+    /// Gain in dB added after compression.
+    pub makeup: f64,
+    /// Current gain change in dB before makeup (zero or negative).
+    reduction: f64,
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compressor {
+    pub fn new() -> Self {
+        Self {
+            threshold: PARAMS[0].default,
+            ratio: PARAMS[1].default,
+            attack: PARAMS[2].default,
+            release: PARAMS[3].default,
+            knee: PARAMS[4].default,
+            makeup: PARAMS[5].default,
+            reduction: 0.0,
+        }
+    }
+
+    /// Current gain reduction in dB, for metering. Zero or negative.
+    pub fn gain_reduction(&self) -> f64 {
+        self.reduction
+    }
+
+    /// Static curve: gain change in dB for a detector level in dB.
+    fn curve(&self, level: f64) -> f64 {
+        let slope = 1.0 / self.ratio.max(1.0) - 1.0;
+        let over = level - self.threshold;
+        let knee = self.knee.max(0.0);
+
+        if 2.0 * over <= -knee {
+            0.0
+        } else if 2.0 * over.abs() < knee {
+            slope * (over + knee / 2.0).powi(2) / (2.0 * knee)
+        } else {
+            slope * over
+        }
+    }
+}
+
+impl AudioNode for Compressor {
+    fn render(
+        &mut self,
+        inputs: &[&AudioBuffer],
+        output: &mut AudioBuffer,
+        frame_range: Range<usize>,
+        sample_rate: f64,
+    ) {
+        let attack = time_coefficient(self.attack, sample_rate);
+        let release = time_coefficient(self.release, sample_rate);
+        let (left, right) = output.two_channels_mut(0, 1);
+
+        for frame in frame_range {
+            let main = stereo::input_frame(inputs, frame);
+            let key = stereo::sidechain_frame(inputs, frame).unwrap_or(main);
+
+            let level = gain_to_db(key[0].abs().max(key[1].abs()));
+            let target = self.curve(level);
+
+            // More reduction uses the attack time, less uses the release.
+            let coefficient = if target < self.reduction {
+                attack
+            } else {
+                release
+            };
+            self.reduction = target + (self.reduction - target) * coefficient;
+
+            let gain = db_to_gain(self.reduction + self.makeup);
+            left[frame] = (main[0] * gain) as f32;
+            right[frame] = (main[1] * gain) as f32;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Midi(MidiEvent::ControlChange { control, value }) = event {
+            param::apply_control(self, *control, *value);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.reduction = 0.0;
+    }
+}
+
+impl Automatable for Compressor {
+    fn params(&self) -> &'static [ParamInfo] {
+        PARAMS
+    }
+
+    fn get(&self, index: usize) -> Option<f64> {
+        let value = match index {
+            0 => self.threshold,
+            1 => self.ratio,
+            2 => self.attack,
+            3 => self.release,
+            4 => self.knee,
+            5 => self.makeup,
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        let Some(info) = PARAMS.get(index) else {
+            return;
+        };
+
+        let value = info.clamp(value);
+
+        match index {
+            0 => self.threshold = value,
+            1 => self.ratio = value,
+            2 => self.attack = value,
+            3 => self.release = value,
+            4 => self.knee = value,
+            5 => self.makeup = value,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use motif_engine::graph::evaluate_node;
+
+    const SAMPLE_RATE: f64 = 48000.0;
+
+    /// Hard-knee compressor with fast timing.
+    fn make_compressor() -> Compressor {
+        Compressor {
+            threshold: -20.0,
+            ratio: 4.0,
+            attack: 0.001,
+            release: 0.05,
+            knee: 0.0,
+            ..Compressor::new()
+        }
+    }
+
+    /// Square wave at `db`, so the peak detector sees a constant level.
+    fn square(db: f64, frames: usize) -> AudioBuffer {
+        let level = db_to_gain(db) as f32;
+        let mut buffer = AudioBuffer::new(2, frames);
+        buffer.prepare(frames);
+
+        for channel in 0..2 {
+            for (i, sample) in buffer.channel_mut(channel).iter_mut().enumerate() {
+                *sample = if (i / 50) % 2 == 0 { level } else { -level };
+            }
+        }
+
+        buffer
+    }
+
+    fn run(compressor: &mut Compressor, inputs: &[&AudioBuffer]) -> AudioBuffer {
+        let frames = inputs[0].frames();
+        let mut output = AudioBuffer::new(2, frames);
+        output.prepare(frames);
+
+        evaluate_node(compressor, inputs, &mut output, &[], SAMPLE_RATE);
+
+        output
+    }
+
+    fn last_db(buffer: &AudioBuffer) -> f64 {
+        gain_to_db(*buffer.channel(0).last().unwrap() as f64)
+    }
+
+    #[test]
+    fn below_threshold_passes_unchanged() {
+        let mut compressor = make_compressor();
+        let input = square(-30.0, 4800);
+        let output = run(&mut compressor, &[&input]);
+
+        assert_eq!(output.channel(0), input.channel(0));
+        assert_eq!(compressor.gain_reduction(), 0.0);
+    }
+
+    #[test]
+    fn settles_at_ratio_above_threshold() {
+        let mut compressor = make_compressor();
+        let output = run(&mut compressor, &[&square(-4.0, 9600)]);
+
+        // 16 dB over at 4:1 comes out 4 dB over.
+        assert!((last_db(&output) + 16.0).abs() < 0.1);
+        assert!((compressor.gain_reduction() + 12.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn attack_lets_the_transient_through() {
+        let mut compressor = Compressor {
+            attack: 0.05,
+            ..make_compressor()
+        };
+        let output = run(&mut compressor, &[&square(-4.0, 9600)]);
+        let first = gain_to_db(output.channel(0)[0] as f64);
+
+        assert!(first > -5.0);
+        assert!(last_db(&output) < -14.0);
+    }
+
+    #[test]
+    fn soft_knee_starts_below_threshold() {
+        let soft = Compressor {
+            knee: 12.0,
+            ..make_compressor()
+        };
+
+        assert_eq!(soft.curve(-27.0), 0.0);
+        assert!(soft.curve(-22.0) < 0.0);
+        assert!((soft.curve(-10.0) + 7.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn makeup_raises_output() {
+        let mut compressor = Compressor {
+            makeup: 6.0,
+            ..make_compressor()
+        };
+        let output = run(&mut compressor, &[&square(-30.0, 480)]);
+
+        assert!((last_db(&output) + 24.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sidechain_ducks_main_input() {
+        let mut compressor = make_compressor();
+        let main = square(-30.0, 9600);
+        let key = square(0.0, 9600);
+        let output = run(&mut compressor, &[&main, &key]);
+
+        // The key is 20 dB over, so the quiet main drops by 15 dB.
+        assert!((last_db(&output) + 45.0).abs() < 0.1);
+    }
+}
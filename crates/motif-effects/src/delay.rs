@@ -0,0 +1,401 @@
+use std::{f64::consts::TAU, ops::Range};
+
+use motif_core::tick::TICKS_PER_QUARTER;
+use motif_engine::{
+    buffer::AudioBuffer,
+    events::{Event, MidiEvent},
+    node::AudioNode,
+};
+
+use crate::{
+    line::DelayLine,
+    param::{self, Automatable, ParamInfo, Smoothed},
+    stereo,
+};
+
+/// Longest delay time, synced or not.
+pub const MAX_SECONDS: f64 = 2.0;
+
+const PARAMS: &[ParamInfo] = &[
+    ParamInfo {
+        name: "time",
+        min: 0.001,
+        max: MAX_SECONDS,
+        default: 0.375,
+    },
+    ParamInfo {
+        name: "feedback",
+        min: 0.0,
+        max: 0.95,
+        default: 0.4,
+    },
+    ParamInfo {
+        name: "low cut",
+        min: 20.0,
+        max: 2000.0,
+        default: 120.0,
+    },
+    ParamInfo {
+        name: "high cut",
+        min: 500.0,
+        max: 20000.0,
+        default: 6000.0,
+    },
+    ParamInfo {
+        name: "mix",
+        min: 0.0,
+        max: 1.0,
+        default: 0.3,
+    },
+    ParamInfo {
+        name: "ping pong",
+        min: 0.0,
+        max: 1.0,
+        default: 0.0,
+    },
+];
+
+/// Stereo delay. Consumes `inputs[0]` and writes channels 0 and 1.
+///
+/// Repeats run through a highpass and a lowpass in the feedback path, so
+/// each one is thinner and darker than the last. With `sync` set the delay
+/// time follows `bpm` instead of `time`.
+#[derive(Debug)]
+pub struct Delay {
+    /// Seconds between repeats when not synced.
+    pub time: f64,
+    /// Repeat length in ticks (480 per quarter), e.g. 360 for a dotted
+    /// eighth. `None` uses `time`.
+    pub sync: Option<u64>,
+    /// Host tempo, used when `sync` is set.
+    pub bpm: f64,
+    /// Level of each repeat relative to the last (0.0–0.95).
+    pub feedback: f64,
+    /// Feedback highpass cutoff in Hz.
+    pub low_cut: f64,
+    /// Feedback lowpass cutoff in Hz.
+    pub high_cut: f64,
+    /// Dry/wet balance (0.0 = dry only).
+    pub mix: f64,
+    /// Repeats bounce between left and right. The input is summed to mono
+    /// and enters on the left.
+    pub ping_pong: bool,
+    lines: [DelayLine; 2],
+    filters: [FeedbackFilter; 2],
+    smoothed_time: Smoothed,
+    smoothed_mix: Smoothed,
+}
+
+impl Default for Delay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Delay {
+    pub fn new() -> Self {
+        Self {
+            time: PARAMS[0].default,
+            sync: None,
+            bpm: 120.0,
+            feedback: PARAMS[1].default,
+            low_cut: PARAMS[2].default,
+            high_cut: PARAMS[3].default,
+            mix: PARAMS[4].default,
+            ping_pong: false,
+            lines: [
+                DelayLine::with_seconds(MAX_SECONDS),
+                DelayLine::with_seconds(MAX_SECONDS),
+            ],
+            filters: Default::default(),
+            smoothed_time: Smoothed::new(),
+            smoothed_mix: Smoothed::new(),
+        }
+    }
+
+    /// Delay time in seconds after tempo sync.
+    pub fn seconds(&self) -> f64 {
+        let seconds = match self.sync {
+            Some(ticks) => ticks as f64 / TICKS_PER_QUARTER as f64 * 60.0 / self.bpm.max(1.0),
+            None => self.time,
+        };
+
+        seconds.clamp(PARAMS[0].min, MAX_SECONDS)
+    }
+}
+
+impl AudioNode for Delay {
+    fn render(
+        &mut self,
+        inputs: &[&AudioBuffer],
+        output: &mut AudioBuffer,
+        frame_range: Range<usize>,
+        sample_rate: f64,
+    ) {
+        let seconds = self.seconds();
+        let feedback = self.feedback.clamp(0.0, PARAMS[1].max);
+        let (left, right) = output.two_channels_mut(0, 1);
+
+        for frame in frame_range {
+            let dry = stereo::input_frame(inputs, frame);
+            let delay = self.smoothed_time.next(seconds, sample_rate) * sample_rate;
+            let mix = self.smoothed_mix.next(self.mix, sample_rate);
+
+            let wet = [self.lines[0].read(delay), self.lines[1].read(delay)];
+            let mut repeats = [0.0; 2];
+
+            for (channel, filter) in self.filters.iter_mut().enumerate() {
+                repeats[channel] =
+                    filter.process(wet[channel], self.low_cut, self.high_cut, sample_rate)
+                        * feedback;
+            }
+
+            if self.ping_pong {
+                self.lines[0].push((dry[0] + dry[1]) / 2.0 + repeats[1]);
+                self.lines[1].push(repeats[0]);
+            } else {
+                self.lines[0].push(dry[0] + repeats[0]);
+                self.lines[1].push(dry[1] + repeats[1]);
+            }
+
+            left[frame] = stereo::blend(dry[0], wet[0], mix) as f32;
+            right[frame] = stereo::blend(dry[1], wet[1], mix) as f32;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Midi(MidiEvent::ControlChange { control, value }) = event {
+            param::apply_control(self, *control, *value);
+        }
+    }
+
+    fn reset(&mut self) {
+        for line in &mut self.lines {
+            line.clear();
+        }
+
+        self.filters = Default::default();
+        self.smoothed_time.reset();
+        self.smoothed_mix.reset();
+    }
+}
+
+impl Automatable for Delay {
+    fn params(&self) -> &'static [ParamInfo] {
+        PARAMS
+    }
+
+    fn get(&self, index: usize) -> Option<f64> {
+        let value = match index {
+            0 => self.time,
+            1 => self.feedback,
+            2 => self.low_cut,
+            3 => self.high_cut,
+            4 => self.mix,
+            5 => f64::from(u8::from(self.ping_pong)),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        let Some(info) = PARAMS.get(index) else {
+            return;
+        };
+
+        let value = info.clamp(value);
+
+        match index {
+            0 => self.time = value,
+            1 => self.feedback = value,
+            2 => self.low_cut = value,
+            3 => self.high_cut = value,
+            4 => self.mix = value,
+            5 => self.ping_pong = value >= 0.5,
+            _ => {}
+        }
+    }
+}
+
+/// One-pole highpass into one-pole lowpass.
+#[derive(Debug, Default, Clone, Copy)]
+struct FeedbackFilter {
+    highpass_input: f64,
+    highpass_output: f64,
+    lowpass: f64,
+}
+
+impl FeedbackFilter {
+    fn process(&mut self, input: f64, low_cut: f64, high_cut: f64, sample_rate: f64) -> f64 {
+        let rc = 1.0 / (TAU * low_cut);
+        let a = rc / (rc + 1.0 / sample_rate);
+        self.highpass_output = a * (self.highpass_output + input - self.highpass_input);
+        self.highpass_input = input;
+
+        let coefficient = 1.0 - (-TAU * high_cut.min(sample_rate * 0.45) / sample_rate).exp();
+        self.lowpass += (self.highpass_output - self.lowpass) * coefficient;
+
+        self.lowpass
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use motif_engine::{events::ScheduledEvent, graph::evaluate_node};
+    use wmidi::{ControlFunction, U7};
+
+    const SAMPLE_RATE: f64 = 48000.0;
+
+    fn impulse(frames: usize) -> AudioBuffer {
+        let mut input = AudioBuffer::new(2, frames);
+        input.prepare(frames);
+        input.channel_mut(0)[0] = 1.0;
+        input.channel_mut(1)[0] = 1.0;
+
+        input
+    }
+
+    /// Wet-only delay with no feedback filtering to speak of.
+    fn make_delay() -> Delay {
+        Delay {
+            time: 0.01,
+            mix: 1.0,
+            low_cut: 20.0,
+            high_cut: 20000.0,
+            ..Delay::new()
+        }
+    }
+
+    fn run(delay: &mut Delay, input: &AudioBuffer) -> AudioBuffer {
+        let mut output = AudioBuffer::new(2, input.frames());
+        output.prepare(input.frames());
+
+        evaluate_node(delay, &[input], &mut output, &[], SAMPLE_RATE);
+
+        output
+    }
+
+    fn loudest(samples: &[f32]) -> usize {
+        samples
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+            .map(|(i, _)| i)
+            .unwrap()
+    }
+
+    #[test]
+    fn first_repeat_lands_on_delay_time() {
+        let mut delay = make_delay();
+        let output = run(&mut delay, &impulse(1000));
+
+        assert_eq!(loudest(&output.channel(0)[..700]), 480);
+        assert_eq!(loudest(&output.channel(1)[..700]), 480);
+    }
+
+    #[test]
+    fn feedback_repeats_decay() {
+        let mut delay = make_delay();
+        let output = run(&mut delay, &impulse(1500));
+        let left = output.channel(0);
+
+        let first = left[470..490].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        let second = left[950..970].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+
+        assert!(second > 0.0);
+        assert!(second < first * 0.5);
+    }
+
+    #[test]
+    fn sync_follows_tempo() {
+        let mut delay = Delay {
+            sync: Some(TICKS_PER_QUARTER / 2),
+            bpm: 120.0,
+            ..make_delay()
+        };
+
+        // An eighth at 120 BPM is a quarter second.
+        assert_eq!(delay.seconds(), 0.25);
+
+        let output = run(&mut delay, &impulse(16000));
+        assert_eq!(loudest(output.channel(0)), 12000);
+    }
+
+    #[test]
+    fn ping_pong_alternates_sides() {
+        let mut delay = Delay {
+            ping_pong: true,
+            feedback: 0.9,
+            ..make_delay()
+        };
+        let output = run(&mut delay, &impulse(1200));
+        let (left, right) = (output.channel(0), output.channel(1));
+
+        assert!(left[480].abs() > 0.1);
+        assert!(right[480].abs() < 1e-3);
+        assert!(right[960].abs() > 0.1);
+        assert!(left[960].abs() < 1e-3);
+    }
+
+    #[test]
+    fn high_cut_darkens_repeats() {
+        let mut bright = make_delay();
+        let mut dark = Delay {
+            high_cut: 500.0,
+            ..make_delay()
+        };
+
+        let bright = run(&mut bright, &impulse(1000));
+        let dark = run(&mut dark, &impulse(1000));
+
+        // A dulled impulse is smeared, so its peak drops.
+        assert!(dark.channel(0)[960].abs() < bright.channel(0)[960].abs() * 0.5);
+    }
+
+    #[test]
+    fn dry_mix_passes_input() {
+        let mut delay = Delay {
+            mix: 0.0,
+            ..make_delay()
+        };
+        let input = impulse(1000);
+        let output = run(&mut delay, &input);
+
+        assert_eq!(output.channel(0), input.channel(0));
+    }
+
+    #[test]
+    fn controller_sets_feedback() {
+        let mut delay = make_delay();
+        let mut output = AudioBuffer::new(2, 16);
+        output.prepare(16);
+
+        let events = [ScheduledEvent {
+            sample_offset: 0,
+            event: Event::Midi(MidiEvent::ControlChange {
+                control: ControlFunction(U7::try_from(71).unwrap()),
+                value: U7::MIN,
+            }),
+        }];
+        evaluate_node(&mut delay, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert_eq!(delay.get(1), Some(0.0));
+    }
+
+    #[test]
+    fn reset_clears_tail() {
+        let mut delay = make_delay();
+        let input = impulse(100);
+        run(&mut delay, &input);
+
+        delay.reset();
+        let mut silence = AudioBuffer::new(2, 1000);
+        silence.prepare(1000);
+
+        let output = run(&mut delay, &silence);
+        assert!(output.channel(0).iter().all(|&s| s == 0.0));
+    }
+}
@@ -0,0 +1,606 @@
+use std::ops::Range;
+
+use motif_engine::{
+    biquad::{self, Biquad, Coefficients, Kind, Ramp},
+    buffer::AudioBuffer,
+    events::{Event, MidiEvent},
+    node::AudioNode,
+};
+
+use crate::{
+    param::{self, Automatable, ParamInfo},
+    stereo,
+};
+
+/// Number of bands in an `Equalizer`.
+pub const BANDS: usize = 6;
+
+/// Sections in the steepest cut (48 dB/octave).
+const MAX_SECTIONS: usize = 4;
+
+/// Time for coefficients to glide to a new setting.
+const RAMP_SECONDS: f64 = 0.01;
+
+/// Parameters per band: frequency, gain, q.
+const BAND_PARAMS: usize = 3;
+
+const DEFAULT_BANDS: [Band; BANDS] = [
+    Band {
+        kind: BandKind::LowCut(Slope::Db24),
+        frequency: 30.0,
+        gain: 0.0,
+        q: 0.707,
+        enabled: false,
+    },
+    Band {
+        kind: BandKind::LowShelf,
+        frequency: 100.0,
+        gain: 0.0,
+        q: 0.707,
+        enabled: true,
+    },
+    Band {
+        kind: BandKind::Peak,
+        frequency: 400.0,
+        gain: 0.0,
+        q: 1.0,
+        enabled: true,
+    },
+    Band {
+        kind: BandKind::Peak,
+        frequency: 2500.0,
+        gain: 0.0,
+        q: 1.0,
+        enabled: true,
+    },
+    Band {
+        kind: BandKind::HighShelf,
+        frequency: 8000.0,
+        gain: 0.0,
+        q: 0.707,
+        enabled: true,
+    },
+    Band {
+        kind: BandKind::HighCut(Slope::Db24),
+        frequency: 18000.0,
+        gain: 0.0,
+        q: 0.707,
+        enabled: false,
+    },
+];
+
+const PARAMS: &[ParamInfo] = &band_params();
+
+/// Frequency, gain and q for each band in turn, so band `b`'s parameters
+/// start at index `b * 3`.
+const fn band_params() -> [ParamInfo; BANDS * BAND_PARAMS] {
+    let mut params = [ParamInfo {
+        name: "",
+        min: 0.0,
+        max: 0.0,
+        default: 0.0,
+    }; BANDS * BAND_PARAMS];
+    let mut band = 0;
+
+    while band < BANDS {
+        let defaults = &DEFAULT_BANDS[band];
+
+        params[band * BAND_PARAMS] = ParamInfo {
+            name: "frequency",
+            min: 20.0,
+            max: 20000.0,
+            default: defaults.frequency,
+        };
+        params[band * BAND_PARAMS + 1] = ParamInfo {
+            name: "gain",
+            min: -24.0,
+            max: 24.0,
+            default: defaults.gain,
+        };
+        params[band * BAND_PARAMS + 2] = ParamInfo {
+            name: "q",
+            min: 0.1,
+            max: 18.0,
+            default: defaults.q,
+        };
+
+        band += 1;
+    }
+
+    params
+}
+
+/// Steepness of a cut band.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slope {
+    Db12,
+    Db24,
+    Db36,
+    Db48,
+}
+
+impl Slope {
+    /// Second-order sections cascaded for this slope.
+    pub fn sections(self) -> usize {
+        match self {
+            Slope::Db12 => 1,
+            Slope::Db24 => 2,
+            Slope::Db36 => 3,
+            Slope::Db48 => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BandKind {
+    /// Butterworth highpass. Ignores gain and q.
+    LowCut(Slope),
+    LowShelf,
+    Peak,
+    HighShelf,
+    /// Butterworth lowpass. Ignores gain and q.
+    HighCut(Slope),
+}
+
+/// One band of the equalizer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Band {
+    pub kind: BandKind,
+    /// Centre or corner frequency in Hz.
+    pub frequency: f64,
+    /// Boost or cut in dB, for shelves and peaks.
+    pub gain: f64,
+    /// Bandwidth of peaks and corner shape of shelves.
+    pub q: f64,
+    /// A disabled band passes audio unchanged.
+    pub enabled: bool,
+}
+
+impl Band {
+    /// Analytic gain at `frequency`, as a linear amplitude. For drawing the
+    /// band's curve.
+    pub fn magnitude(&self, frequency: f64, sample_rate: f64) -> f64 {
+        self.sections(sample_rate)
+            .iter()
+            .map(|c| c.magnitude(frequency, sample_rate))
+            .product()
+    }
+
+    /// Coefficients for each section, identity where unused.
+    fn sections(&self, sample_rate: f64) -> [Coefficients; MAX_SECTIONS] {
+        let mut sections = [Coefficients::IDENTITY; MAX_SECTIONS];
+
+        if !self.enabled {
+            return sections;
+        }
+
+        let frequency = PARAMS[0].clamp(self.frequency);
+        let gain = PARAMS[1].clamp(self.gain);
+        let q = PARAMS[2].clamp(self.q);
+
+        let (kind, slope) = match self.kind {
+            BandKind::LowCut(slope) => (Kind::Highpass, slope),
+            BandKind::HighCut(slope) => (Kind::Lowpass, slope),
+            BandKind::LowShelf => {
+                sections[0] = Coefficients::new(Kind::LowShelf, frequency, q, gain, sample_rate);
+                return sections;
+            }
+            BandKind::Peak => {
+                sections[0] = Coefficients::new(Kind::Peak, frequency, q, gain, sample_rate);
+                return sections;
+            }
+            BandKind::HighShelf => {
+                sections[0] = Coefficients::new(Kind::HighShelf, frequency, q, gain, sample_rate);
+                return sections;
+            }
+        };
+
+        let count = slope.sections();
+
+        for (i, section) in sections.iter_mut().take(count).enumerate() {
+            let q = biquad::butterworth_q(i, count);
+            *section = Coefficients::new(kind, frequency, q, 0.0, sample_rate);
+        }
+
+        sections
+    }
+}
+
+/// Multi-band parametric EQ: a low cut, two shelves, two peaks and a high
+/// cut by default, each band free to take any kind. Filters `inputs[0]` and
+/// writes channels 0 and 1.
+///
+/// Changing a band glides its coefficients over 10 ms rather than jumping,
+/// so sweeps and automation don't click. Only the first ten parameters are
+/// reachable by controller (bands 1–3 and band 4's frequency).
+#[derive(Debug)]
+pub struct Equalizer {
+    pub bands: [Band; BANDS],
+    ramps: [[Ramp; MAX_SECTIONS]; BANDS],
+    /// Per-section filter state for the left and right sides.
+    filters: [[[Biquad; 2]; MAX_SECTIONS]; BANDS],
+    /// False until the first render, which jumps straight to the bands.
+    primed: bool,
+}
+
+impl Default for Equalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Equalizer {
+    /// Every band at 0 dB with the cuts disabled, so the signal is unchanged.
+    pub fn new() -> Self {
+        Self {
+            bands: DEFAULT_BANDS,
+            ramps: [[Ramp::new(Coefficients::IDENTITY); MAX_SECTIONS]; BANDS],
+            filters: [[[Biquad::default(); 2]; MAX_SECTIONS]; BANDS],
+            primed: false,
+        }
+    }
+
+    /// Analytic gain of all bands together at `frequency`, as a linear
+    /// amplitude. For drawing the response curve.
+    pub fn magnitude(&self, frequency: f64, sample_rate: f64) -> f64 {
+        self.bands
+            .iter()
+            .map(|band| band.magnitude(frequency, sample_rate))
+            .product()
+    }
+
+    /// Point each section's ramp at the bands' current settings.
+    fn retarget(&mut self, sample_rate: f64) {
+        let samples = if self.primed {
+            (RAMP_SECONDS * sample_rate).round() as usize
+        } else {
+            0
+        };
+
+        for (band, ramps) in self.bands.iter().zip(&mut self.ramps) {
+            for (ramp, target) in ramps.iter_mut().zip(band.sections(sample_rate)) {
+                ramp.set(target, samples);
+            }
+        }
+
+        self.primed = true;
+    }
+}
+
+impl AudioNode for Equalizer {
+    fn render(
+        &mut self,
+        inputs: &[&AudioBuffer],
+        output: &mut AudioBuffer,
+        frame_range: Range<usize>,
+        sample_rate: f64,
+    ) {
+        self.retarget(sample_rate);
+
+        let (left, right) = output.two_channels_mut(0, 1);
+
+        for frame in frame_range {
+            let mut signal = stereo::input_frame(inputs, frame);
+
+            for (ramps, filters) in self.ramps.iter_mut().zip(&mut self.filters) {
+                for (ramp, sides) in ramps.iter_mut().zip(filters) {
+                    // Unused sections cost nothing once they've settled.
+                    if ramp.is_settled() && *ramp.current() == Coefficients::IDENTITY {
+                        sides[0].reset();
+                        sides[1].reset();
+                        continue;
+                    }
+
+                    let coefficients = ramp.tick();
+
+                    for (sample, filter) in signal.iter_mut().zip(sides) {
+                        *sample = filter.process(*sample, &coefficients);
+                    }
+                }
+            }
+
+            left[frame] = signal[0] as f32;
+            right[frame] = signal[1] as f32;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Midi(MidiEvent::ControlChange { control, value }) = event {
+            param::apply_control(self, *control, *value);
+        }
+    }
+
+    fn reset(&mut self) {
+        for sides in self.filters.iter_mut().flatten() {
+            for filter in sides {
+                filter.reset();
+            }
+        }
+
+        self.primed = false;
+    }
+}
+
+impl Automatable for Equalizer {
+    fn params(&self) -> &'static [ParamInfo] {
+        PARAMS
+    }
+
+    fn get(&self, index: usize) -> Option<f64> {
+        let band = self.bands.get(index / BAND_PARAMS)?;
+
+        let value = match index % BAND_PARAMS {
+            0 => band.frequency,
+            1 => band.gain,
+            _ => band.q,
+        };
+
+        Some(value)
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        let Some(info) = PARAMS.get(index) else {
+            return;
+        };
+
+        let value = info.clamp(value);
+        let band = &mut self.bands[index / BAND_PARAMS];
+
+        match index % BAND_PARAMS {
+            0 => band.frequency = value,
+            1 => band.gain = value,
+            _ => band.q = value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::f64::consts::TAU;
+
+    use motif_engine::graph::evaluate_node;
+
+    const SAMPLE_RATE: f64 = 48000.0;
+
+    /// Long enough for every test band's impulse response to die away.
+    const IMPULSE_FRAMES: usize = 16384;
+
+    fn db(gain: f64) -> f64 {
+        20.0 * gain.log10()
+    }
+
+    fn run(eq: &mut Equalizer, input: &AudioBuffer) -> AudioBuffer {
+        let mut output = AudioBuffer::new(2, input.frames());
+        output.prepare(input.frames());
+
+        evaluate_node(eq, &[input], &mut output, &[], SAMPLE_RATE);
+
+        output
+    }
+
+    /// Render an impulse through `eq` and measure the gain in dB at each
+    /// frequency with a single-bin DFT of the response.
+    fn response(eq: &mut Equalizer, frequencies: &[f64]) -> Vec<f64> {
+        let mut impulse = AudioBuffer::new(2, IMPULSE_FRAMES);
+        impulse.prepare(IMPULSE_FRAMES);
+        impulse.channel_mut(0)[0] = 1.0;
+        impulse.channel_mut(1)[0] = 1.0;
+
+        let output = run(eq, &impulse);
+
+        frequencies
+            .iter()
+            .map(|&frequency| {
+                let omega = TAU * frequency / SAMPLE_RATE;
+                let (re, im) = output.channel(0).iter().enumerate().fold(
+                    (0.0, 0.0),
+                    |(re, im), (n, &sample)| {
+                        let (sin, cos) = (omega * n as f64).sin_cos();
+                        (re + sample as f64 * cos, im - sample as f64 * sin)
+                    },
+                );
+
+                db(f64::hypot(re, im))
+            })
+            .collect()
+    }
+
+    fn sine(frequency: f64, frames: usize) -> AudioBuffer {
+        let mut buffer = AudioBuffer::new(2, frames);
+        buffer.prepare(frames);
+
+        for channel in 0..2 {
+            for (i, sample) in buffer.channel_mut(channel).iter_mut().enumerate() {
+                *sample = (TAU * frequency * i as f64 / SAMPLE_RATE).sin() as f32;
+            }
+        }
+
+        buffer
+    }
+
+    /// Ideal Butterworth response in dB for a cut of `sections` sections,
+    /// `ratio` being the distance from the corner into the stopband.
+    fn butterworth_db(ratio: f64, sections: usize) -> f64 {
+        -10.0 * (1.0 + ratio.powi(4 * sections as i32)).log10()
+    }
+
+    #[test]
+    fn defaults_are_flat() {
+        let mut eq = Equalizer::new();
+
+        for gain in response(&mut eq, &[30.0, 1000.0, 15000.0]) {
+            assert!(gain.abs() < 0.01, "{gain} dB");
+        }
+    }
+
+    #[test]
+    fn peak_boosts_its_band_only() {
+        let mut eq = Equalizer::new();
+        eq.bands[3].frequency = 1000.0;
+        eq.bands[3].gain = 6.0;
+
+        let gains = response(&mut eq, &[1000.0, 50.0, 15000.0]);
+
+        assert!((gains[0] - 6.0).abs() < 0.05);
+        assert!(gains[1].abs() < 0.1);
+        assert!(gains[2].abs() < 0.1);
+    }
+
+    #[test]
+    fn shelves_lift_and_cut_their_ends() {
+        let mut eq = Equalizer::new();
+        eq.bands[1].gain = 6.0;
+        eq.bands[4].gain = -6.0;
+
+        let gains = response(&mut eq, &[20.0, 1000.0, 20000.0]);
+
+        assert!((gains[0] - 6.0).abs() < 0.2);
+        assert!(gains[1].abs() < 0.2);
+        assert!((gains[2] + 6.0).abs() < 0.2);
+    }
+
+    #[test]
+    fn low_cut_slopes() {
+        for slope in [Slope::Db12, Slope::Db24, Slope::Db36, Slope::Db48] {
+            let mut eq = Equalizer::new();
+            eq.bands[0] = Band {
+                kind: BandKind::LowCut(slope),
+                frequency: 1000.0,
+                enabled: true,
+                ..eq.bands[0]
+            };
+
+            let gains = response(&mut eq, &[1000.0, 250.0, 8000.0]);
+
+            assert!((gains[0] + 3.01).abs() < 0.05, "{slope:?}");
+            assert!(
+                (gains[1] - butterworth_db(4.0, slope.sections())).abs() < 0.5,
+                "{slope:?}: {} dB",
+                gains[1]
+            );
+            assert!(gains[2].abs() < 0.05, "{slope:?}");
+        }
+    }
+
+    #[test]
+    fn high_cut_slopes() {
+        for slope in [Slope::Db12, Slope::Db48] {
+            let mut eq = Equalizer::new();
+            eq.bands[5] = Band {
+                kind: BandKind::HighCut(slope),
+                frequency: 1000.0,
+                enabled: true,
+                ..eq.bands[5]
+            };
+
+            let gains = response(&mut eq, &[1000.0, 4000.0, 100.0]);
+
+            assert!((gains[0] + 3.01).abs() < 0.05, "{slope:?}");
+            // The bilinear transform steepens a lowpass as it nears Nyquist.
+            assert!(
+                gains[1] < butterworth_db(4.0, slope.sections()),
+                "{slope:?}"
+            );
+            assert!(gains[2].abs() < 0.05, "{slope:?}");
+        }
+    }
+
+    #[test]
+    fn rendered_response_matches_analytic_curve() {
+        let mut eq = Equalizer::new();
+        eq.bands[0].enabled = true;
+        eq.bands[1].gain = -4.0;
+        eq.bands[2].gain = 9.0;
+        eq.bands[2].q = 4.0;
+        eq.bands[3].gain = -12.0;
+        eq.bands[4].gain = 3.0;
+
+        let frequencies = [40.0, 100.0, 400.0, 1000.0, 2500.0, 8000.0, 16000.0];
+        let gains = response(&mut eq, &frequencies);
+
+        for (frequency, gain) in frequencies.iter().zip(gains) {
+            let expected = db(eq.magnitude(*frequency, SAMPLE_RATE));
+            assert!((gain - expected).abs() < 0.01, "{frequency} Hz");
+        }
+    }
+
+    #[test]
+    fn changes_glide_instead_of_jumping() {
+        let input = sine(1000.0, 9600);
+        let run_split = |gain: f64| {
+            let mut eq = Equalizer::new();
+            eq.bands[3].frequency = 1000.0;
+
+            let mut output = AudioBuffer::new(2, 9600);
+            output.prepare(9600);
+            eq.render(&[&input], &mut output, 0..4800, SAMPLE_RATE);
+            eq.bands[3].gain = gain;
+            eq.render(&[&input], &mut output, 4800..9600, SAMPLE_RATE);
+
+            output
+        };
+
+        let steady = run_split(0.0);
+        let boosted = run_split(12.0);
+        let (steady, boosted) = (steady.channel(0), boosted.channel(0));
+
+        // Identical at the change, drifting apart a little at a time.
+        assert_eq!(steady[4800], boosted[4800]);
+        for i in 4801..4810 {
+            assert!((boosted[i] - steady[i]).abs() < 0.05);
+        }
+
+        // Fully boosted once the glide is over.
+        let peak = boosted[8000..].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((db(peak as f64) - 12.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn first_render_starts_at_the_settings() {
+        let mut eq = Equalizer::new();
+        eq.bands[3].frequency = 1000.0;
+        eq.bands[3].gain = 12.0;
+
+        let output = run(&mut eq, &sine(1000.0, 4800));
+        let peak = output.channel(0)[..480]
+            .iter()
+            .fold(0.0f32, |m, s| m.max(s.abs()));
+
+        // Boosted from the start rather than gliding up from flat.
+        assert!(db(peak as f64) > 11.0);
+    }
+
+    #[test]
+    fn reset_clears_ringing() {
+        let mut eq = Equalizer::new();
+        eq.bands[2].gain = 18.0;
+        eq.bands[2].q = 18.0;
+
+        let mut impulse = AudioBuffer::new(2, 64);
+        impulse.prepare(64);
+        impulse.channel_mut(0)[0] = 1.0;
+        run(&mut eq, &impulse);
+
+        eq.reset();
+        let mut silence = AudioBuffer::new(2, 64);
+        silence.prepare(64);
+        let output = run(&mut eq, &silence);
+
+        assert!(output.channel(0).iter().all(|s| *s == 0.0));
+    }
+
+    #[test]
+    fn params_follow_band_order() {
+        let mut eq = Equalizer::new();
+        eq.set(4, 40.0);
+        eq.set(9, 3000.0);
+
+        assert_eq!(eq.bands[1].gain, 24.0);
+        assert_eq!(eq.bands[3].frequency, 3000.0);
+        assert_eq!(eq.get(9), Some(3000.0));
+        assert_eq!(eq.get(BANDS * 3), None);
+        assert_eq!(eq.params()[12].default, 8000.0);
+    }
+}
@@ -0,0 +1,26 @@
+/// Decibels to linear amplitude.
+pub fn db_to_gain(db: f64) -> f64 {
+    10.0_f64.powf(db / 20.0)
+}
+
+/// Linear amplitude to decibels. Silence floors at -200 dB instead of -inf.
+pub fn gain_to_db(gain: f64) -> f64 {
+    20.0 * gain.abs().max(1e-10).log10()
+}
+
+/// One-pole smoothing coefficient for a time constant in seconds.
+pub fn time_coefficient(seconds: f64, sample_rate: f64) -> f64 {
+    (-1.0 / (seconds.max(1e-6) * sample_rate)).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn db_round_trips() {
+        assert!((db_to_gain(-6.0) - 0.501).abs() < 1e-3);
+        assert!((gain_to_db(db_to_gain(-18.0)) + 18.0).abs() < 1e-9);
+        assert_eq!(gain_to_db(0.0), -200.0);
+    }
+}
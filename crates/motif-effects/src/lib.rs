@@ -0,0 +1,10 @@
+pub mod chorus;
+pub mod compressor;
+pub mod delay;
+pub mod eq;
+pub mod gain;
+pub mod limiter;
+pub mod line;
+pub mod param;
+pub mod reverb;
+pub mod stereo;
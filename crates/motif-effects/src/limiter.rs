@@ -0,0 +1,392 @@
+use std::ops::Range;
+
+use motif_engine::{
+    buffer::AudioBuffer,
+    events::{Event, MidiEvent},
+    node::AudioNode,
+};
+
+use crate::{
+    gain::{db_to_gain, gain_to_db, time_coefficient},
+    line::{DelayLine, MAX_SAMPLE_RATE},
+    param::{self, Automatable, ParamInfo},
+    stereo,
+};
+
+/// How far ahead the limiter looks. Gain reduction ramps in over this long,
+/// so peaks are caught without a click.
+pub const LOOKAHEAD_SECONDS: f64 = 0.005;
+
+const PARAMS: &[ParamInfo] = &[
+    ParamInfo {
+        name: "ceiling",
+        min: -24.0,
+        max: 0.0,
+        default: -0.3,
+    },
+    ParamInfo {
+        name: "release",
+        min: 0.001,
+        max: 1.0,
+        default: 0.05,
+    },
+];
+
+/// Stereo-linked lookahead brickwall limiter. Limits `inputs[0]` and writes
+/// channels 0 and 1, delayed by `latency()`.
+///
+/// Without a sidechain nothing leaves above `ceiling`. With `inputs[1]`
+/// connected the key signal drives the gain instead, which ducks the main
+/// input but no longer guarantees its peaks.
+#[derive(Debug)]
+pub struct Limiter {
+    /// Highest output level in dB.
+    pub ceiling: f64,
+    /// Seconds for gain reduction to let go.
+    pub release: f64,
+    lines: [DelayLine; 2],
+    minimum: SlidingMin,
+    /// Envelope values over the last window, averaged into the gain.
+    ramp: Vec<f64>,
+    ramp_position: usize,
+    ramp_sum: f64,
+    envelope: f64,
+    /// Lookahead in samples at the current rate. Zero until the first render.
+    window: usize,
+}
+
+impl Default for Limiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Limiter {
+    pub fn new() -> Self {
+        let capacity = (LOOKAHEAD_SECONDS * MAX_SAMPLE_RATE).ceil() as usize + 1;
+
+        Self {
+            ceiling: PARAMS[0].default,
+            release: PARAMS[1].default,
+            lines: [DelayLine::new(capacity + 1), DelayLine::new(capacity + 1)],
+            minimum: SlidingMin::new(capacity + 1),
+            ramp: vec![1.0; capacity],
+            ramp_position: 0,
+            ramp_sum: 0.0,
+            envelope: 1.0,
+            window: 0,
+        }
+    }
+
+    /// Output delay in samples at `sample_rate`.
+    pub fn latency(&self, sample_rate: f64) -> usize {
+        Self::window_for(sample_rate, self.ramp.len()) - 1
+    }
+
+    /// Current gain reduction in dB, for metering. Zero or negative.
+    pub fn gain_reduction(&self) -> f64 {
+        if self.window == 0 {
+            return 0.0;
+        }
+
+        gain_to_db(self.ramp_sum / self.window as f64).min(0.0)
+    }
+
+    fn window_for(sample_rate: f64, capacity: usize) -> usize {
+        ((LOOKAHEAD_SECONDS * sample_rate).round() as usize).clamp(1, capacity)
+    }
+
+    /// Start over with a window of `window` samples.
+    fn configure(&mut self, window: usize) {
+        self.window = window;
+        self.clear();
+    }
+
+    fn clear(&mut self) {
+        for line in &mut self.lines {
+            line.clear();
+        }
+
+        self.minimum.clear();
+        self.ramp.fill(1.0);
+        self.ramp_position = 0;
+        self.ramp_sum = self.window as f64;
+        self.envelope = 1.0;
+    }
+}
+
+impl AudioNode for Limiter {
+    fn render(
+        &mut self,
+        inputs: &[&AudioBuffer],
+        output: &mut AudioBuffer,
+        frame_range: Range<usize>,
+        sample_rate: f64,
+    ) {
+        let window = Self::window_for(sample_rate, self.ramp.len());
+
+        if window != self.window {
+            self.configure(window);
+        }
+
+        let ceiling = db_to_gain(PARAMS[0].clamp(self.ceiling));
+        let release = time_coefficient(self.release, sample_rate);
+        let sidechain = inputs.len() > 1;
+        let (left, right) = output.two_channels_mut(0, 1);
+
+        for frame in frame_range {
+            let main = stereo::input_frame(inputs, frame);
+            let key = stereo::sidechain_frame(inputs, frame).unwrap_or(main);
+
+            let peak = key[0].abs().max(key[1].abs());
+            let required = if peak > ceiling { ceiling / peak } else { 1.0 };
+
+            // Hold the lowest gain the window needs, then release from it.
+            let held = self.minimum.push(required, window);
+            self.envelope = if held < self.envelope {
+                held
+            } else {
+                held + (self.envelope - held) * release
+            };
+
+            // Averaging over the window ramps the gain down so it reaches
+            // each peak's level exactly as that peak leaves the delay.
+            self.ramp_sum += self.envelope - self.ramp[self.ramp_position];
+            self.ramp[self.ramp_position] = self.envelope;
+            self.ramp_position = (self.ramp_position + 1) % window;
+            let gain = self.ramp_sum / window as f64;
+
+            let mut out = [0.0; 2];
+            for (side, line) in self.lines.iter_mut().enumerate() {
+                // Read after writing, so a tap of one is no delay.
+                line.push(main[side]);
+                out[side] = line.tap(window) * gain;
+
+                // Catches rounding in the running sum.
+                if !sidechain {
+                    out[side] = out[side].clamp(-ceiling, ceiling);
+                }
+            }
+
+            left[frame] = out[0] as f32;
+            right[frame] = out[1] as f32;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Midi(MidiEvent::ControlChange { control, value }) = event {
+            param::apply_control(self, *control, *value);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.clear();
+    }
+}
+
+impl Automatable for Limiter {
+    fn params(&self) -> &'static [ParamInfo] {
+        PARAMS
+    }
+
+    fn get(&self, index: usize) -> Option<f64> {
+        let value = match index {
+            0 => self.ceiling,
+            1 => self.release,
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        let Some(info) = PARAMS.get(index) else {
+            return;
+        };
+
+        let value = info.clamp(value);
+
+        match index {
+            0 => self.ceiling = value,
+            1 => self.release = value,
+            _ => {}
+        }
+    }
+}
+
+/// Minimum over a sliding window in O(1) per sample: a monotonic queue of
+/// (sample index, value) kept in a fixed ring.
+#[derive(Debug)]
+struct SlidingMin {
+    entries: Vec<(u64, f64)>,
+    head: usize,
+    len: usize,
+    count: u64,
+}
+
+impl SlidingMin {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: vec![(0, 0.0); capacity],
+            head: 0,
+            len: 0,
+            count: 0,
+        }
+    }
+
+    /// Add a value and return the minimum of the last `window` values.
+    fn push(&mut self, value: f64, window: usize) -> f64 {
+        let capacity = self.entries.len();
+
+        // Anything at least as large as the new value can never be the
+        // minimum again.
+        while self.len > 0 && self.entries[(self.head + self.len - 1) % capacity].1 >= value {
+            self.len -= 1;
+        }
+
+        self.entries[(self.head + self.len) % capacity] = (self.count, value);
+        self.len += 1;
+
+        while self.entries[self.head].0 + window as u64 <= self.count {
+            self.head = (self.head + 1) % capacity;
+            self.len -= 1;
+        }
+
+        self.count += 1;
+
+        self.entries[self.head].1
+    }
+
+    fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+        self.count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use motif_engine::graph::evaluate_node;
+
+    const SAMPLE_RATE: f64 = 48000.0;
+
+    fn make_limiter() -> Limiter {
+        Limiter {
+            ceiling: -6.0,
+            ..Limiter::new()
+        }
+    }
+
+    fn buffer_from(mut samples: impl FnMut(usize) -> f32, frames: usize) -> AudioBuffer {
+        let mut buffer = AudioBuffer::new(2, frames);
+        buffer.prepare(frames);
+
+        for channel in 0..2 {
+            for (i, sample) in buffer.channel_mut(channel).iter_mut().enumerate() {
+                *sample = samples(i);
+            }
+        }
+
+        buffer
+    }
+
+    fn run(limiter: &mut Limiter, inputs: &[&AudioBuffer]) -> AudioBuffer {
+        let frames = inputs[0].frames();
+        let mut output = AudioBuffer::new(2, frames);
+        output.prepare(frames);
+
+        evaluate_node(limiter, inputs, &mut output, &[], SAMPLE_RATE);
+
+        output
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0, |m, s| m.max(s.abs()))
+    }
+
+    #[test]
+    fn quiet_signal_is_only_delayed() {
+        let mut limiter = make_limiter();
+        let input = buffer_from(|i| ((i % 7) as f32 - 3.0) * 0.05, 2000);
+        let output = run(&mut limiter, &[&input]);
+        let latency = limiter.latency(SAMPLE_RATE);
+
+        assert_eq!(latency, 239);
+        for i in latency..2000 {
+            assert!((output.channel(0)[i] - input.channel(0)[i - latency]).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn nothing_passes_the_ceiling() {
+        let ceiling = db_to_gain(-6.0) as f32;
+        let mut limiter = make_limiter();
+
+        // Loud noise with sudden spikes.
+        let mut state = 1u32;
+        let input = buffer_from(
+            |i| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let noise = (state >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0;
+
+                if i % 1000 == 500 { 8.0 } else { noise * 1.5 }
+            },
+            9600,
+        );
+        let output = run(&mut limiter, &[&input]);
+
+        assert!(peak(output.channel(0)) <= ceiling);
+        assert!(peak(output.channel(1)) <= ceiling);
+    }
+
+    #[test]
+    fn gain_ramps_in_before_a_transient() {
+        let mut limiter = make_limiter();
+        let input = buffer_from(|i| if i == 1000 { 1.0 } else { 0.1 }, 2000);
+        let output = run(&mut limiter, &[&input]);
+        let latency = limiter.latency(SAMPLE_RATE);
+        let left = output.channel(0);
+
+        // The spike comes out at the ceiling, and the quiet signal just
+        // before it is already turned down.
+        assert!((left[1000 + latency] - db_to_gain(-6.0) as f32).abs() < 1e-4);
+        assert!(left[1000 + latency - 10] < 0.06);
+        assert!(left[1000 + latency - 10] > 0.05);
+    }
+
+    #[test]
+    fn gain_recovers_after_release() {
+        let mut limiter = make_limiter();
+        let input = buffer_from(|i| if i < 100 { 1.0 } else { 0.1 }, 48000);
+        let output = run(&mut limiter, &[&input]);
+
+        assert!((output.channel(0)[47999] - 0.1).abs() < 1e-4);
+        assert!(limiter.gain_reduction() > -0.01);
+    }
+
+    #[test]
+    fn sidechain_ducks_main_input() {
+        let mut limiter = make_limiter();
+        let main = buffer_from(|_| 0.25, 4800);
+        let key = buffer_from(|_| 1.0, 4800);
+        let output = run(&mut limiter, &[&main, &key]);
+
+        // The key is 6 dB over, so the main drops by 6 dB.
+        assert!((output.channel(0)[4799] - 0.25 * db_to_gain(-6.0) as f32).abs() < 1e-4);
+    }
+
+    #[test]
+    fn sliding_min_forgets_old_values() {
+        let mut minimum = SlidingMin::new(4);
+        let window = 3;
+
+        let seen: Vec<f64> = [5.0, 1.0, 4.0, 3.0, 6.0, 7.0]
+            .into_iter()
+            .map(|value| minimum.push(value, window))
+            .collect();
+
+        assert_eq!(seen, [5.0, 1.0, 1.0, 1.0, 3.0, 3.0]);
+    }
+}
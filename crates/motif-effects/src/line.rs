@@ -0,0 +1,112 @@
+/// Highest sample rate delay lines are sized for. Effects allocate at
+/// construction, before the stream's rate is known.
+pub const MAX_SAMPLE_RATE: f64 = 192000.0;
+
+/// Circular delay line with fractional reads. Allocated once at
+/// construction; reading and writing are real-time safe.
+#[derive(Debug, Clone)]
+pub struct DelayLine {
+    buffer: Vec<f32>,
+    write: usize,
+}
+
+impl DelayLine {
+    /// Holds up to `capacity - 1` samples of delay. Panics if `capacity` is below 2.
+    pub fn new(capacity: usize) -> Self {
+        assert!(
+            capacity >= 2,
+            "DelayLine needs room for at least one sample of delay"
+        );
+
+        Self {
+            buffer: vec![0.0; capacity],
+            write: 0,
+        }
+    }
+
+    /// Room for `seconds` of delay at up to `MAX_SAMPLE_RATE`.
+    pub fn with_seconds(seconds: f64) -> Self {
+        Self::new((seconds * MAX_SAMPLE_RATE).ceil() as usize + 2)
+    }
+
+    /// Longest delay `read()` can reach, in samples.
+    pub fn max_delay(&self) -> usize {
+        self.buffer.len() - 1
+    }
+
+    /// Sample written `delay` samples ago, interpolated linearly.
+    /// Clamped to 1..=`max_delay()`.
+    pub fn read(&self, delay: f64) -> f64 {
+        let delay = delay.clamp(1.0, self.max_delay() as f64);
+        let whole = delay.floor() as usize;
+        let fraction = delay - whole as f64;
+
+        let a = self.at(whole);
+        let b = self.at((whole + 1).min(self.max_delay()));
+
+        a + (b - a) * fraction
+    }
+
+    /// Sample written exactly `delay` samples ago. Clamped like `read()`.
+    pub fn tap(&self, delay: usize) -> f64 {
+        self.at(delay.clamp(1, self.max_delay()))
+    }
+
+    pub fn push(&mut self, sample: f64) {
+        self.buffer[self.write] = sample as f32;
+        self.write += 1;
+
+        if self.write == self.buffer.len() {
+            self.write = 0;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.buffer.fill(0.0);
+        self.write = 0;
+    }
+
+    fn at(&self, delay: usize) -> f64 {
+        let len = self.buffer.len();
+
+        self.buffer[(self.write + len - delay) % len] as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tap_returns_past_samples() {
+        let mut line = DelayLine::new(8);
+
+        for sample in 1..=5 {
+            line.push(sample as f64);
+        }
+
+        assert_eq!(line.tap(1), 5.0);
+        assert_eq!(line.tap(3), 3.0);
+    }
+
+    #[test]
+    fn read_interpolates_between_samples() {
+        let mut line = DelayLine::new(8);
+        line.push(2.0);
+        line.push(4.0);
+
+        assert_eq!(line.read(1.5), 3.0);
+    }
+
+    #[test]
+    fn wraps_around_capacity() {
+        let mut line = DelayLine::new(4);
+
+        for sample in 1..=10 {
+            line.push(sample as f64);
+        }
+
+        assert_eq!(line.tap(1), 10.0);
+        assert_eq!(line.tap(3), 8.0);
+    }
+}
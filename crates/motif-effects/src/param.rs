@@ -0,0 +1,193 @@
+use wmidi::{ControlFunction, ControlValue};
+
+/// Controller mapped to an effect's first parameter (Sound Controller 1).
+/// Parameter `i` follows on CC `70 + i`.
+pub const FIRST_CONTROLLER: u8 = 70;
+
+/// Sound Controllers 1–10 (CC70–79).
+const CONTROLLER_COUNT: u8 = 10;
+
+/// Time for a smoothed parameter to cover ~63% of a jump.
+const SMOOTHING_SECONDS: f64 = 0.01;
+
+/// Name and range of one automatable parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamInfo {
+    pub name: &'static str,
+    pub min: f64,
+    pub max: f64,
+    pub default: f64,
+}
+
+impl ParamInfo {
+    /// Map 0.0–1.0 onto the parameter's range.
+    pub fn denormalize(&self, normalized: f64) -> f64 {
+        self.min + (self.max - self.min) * normalized.clamp(0.0, 1.0)
+    }
+
+    pub fn clamp(&self, value: f64) -> f64 {
+        value.clamp(self.min, self.max)
+    }
+}
+
+/// An effect whose parameters can be read and written by index, in the
+/// order of `params()`. Automation lanes and controllers go through this.
+pub trait Automatable {
+    fn params(&self) -> &'static [ParamInfo];
+
+    fn get(&self, index: usize) -> Option<f64>;
+
+    /// Set a parameter, clamped to its range. Unknown indices are ignored.
+    ///
+    /// REAL-TIME SAFETY: Safe to call on the audio thread.
+    fn set(&mut self, index: usize, value: f64);
+}
+
+/// Apply a ControlChange to the parameter mapped to its controller.
+/// Controllers outside CC70–79 or past the effect's last parameter are ignored.
+pub fn apply_control(effect: &mut impl Automatable, control: ControlFunction, value: ControlValue) {
+    let index = u8::from(control).wrapping_sub(FIRST_CONTROLLER);
+
+    if index >= CONTROLLER_COUNT {
+        return;
+    }
+
+    let Some(info) = effect.params().get(index as usize) else {
+        return;
+    };
+
+    let value = info.denormalize(u8::from(value) as f64 / 127.0);
+    effect.set(index as usize, value);
+}
+
+/// One-pole follower for parameters read every sample, so automation steps
+/// don't click. The effect's public field is the target; the smoothed value
+/// trails it.
+#[derive(Debug, Clone, Copy)]
+pub struct Smoothed {
+    value: f64,
+    primed: bool,
+}
+
+impl Smoothed {
+    pub fn new() -> Self {
+        Self {
+            value: 0.0,
+            primed: false,
+        }
+    }
+
+    /// Move one sample towards `target`. The first call jumps straight to it.
+    pub fn next(&mut self, target: f64, sample_rate: f64) -> f64 {
+        if !self.primed {
+            self.value = target;
+            self.primed = true;
+
+            return target;
+        }
+
+        let coefficient = 1.0 - (-1.0 / (SMOOTHING_SECONDS * sample_rate)).exp();
+        self.value += (target - self.value) * coefficient;
+
+        self.value
+    }
+
+    /// Forget the current value so the next call jumps to its target.
+    pub fn reset(&mut self) {
+        self.primed = false;
+    }
+}
+
+impl Default for Smoothed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use wmidi::U7;
+
+    struct Fake {
+        values: [f64; 2],
+    }
+
+    const PARAMS: &[ParamInfo] = &[
+        ParamInfo {
+            name: "a",
+            min: 0.0,
+            max: 1.0,
+            default: 0.0,
+        },
+        ParamInfo {
+            name: "b",
+            min: 20.0,
+            max: 220.0,
+            default: 20.0,
+        },
+    ];
+
+    impl Automatable for Fake {
+        fn params(&self) -> &'static [ParamInfo] {
+            PARAMS
+        }
+
+        fn get(&self, index: usize) -> Option<f64> {
+            self.values.get(index).copied()
+        }
+
+        fn set(&mut self, index: usize, value: f64) {
+            if let Some(slot) = self.values.get_mut(index) {
+                *slot = PARAMS[index].clamp(value);
+            }
+        }
+    }
+
+    fn cc(control: u8, value: u8) -> (ControlFunction, ControlValue) {
+        (
+            ControlFunction(U7::try_from(control).unwrap()),
+            U7::try_from(value).unwrap(),
+        )
+    }
+
+    #[test]
+    fn controllers_map_to_params_in_order() {
+        let mut fake = Fake { values: [0.0; 2] };
+
+        let (control, value) = cc(71, 127);
+        apply_control(&mut fake, control, value);
+
+        assert_eq!(fake.get(1), Some(220.0));
+        assert_eq!(fake.get(0), Some(0.0));
+    }
+
+    #[test]
+    fn unmapped_controllers_are_ignored() {
+        let mut fake = Fake { values: [0.0; 2] };
+
+        for control in [7, 69, 72, 80] {
+            let (control, value) = cc(control, 127);
+            apply_control(&mut fake, control, value);
+        }
+
+        assert_eq!(fake.values, [0.0; 2]);
+    }
+
+    #[test]
+    fn smoothed_jumps_first_then_glides() {
+        let mut smoothed = Smoothed::new();
+
+        assert_eq!(smoothed.next(1.0, 48000.0), 1.0);
+
+        let first = smoothed.next(0.0, 48000.0);
+        assert!(first > 0.9 && first < 1.0);
+
+        for _ in 0..4800 {
+            smoothed.next(0.0, 48000.0);
+        }
+
+        assert!(smoothed.next(0.0, 48000.0) < 1e-3);
+    }
+}
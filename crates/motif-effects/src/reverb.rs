@@ -0,0 +1,392 @@
+use std::ops::Range;
+
+use motif_engine::{
+    buffer::AudioBuffer,
+    events::{Event, MidiEvent},
+    node::AudioNode,
+};
+
+use crate::{
+    line::DelayLine,
+    param::{self, Automatable, ParamInfo, Smoothed},
+    stereo,
+};
+
+/// Comb and allpass lengths from Freeverb, in samples at 44.1 kHz. Scaled
+/// to the running sample rate.
+const COMB_LENGTHS: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+const ALLPASS_LENGTHS: [usize; 4] = [556, 441, 341, 225];
+
+/// Extra length of the right channel's filters, decorrelating the sides.
+const STEREO_SPREAD: usize = 23;
+
+const REFERENCE_RATE: f64 = 44100.0;
+
+/// Input scaling into the comb bank, and makeup on the way out.
+const INPUT_GAIN: f64 = 0.015;
+const WET_GAIN: f64 = 3.0;
+
+const ALLPASS_FEEDBACK: f64 = 0.5;
+
+/// Longest pre-delay in seconds.
+pub const MAX_PRE_DELAY: f64 = 0.2;
+
+const PARAMS: &[ParamInfo] = &[
+    ParamInfo {
+        name: "room size",
+        min: 0.0,
+        max: 1.0,
+        default: 0.5,
+    },
+    ParamInfo {
+        name: "damping",
+        min: 0.0,
+        max: 1.0,
+        default: 0.5,
+    },
+    ParamInfo {
+        name: "width",
+        min: 0.0,
+        max: 1.0,
+        default: 1.0,
+    },
+    ParamInfo {
+        name: "pre-delay",
+        min: 0.0,
+        max: MAX_PRE_DELAY,
+        default: 0.0,
+    },
+    ParamInfo {
+        name: "mix",
+        min: 0.0,
+        max: 1.0,
+        default: 0.25,
+    },
+];
+
+/// Freeverb-style stereo reverb: eight parallel lowpass-feedback combs into
+/// four series allpasses per side. Consumes `inputs[0]` and writes
+/// channels 0 and 1.
+#[derive(Debug)]
+pub struct Reverb {
+    /// Decay length (0.0–1.0).
+    pub room_size: f64,
+    /// High-frequency absorption in the tail (0.0–1.0).
+    pub damping: f64,
+    /// Stereo spread of the tail (0.0 = mono).
+    pub width: f64,
+    /// Seconds before the tail starts.
+    pub pre_delay: f64,
+    /// Dry/wet balance (0.0 = dry only).
+    pub mix: f64,
+    pre_delay_line: DelayLine,
+    combs: [[Comb; 8]; 2],
+    allpasses: [[DelayLine; 4]; 2],
+    smoothed_mix: Smoothed,
+}
+
+impl Default for Reverb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reverb {
+    pub fn new() -> Self {
+        let spread = |side: usize| side * STEREO_SPREAD;
+
+        Self {
+            room_size: PARAMS[0].default,
+            damping: PARAMS[1].default,
+            width: PARAMS[2].default,
+            pre_delay: PARAMS[3].default,
+            mix: PARAMS[4].default,
+            pre_delay_line: DelayLine::with_seconds(MAX_PRE_DELAY),
+            combs: std::array::from_fn(|side| {
+                COMB_LENGTHS.map(|length| Comb::new(length + spread(side)))
+            }),
+            allpasses: std::array::from_fn(|side| {
+                ALLPASS_LENGTHS.map(|length| line_for(length + spread(side)))
+            }),
+            smoothed_mix: Smoothed::new(),
+        }
+    }
+}
+
+impl AudioNode for Reverb {
+    fn render(
+        &mut self,
+        inputs: &[&AudioBuffer],
+        output: &mut AudioBuffer,
+        frame_range: Range<usize>,
+        sample_rate: f64,
+    ) {
+        let scale = sample_rate / REFERENCE_RATE;
+        let feedback = 0.7 + 0.28 * self.room_size.clamp(0.0, 1.0);
+        let damp = 0.4 * self.damping.clamp(0.0, 1.0);
+        let width = self.width.clamp(0.0, 1.0);
+        let (direct, cross) = ((1.0 + width) / 2.0, (1.0 - width) / 2.0);
+        let pre_delay = self.pre_delay.clamp(0.0, MAX_PRE_DELAY) * sample_rate;
+
+        let (left, right) = output.two_channels_mut(0, 1);
+
+        for frame in frame_range {
+            let dry = stereo::input_frame(inputs, frame);
+            let mix = self.smoothed_mix.next(self.mix, sample_rate);
+
+            // Read after writing, so a delay of one is the current sample.
+            self.pre_delay_line.push((dry[0] + dry[1]) * INPUT_GAIN);
+            let input = self.pre_delay_line.read(pre_delay + 1.0);
+
+            let mut wet = [0.0; 2];
+
+            for (side, out) in wet.iter_mut().enumerate() {
+                let mut sum: f64 = self.combs[side]
+                    .iter_mut()
+                    .map(|comb| comb.process(input, scale, feedback, damp))
+                    .sum();
+
+                for (line, length) in self.allpasses[side].iter_mut().zip(ALLPASS_LENGTHS) {
+                    let delay = scaled(length + side * STEREO_SPREAD, scale);
+                    let delayed = line.tap(delay);
+
+                    line.push(sum + delayed * ALLPASS_FEEDBACK);
+                    sum = delayed - sum;
+                }
+
+                *out = sum * WET_GAIN;
+            }
+
+            let wet_left = wet[0] * direct + wet[1] * cross;
+            let wet_right = wet[1] * direct + wet[0] * cross;
+
+            left[frame] = stereo::blend(dry[0], wet_left, mix) as f32;
+            right[frame] = stereo::blend(dry[1], wet_right, mix) as f32;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Midi(MidiEvent::ControlChange { control, value }) = event {
+            param::apply_control(self, *control, *value);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.pre_delay_line.clear();
+
+        for comb in self.combs.iter_mut().flatten() {
+            comb.clear();
+        }
+
+        for line in self.allpasses.iter_mut().flatten() {
+            line.clear();
+        }
+
+        self.smoothed_mix.reset();
+    }
+}
+
+impl Automatable for Reverb {
+    fn params(&self) -> &'static [ParamInfo] {
+        PARAMS
+    }
+
+    fn get(&self, index: usize) -> Option<f64> {
+        let value = match index {
+            0 => self.room_size,
+            1 => self.damping,
+            2 => self.width,
+            3 => self.pre_delay,
+            4 => self.mix,
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        let Some(info) = PARAMS.get(index) else {
+            return;
+        };
+
+        let value = info.clamp(value);
+
+        match index {
+            0 => self.room_size = value,
+            1 => self.damping = value,
+            2 => self.width = value,
+            3 => self.pre_delay = value,
+            4 => self.mix = value,
+            _ => {}
+        }
+    }
+}
+
+/// Feedback comb with a one-pole lowpass in the loop.
+#[derive(Debug, Clone)]
+struct Comb {
+    line: DelayLine,
+    /// Length at 44.1 kHz.
+    length: usize,
+    filtered: f64,
+}
+
+impl Comb {
+    fn new(length: usize) -> Self {
+        Self {
+            line: line_for(length),
+            length,
+            filtered: 0.0,
+        }
+    }
+
+    fn process(&mut self, input: f64, scale: f64, feedback: f64, damp: f64) -> f64 {
+        let output = self.line.tap(scaled(self.length, scale));
+
+        self.filtered = output * (1.0 - damp) + self.filtered * damp;
+        self.line.push(input + self.filtered * feedback);
+
+        output
+    }
+
+    fn clear(&mut self) {
+        self.line.clear();
+        self.filtered = 0.0;
+    }
+}
+
+/// A delay line long enough for `length` (at 44.1 kHz) at any supported rate.
+fn line_for(length: usize) -> DelayLine {
+    DelayLine::with_seconds(length as f64 / REFERENCE_RATE)
+}
+
+fn scaled(length: usize, scale: f64) -> usize {
+    (length as f64 * scale).round() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use motif_engine::graph::evaluate_node;
+
+    const SAMPLE_RATE: f64 = 48000.0;
+
+    fn impulse(frames: usize) -> AudioBuffer {
+        let mut input = AudioBuffer::new(2, frames);
+        input.prepare(frames);
+        input.channel_mut(0)[0] = 1.0;
+        input.channel_mut(1)[0] = 1.0;
+
+        input
+    }
+
+    fn wet() -> Reverb {
+        Reverb {
+            mix: 1.0,
+            ..Reverb::new()
+        }
+    }
+
+    fn run(reverb: &mut Reverb, input: &AudioBuffer) -> AudioBuffer {
+        let mut output = AudioBuffer::new(2, input.frames());
+        output.prepare(input.frames());
+
+        evaluate_node(reverb, &[input], &mut output, &[], SAMPLE_RATE);
+
+        output
+    }
+
+    fn energy(samples: &[f32]) -> f64 {
+        samples.iter().map(|&s| (s as f64).powi(2)).sum()
+    }
+
+    #[test]
+    fn impulse_leaves_a_tail() {
+        let mut reverb = wet();
+        let output = run(&mut reverb, &impulse(48000));
+        let left = output.channel(0);
+
+        // Nothing arrives before the shortest comb.
+        assert!(left[..1000].iter().all(|&s| s == 0.0));
+        assert!(energy(&left[24000..]) > 0.0);
+        assert!(left.iter().all(|s| s.abs() < 1.0));
+    }
+
+    #[test]
+    fn larger_room_rings_longer() {
+        let mut small = Reverb {
+            room_size: 0.1,
+            ..wet()
+        };
+        let mut large = Reverb {
+            room_size: 0.9,
+            ..wet()
+        };
+
+        let small = run(&mut small, &impulse(48000));
+        let large = run(&mut large, &impulse(48000));
+
+        assert!(energy(&large.channel(0)[24000..]) > energy(&small.channel(0)[24000..]) * 10.0);
+    }
+
+    #[test]
+    fn zero_width_is_mono() {
+        let mut reverb = Reverb {
+            width: 0.0,
+            ..wet()
+        };
+        let output = run(&mut reverb, &impulse(4800));
+
+        assert_eq!(output.channel(0), output.channel(1));
+    }
+
+    #[test]
+    fn full_width_decorrelates_sides() {
+        let mut reverb = wet();
+        let output = run(&mut reverb, &impulse(4800));
+
+        assert_ne!(output.channel(0), output.channel(1));
+    }
+
+    #[test]
+    fn pre_delay_holds_back_the_tail() {
+        let mut plain = wet();
+        let mut delayed = Reverb {
+            pre_delay: 0.05,
+            ..wet()
+        };
+
+        let plain = run(&mut plain, &impulse(9600));
+        let delayed = run(&mut delayed, &impulse(9600));
+
+        let onset =
+            |buffer: &AudioBuffer| buffer.channel(0).iter().position(|&s| s != 0.0).unwrap();
+        assert_eq!(onset(&delayed) - onset(&plain), 2400);
+    }
+
+    #[test]
+    fn dry_mix_passes_input() {
+        let mut reverb = Reverb {
+            mix: 0.0,
+            ..Reverb::new()
+        };
+        let input = impulse(1000);
+        let output = run(&mut reverb, &input);
+
+        assert_eq!(output.channel(0), input.channel(0));
+    }
+
+    #[test]
+    fn reset_clears_tail() {
+        let mut reverb = wet();
+        run(&mut reverb, &impulse(4800));
+
+        reverb.reset();
+        let mut silence = AudioBuffer::new(2, 4800);
+        silence.prepare(4800);
+
+        let output = run(&mut reverb, &silence);
+        assert!(output.channel(0).iter().all(|&s| s == 0.0));
+    }
+}
@@ -0,0 +1,62 @@
+use motif_engine::buffer::AudioBuffer;
+
+/// Left and right samples of the first input at `frame`. A mono input
+/// feeds both sides; no input reads as silence.
+pub fn input_frame(inputs: &[&AudioBuffer], frame: usize) -> [f64; 2] {
+    inputs
+        .first()
+        .map_or([0.0; 2], |input| frame_of(input, frame))
+}
+
+/// Left and right samples of the sidechain (`inputs[1]`) at `frame`, if
+/// one is connected.
+pub fn sidechain_frame(inputs: &[&AudioBuffer], frame: usize) -> Option<[f64; 2]> {
+    inputs.get(1).map(|input| frame_of(input, frame))
+}
+
+fn frame_of(input: &AudioBuffer, frame: usize) -> [f64; 2] {
+    let left = input.channel(0)[frame] as f64;
+    let right = if input.channels() > 1 {
+        input.channel(1)[frame] as f64
+    } else {
+        left
+    };
+
+    [left, right]
+}
+
+/// Crossfade between the dry and wet signal. `mix` 0.0 is dry only.
+pub fn blend(dry: f64, wet: f64, mix: f64) -> f64 {
+    dry + (wet - dry) * mix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mono_input_feeds_both_sides() {
+        let mut input = AudioBuffer::new(1, 4);
+        input.prepare(4);
+        input.channel_mut(0)[2] = 0.5;
+
+        assert_eq!(input_frame(&[&input], 2), [0.5, 0.5]);
+    }
+
+    #[test]
+    fn missing_input_is_silence() {
+        assert_eq!(input_frame(&[], 0), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn sidechain_is_second_input() {
+        let mut main = AudioBuffer::new(2, 1);
+        let mut key = AudioBuffer::new(2, 1);
+        main.prepare(1);
+        key.prepare(1);
+        key.channel_mut(1)[0] = 0.5;
+
+        assert_eq!(sidechain_frame(&[&main], 0), None);
+        assert_eq!(sidechain_frame(&[&main, &key], 0), Some([0.0, 0.5]));
+    }
+}
@@ -0,0 +1,345 @@
+use std::f64::consts::TAU;
+
+/// Response of one second-order section (RBJ Audio EQ Cookbook).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Lowpass,
+    Highpass,
+    /// Boosts or cuts below the corner frequency.
+    LowShelf,
+    /// Boosts or cuts above the corner frequency.
+    HighShelf,
+    /// Bell around the centre frequency.
+    Peak,
+}
+
+/// Normalized biquad coefficients (a0 = 1).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coefficients {
+    pub b0: f64,
+    pub b1: f64,
+    pub b2: f64,
+    pub a1: f64,
+    pub a2: f64,
+}
+
+impl Coefficients {
+    /// Passes input through unchanged.
+    pub const IDENTITY: Coefficients = Coefficients {
+        b0: 1.0,
+        b1: 0.0,
+        b2: 0.0,
+        a1: 0.0,
+        a2: 0.0,
+    };
+
+    /// `gain` is in dB and only used by shelves and peaks. `q` sets the
+    /// bandwidth of peaks, the resonance of lowpass/highpass and the corner
+    /// shape of shelves (0.707 is the gentlest without overshoot).
+    pub fn new(kind: Kind, frequency: f64, q: f64, gain: f64, sample_rate: f64) -> Self {
+        let frequency = frequency.clamp(1.0, sample_rate * 0.49);
+        let omega = TAU * frequency / sample_rate;
+        let (sin, cos) = omega.sin_cos();
+        let alpha = sin / (2.0 * q.max(0.01));
+        let a = 10.0_f64.powf(gain / 40.0);
+
+        let (b0, b1, b2, a0, a1, a2) = match kind {
+            Kind::Lowpass => (
+                (1.0 - cos) / 2.0,
+                1.0 - cos,
+                (1.0 - cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            Kind::Highpass => (
+                (1.0 + cos) / 2.0,
+                -(1.0 + cos),
+                (1.0 + cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            Kind::Peak => (
+                1.0 + alpha * a,
+                -2.0 * cos,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos,
+                1.0 - alpha / a,
+            ),
+            Kind::LowShelf => {
+                let root = 2.0 * a.sqrt() * alpha;
+
+                (
+                    a * ((a + 1.0) - (a - 1.0) * cos + root),
+                    2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                    a * ((a + 1.0) - (a - 1.0) * cos - root),
+                    (a + 1.0) + (a - 1.0) * cos + root,
+                    -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                    (a + 1.0) + (a - 1.0) * cos - root,
+                )
+            }
+            Kind::HighShelf => {
+                let root = 2.0 * a.sqrt() * alpha;
+
+                (
+                    a * ((a + 1.0) + (a - 1.0) * cos + root),
+                    -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                    a * ((a + 1.0) + (a - 1.0) * cos - root),
+                    (a + 1.0) - (a - 1.0) * cos + root,
+                    2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                    (a + 1.0) - (a - 1.0) * cos - root,
+                )
+            }
+        };
+
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+
+    /// Analytic gain at `frequency`, as a linear amplitude. For drawing
+    /// response curves.
+    pub fn magnitude(&self, frequency: f64, sample_rate: f64) -> f64 {
+        let omega = TAU * frequency / sample_rate;
+        let (sin1, cos1) = omega.sin_cos();
+        let (sin2, cos2) = (2.0 * omega).sin_cos();
+
+        let numerator_re = self.b0 + self.b1 * cos1 + self.b2 * cos2;
+        let numerator_im = -(self.b1 * sin1 + self.b2 * sin2);
+        let denominator_re = 1.0 + self.a1 * cos1 + self.a2 * cos2;
+        let denominator_im = -(self.a1 * sin1 + self.a2 * sin2);
+
+        numerator_re.hypot(numerator_im) / denominator_re.hypot(denominator_im)
+    }
+
+    fn lerp_step(&self, target: &Coefficients, steps: f64) -> Coefficients {
+        Coefficients {
+            b0: (target.b0 - self.b0) / steps,
+            b1: (target.b1 - self.b1) / steps,
+            b2: (target.b2 - self.b2) / steps,
+            a1: (target.a1 - self.a1) / steps,
+            a2: (target.a2 - self.a2) / steps,
+        }
+    }
+
+    fn add(&mut self, step: &Coefficients) {
+        self.b0 += step.b0;
+        self.b1 += step.b1;
+        self.b2 += step.b2;
+        self.a1 += step.a1;
+        self.a2 += step.a2;
+    }
+}
+
+/// Butterworth Q for section `index` of a cascade of `sections`
+/// second-order sections. Cascading them gives a 12·`sections` dB/octave
+/// slope with a flat passband.
+pub fn butterworth_q(index: usize, sections: usize) -> f64 {
+    let order = 4.0 * sections as f64;
+    let angle = std::f64::consts::PI * (2 * index + 1) as f64 / order;
+
+    1.0 / (2.0 * angle.cos())
+}
+
+/// Filter state for one channel. Transposed direct form II, which behaves
+/// well when coefficients move under it.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Biquad {
+    s1: f64,
+    s2: f64,
+}
+
+impl Biquad {
+    pub fn process(&mut self, input: f64, c: &Coefficients) -> f64 {
+        let output = c.b0 * input + self.s1;
+
+        self.s1 = c.b1 * input - c.a1 * output + self.s2;
+        self.s2 = c.b2 * input - c.a2 * output;
+
+        output
+    }
+
+    pub fn reset(&mut self) {
+        self.s1 = 0.0;
+        self.s2 = 0.0;
+    }
+}
+
+/// Coefficients that glide linearly to a new target instead of jumping.
+///
+/// Every stable second-order denominator lies in one convex triangle of
+/// (a1, a2), so every point on a straight line between two stable sets is
+/// stable too.
+#[derive(Debug, Clone, Copy)]
+pub struct Ramp {
+    current: Coefficients,
+    target: Coefficients,
+    step: Coefficients,
+    remaining: usize,
+}
+
+impl Ramp {
+    pub fn new(coefficients: Coefficients) -> Self {
+        Self {
+            current: coefficients,
+            target: coefficients,
+            step: Coefficients::IDENTITY,
+            remaining: 0,
+        }
+    }
+
+    /// Head for `target` over `samples` samples. Zero jumps straight there.
+    /// Setting the target it's already heading for doesn't restart the glide.
+    pub fn set(&mut self, target: Coefficients, samples: usize) {
+        if samples == 0 {
+            self.jump(target);
+            return;
+        }
+
+        if target == self.target {
+            return;
+        }
+
+        self.target = target;
+        self.step = self.current.lerp_step(&target, samples as f64);
+        self.remaining = samples;
+    }
+
+    /// Land on `target` now, abandoning any glide.
+    pub fn jump(&mut self, target: Coefficients) {
+        self.current = target;
+        self.target = target;
+        self.remaining = 0;
+    }
+
+    /// Coefficients for this sample, then advance.
+    pub fn tick(&mut self) -> Coefficients {
+        let coefficients = self.current;
+
+        if self.remaining > 0 {
+            self.remaining -= 1;
+
+            if self.remaining == 0 {
+                self.current = self.target;
+            } else {
+                self.current.add(&self.step);
+            }
+        }
+
+        coefficients
+    }
+
+    pub fn current(&self) -> &Coefficients {
+        &self.current
+    }
+
+    pub fn target(&self) -> &Coefficients {
+        &self.target
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f64 = 48000.0;
+
+    fn db(gain: f64) -> f64 {
+        20.0 * gain.log10()
+    }
+
+    #[test]
+    fn lowpass_is_3_db_down_at_cutoff() {
+        let c = Coefficients::new(
+            Kind::Lowpass,
+            1000.0,
+            std::f64::consts::FRAC_1_SQRT_2,
+            0.0,
+            SAMPLE_RATE,
+        );
+
+        assert!(db(c.magnitude(1000.0, SAMPLE_RATE)) + 3.01 < 0.01);
+        assert!(db(c.magnitude(20.0, SAMPLE_RATE)).abs() < 0.01);
+    }
+
+    #[test]
+    fn peak_hits_gain_at_centre() {
+        let c = Coefficients::new(Kind::Peak, 2000.0, 1.0, 6.0, SAMPLE_RATE);
+
+        assert!((db(c.magnitude(2000.0, SAMPLE_RATE)) - 6.0).abs() < 1e-6);
+        assert!(db(c.magnitude(50.0, SAMPLE_RATE)).abs() < 0.05);
+    }
+
+    #[test]
+    fn shelves_reach_gain_on_their_side() {
+        let low = Coefficients::new(Kind::LowShelf, 200.0, 0.707, -9.0, SAMPLE_RATE);
+        let high = Coefficients::new(Kind::HighShelf, 4000.0, 0.707, 9.0, SAMPLE_RATE);
+
+        assert!((db(low.magnitude(10.0, SAMPLE_RATE)) + 9.0).abs() < 0.05);
+        assert!(db(low.magnitude(10000.0, SAMPLE_RATE)).abs() < 0.05);
+        assert!((db(high.magnitude(20000.0, SAMPLE_RATE)) - 9.0).abs() < 0.1);
+        assert!(db(high.magnitude(50.0, SAMPLE_RATE)).abs() < 0.05);
+    }
+
+    #[test]
+    fn butterworth_pairs() {
+        assert!((butterworth_q(0, 1) - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-12);
+        assert!((butterworth_q(0, 2) - 0.5412).abs() < 1e-4);
+        assert!((butterworth_q(1, 2) - 1.3066).abs() < 1e-4);
+    }
+
+    #[test]
+    fn process_matches_analytic_response() {
+        let c = Coefficients::new(Kind::Peak, 1000.0, 2.0, 12.0, SAMPLE_RATE);
+        let mut filter = Biquad::default();
+        let mut peak: f64 = 0.0;
+
+        for n in 0..48000 {
+            let input = (TAU * 1000.0 * n as f64 / SAMPLE_RATE).sin();
+            let output = filter.process(input, &c);
+
+            if n > 24000 {
+                peak = peak.max(output.abs());
+            }
+        }
+
+        assert!((peak - c.magnitude(1000.0, SAMPLE_RATE)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn ramp_glides_then_lands_exactly() {
+        let target = Coefficients::new(Kind::Lowpass, 500.0, 0.707, 0.0, SAMPLE_RATE);
+        let mut ramp = Ramp::new(Coefficients::IDENTITY);
+
+        ramp.set(target, 4);
+
+        assert_eq!(ramp.tick(), Coefficients::IDENTITY);
+        let middle = ramp.tick();
+        assert!(middle.b0 < 1.0 && middle.b0 > target.b0);
+
+        ramp.tick();
+        ramp.tick();
+        assert!(ramp.is_settled());
+        assert_eq!(ramp.tick(), target);
+    }
+
+    #[test]
+    fn zero_length_ramp_jumps() {
+        let target = Coefficients::new(Kind::Highpass, 500.0, 0.707, 0.0, SAMPLE_RATE);
+        let mut ramp = Ramp::new(Coefficients::IDENTITY);
+
+        ramp.set(target, 0);
+
+        assert_eq!(ramp.tick(), target);
+    }
+}
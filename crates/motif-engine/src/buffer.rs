@@ -85,12 +85,19 @@ impl AudioBuffer {
     }
 
     /// Convert planar →  interleaved for cpal output callback.
-    pub fn write_interleaved(&self, output: &mut [f32]) {
-        let channels = self.channels();
-
+    /// `device_channels` is the output's channel count; device channels
+    /// past the buffer's get silence.
+    ///
+    /// Also the master safety stage, always on: NaN and infinity become
+    /// silence and anything past full scale is clipped, so a misbehaving
+    /// node can't blast the device.
+    pub fn write_interleaved(&self, output: &mut [f32], device_channels: usize) {
         for frame in 0..self.frames {
-            for channel in 0..channels {
-                output[frame * channels + channel] = self.data[channel][frame];
+            for channel in 0..device_channels {
+                output[frame * device_channels + channel] = match self.data.get(channel) {
+                    Some(data) => safe_sample(data[frame]),
+                    None => 0.0,
+                };
             }
         }
     }
@@ -114,6 +121,15 @@ impl AudioBuffer {
     }
 }
 
+/// Sample clamped to full scale, with non-finite values silenced.
+fn safe_sample(sample: f32) -> f32 {
+    if sample.is_finite() {
+        sample.clamp(-1.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -313,26 +329,82 @@ mod tests {
     fn write_interleaved_basic() {
         let mut buffer = AudioBuffer::new(2, 4);
         buffer.prepare(3);
-        buffer.channel_mut(0).copy_from_slice(&[1.0, 2.0, 3.0]);
-        buffer.channel_mut(1).copy_from_slice(&[4.0, 5.0, 6.0]);
+        buffer.channel_mut(0).copy_from_slice(&[0.1, 0.2, 0.3]);
+        buffer.channel_mut(1).copy_from_slice(&[0.4, 0.5, 0.6]);
 
         let mut output = vec![0.0_f32; 6];
-        buffer.write_interleaved(&mut output);
+        buffer.write_interleaved(&mut output, 2);
 
-        assert_eq!(output, &[1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+        assert_eq!(output, &[0.1, 0.4, 0.2, 0.5, 0.3, 0.6]);
     }
 
     #[test]
     fn write_interleaved_single_frame() {
         let mut buffer = AudioBuffer::new(2, 4);
         buffer.prepare(1);
-        buffer.channel_mut(0)[0] = 1.0;
-        buffer.channel_mut(1)[0] = 2.0;
+        buffer.channel_mut(0)[0] = 0.5;
+        buffer.channel_mut(1)[0] = -0.5;
+
+        let mut output = vec![0.0_f32; 2];
+        buffer.write_interleaved(&mut output, 2);
+
+        assert_eq!(output, &[0.5, -0.5]);
+    }
+
+    #[test]
+    fn write_interleaved_silences_non_finite() {
+        let mut buffer = AudioBuffer::new(2, 4);
+        buffer.prepare(2);
+        buffer
+            .channel_mut(0)
+            .copy_from_slice(&[f32::NAN, f32::INFINITY]);
+        buffer
+            .channel_mut(1)
+            .copy_from_slice(&[f32::NEG_INFINITY, 0.25]);
+
+        let mut output = vec![1.0_f32; 4];
+        buffer.write_interleaved(&mut output, 2);
+
+        assert_eq!(output, &[0.0, 0.0, 0.0, 0.25]);
+    }
+
+    #[test]
+    fn write_interleaved_clips_overs() {
+        let mut buffer = AudioBuffer::new(2, 4);
+        buffer.prepare(1);
+        buffer.channel_mut(0)[0] = 3.0;
+        buffer.channel_mut(1)[0] = -1.5;
+
+        let mut output = vec![0.0_f32; 2];
+        buffer.write_interleaved(&mut output, 2);
+
+        assert_eq!(output, &[1.0, -1.0]);
+    }
+
+    #[test]
+    fn write_interleaved_silences_extra_device_channels() {
+        let mut buffer = AudioBuffer::new(2, 4);
+        buffer.prepare(2);
+        buffer.channel_mut(0).copy_from_slice(&[0.1, f32::NAN]);
+        buffer.channel_mut(1).copy_from_slice(&[0.2, 2.0]);
+
+        let mut output = vec![1.0_f32; 8];
+        buffer.write_interleaved(&mut output, 4);
+
+        assert_eq!(output, &[0.1, 0.2, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn write_interleaved_mono_device_takes_first_channel() {
+        let mut buffer = AudioBuffer::new(2, 4);
+        buffer.prepare(2);
+        buffer.channel_mut(0).copy_from_slice(&[0.1, -3.0]);
+        buffer.channel_mut(1).copy_from_slice(&[0.2, 0.3]);
 
         let mut output = vec![0.0_f32; 2];
-        buffer.write_interleaved(&mut output);
+        buffer.write_interleaved(&mut output, 1);
 
-        assert_eq!(output, &[1.0, 2.0]);
+        assert_eq!(output, &[0.1, -1.0]);
     }
 
     #[test]
@@ -0,0 +1,374 @@
+use serde::{Deserialize, Serialize};
+
+/// Stages shorter than this are treated as instant to avoid division blowup.
+const MIN_STAGE_SECONDS: f32 = 1e-5;
+
+/// Curvature used by `Curve::Exponential`. Roughly a 43 dB range over
+/// one stage, which reads as an even fade to the ear.
+const EXPONENTIAL_CURVATURE: f64 = 5.0;
+
+/// Largest `Curve::Curved` magnitude. Far steeper than anything audible,
+/// and well short of where `exp_m1` overflows.
+const MAX_CURVATURE: f64 = 50.0;
+
+/// DAHDSR stage. Progresses: Idle → Delay → Attack → Hold → Decay → Sustain → Release → Idle.
+/// Delay and Hold are skipped within the same tick when their time is zero.
+#[derive(Debug, Default, PartialEq)]
+pub enum State {
+    #[default]
+    Idle,
+    Delay,
+    Attack,
+    Hold,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Segment shape. Curvature bends rises to start slow and falls to start
+/// fast, so positive values approach a constant-dB ramp.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Curve {
+    #[default]
+    Linear,
+    Exponential,
+    /// Adjustable curvature. 0.0 is linear, positive bends toward
+    /// exponential, negative toward logarithmic. Clamped to ±50;
+    /// non-finite values are linear.
+    Curved(f32),
+}
+
+impl Curve {
+    fn curvature(self) -> f64 {
+        match self {
+            Curve::Linear => 0.0,
+            Curve::Exponential => EXPONENTIAL_CURVATURE,
+            Curve::Curved(curvature) if curvature.is_finite() => {
+                (curvature as f64).clamp(-MAX_CURVATURE, MAX_CURVATURE)
+            }
+            Curve::Curved(_) => 0.0,
+        }
+    }
+
+    /// Map linear stage progress (0–1) to the fraction of the level change
+    /// covered so far. Always hits 0 at the start and 1 at the end, so stage
+    /// timing is unaffected by the shape.
+    pub fn shape(self, progress: f64, rising: bool) -> f64 {
+        let k = self.curvature();
+
+        if k.abs() < 1e-3 {
+            return progress;
+        }
+
+        let bend = |x: f64| (k * x).exp_m1() / k.exp_m1();
+
+        if rising {
+            bend(progress)
+        } else {
+            1.0 - bend(1.0 - progress)
+        }
+    }
+}
+
+/// Per-voice DAHDSR envelope. Each voice owns one; the state machine
+/// runs per-sample via tick(). Not SmoothedParam — this is amplitude shaping,
+/// not parameter interpolation.
+///
+/// Stages advance a normalized progress counter rather than stepping the
+/// level directly, so durations are exact at any sample rate and curve.
+#[derive(Debug, Default)]
+pub struct Envelope {
+    state: State,
+    level: f64,
+    progress: f64,
+    delay: f32,
+    attack: f32,
+    hold: f32,
+    decay: f32,
+    sustain: f32,
+    release: f32,
+    attack_curve: Curve,
+    decay_curve: Curve,
+    release_curve: Curve,
+    release_start: f64,
+}
+
+impl Envelope {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start the envelope from zero. Called on NoteOn (including voice steal).
+    pub fn trigger(&mut self) {
+        self.enter(State::Delay);
+        self.level = 0.0;
+    }
+
+    /// Begin release from current level. Captures level so release ramp
+    /// works correctly even if triggered during attack/decay.
+    pub fn release(&mut self) {
+        self.release_start = self.level;
+        self.enter(State::Release);
+    }
+
+    /// Set ADSR parameters. Called after trigger() so the voice uses
+    /// the synth's current values (times in seconds, sustain is a level 0–1).
+    pub fn adsr(&mut self, attack: f32, decay: f32, sustain: f32, release: f32) {
+        self.attack = attack;
+        self.decay = decay;
+        self.sustain = sustain;
+        self.release = release;
+    }
+
+    /// Set the optional DAHDSR stages (seconds). Zero skips the stage.
+    pub fn delay_hold(&mut self, delay: f32, hold: f32) {
+        self.delay = delay;
+        self.hold = hold;
+    }
+
+    /// Set per-stage curve shapes.
+    pub fn curves(&mut self, attack: Curve, decay: Curve, release: Curve) {
+        self.attack_curve = attack;
+        self.decay_curve = decay;
+        self.release_curve = release;
+    }
+
+    /// Advance one sample, return current amplitude (0.0–1.0).
+    pub fn tick(&mut self, sample_rate: f64) -> f64 {
+        loop {
+            match self.state {
+                State::Idle | State::Sustain => {}
+                State::Delay => {
+                    if self.delay < MIN_STAGE_SECONDS {
+                        self.enter(State::Attack);
+                        continue;
+                    }
+
+                    if self.step(self.delay, sample_rate) {
+                        self.enter(State::Attack);
+                    }
+                }
+                State::Attack => {
+                    let done = self.step(self.attack, sample_rate);
+                    self.level = self.attack_curve.shape(self.progress, true);
+
+                    if done {
+                        self.level = 1.0;
+                        self.enter(State::Hold);
+                    }
+                }
+                State::Hold => {
+                    if self.hold < MIN_STAGE_SECONDS {
+                        self.enter(State::Decay);
+                        continue;
+                    }
+
+                    if self.step(self.hold, sample_rate) {
+                        self.enter(State::Decay);
+                    }
+                }
+                State::Decay => {
+                    let sustain = self.sustain as f64;
+                    let done = self.step(self.decay, sample_rate);
+                    self.level =
+                        1.0 - (1.0 - sustain) * self.decay_curve.shape(self.progress, false);
+
+                    if done {
+                        self.level = sustain;
+                        self.enter(State::Sustain);
+                    }
+                }
+                State::Release => {
+                    let done = self.step(self.release, sample_rate);
+                    self.level =
+                        self.release_start * (1.0 - self.release_curve.shape(self.progress, false));
+
+                    if done {
+                        self.level = 0.0;
+                        self.enter(State::Idle);
+                    }
+                }
+            }
+
+            return self.level;
+        }
+    }
+
+    /// Current amplitude without advancing the state machine.
+    pub fn level(&self) -> f64 {
+        self.level
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.state == State::Idle
+    }
+
+    pub fn is_releasing(&self) -> bool {
+        self.state == State::Release
+    }
+
+    pub fn reset(&mut self) {
+        self.enter(State::Idle);
+        self.level = 0.0;
+    }
+
+    fn enter(&mut self, state: State) {
+        self.state = state;
+        self.progress = 0.0;
+    }
+
+    /// Move stage progress forward one sample. Returns true once the stage is complete.
+    fn step(&mut self, seconds: f32, sample_rate: f64) -> bool {
+        if seconds < MIN_STAGE_SECONDS {
+            self.progress = 1.0;
+        } else {
+            self.progress = (self.progress + 1.0 / (seconds as f64 * sample_rate)).min(1.0);
+        }
+
+        // Tolerance absorbs f32 time and accumulated rounding error so stages end on the exact sample.
+        self.progress >= 1.0 - 1e-6
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tick until the envelope leaves `state`, returning the sample count.
+    fn samples_in(envelope: &mut Envelope, state: State, sample_rate: f64) -> usize {
+        let mut count = 0;
+
+        while envelope.state == state {
+            envelope.tick(sample_rate);
+            count += 1;
+        }
+
+        count
+    }
+
+    #[test]
+    fn attack_time_is_exact_across_sample_rates() {
+        for sample_rate in [44100.0, 48000.0, 96000.0] {
+            let mut envelope = Envelope::new();
+            envelope.adsr(0.01, 0.1, 0.5, 0.1);
+            envelope.curves(Curve::Exponential, Curve::Linear, Curve::Linear);
+            envelope.trigger();
+            envelope.tick(sample_rate);
+
+            let samples = samples_in(&mut envelope, State::Attack, sample_rate) + 1;
+
+            assert_eq!(samples, (0.01 * sample_rate).round() as usize);
+        }
+    }
+
+    #[test]
+    fn curves_hit_endpoints() {
+        for curve in [
+            Curve::Linear,
+            Curve::Exponential,
+            Curve::Curved(-3.0),
+            Curve::Curved(8.0),
+        ] {
+            for rising in [true, false] {
+                assert!(curve.shape(0.0, rising).abs() < 1e-9);
+                assert!((curve.shape(1.0, rising) - 1.0).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn extreme_curvature_stays_finite() {
+        for curve in [
+            Curve::Curved(1000.0),
+            Curve::Curved(-1000.0),
+            Curve::Curved(f32::INFINITY),
+            Curve::Curved(f32::NAN),
+        ] {
+            for rising in [true, false] {
+                for progress in [0.0, 0.1, 0.5, 0.9, 1.0] {
+                    let shaped = curve.shape(progress, rising);
+
+                    assert!((0.0..=1.0).contains(&shaped), "{curve:?} {shaped}");
+                }
+            }
+        }
+
+        assert_eq!(Curve::Curved(f32::NAN).shape(0.3, true), 0.3);
+    }
+
+    #[test]
+    fn exponential_release_falls_faster_than_linear() {
+        let halfway = |curve: Curve| {
+            let mut envelope = Envelope::new();
+            envelope.adsr(0.0, 0.0, 1.0, 0.1);
+            envelope.curves(Curve::Linear, Curve::Linear, curve);
+            envelope.trigger();
+
+            for _ in 0..10 {
+                envelope.tick(48000.0);
+            }
+
+            envelope.release();
+
+            for _ in 0..2400 {
+                envelope.tick(48000.0);
+            }
+
+            envelope.level()
+        };
+
+        assert!((halfway(Curve::Linear) - 0.5).abs() < 1e-3);
+        assert!(halfway(Curve::Exponential) < 0.2);
+    }
+
+    #[test]
+    fn delay_stays_silent_then_hold_stays_full() {
+        let mut envelope = Envelope::new();
+        envelope.adsr(0.0, 0.01, 0.5, 0.01);
+        envelope.delay_hold(0.001, 0.001);
+        envelope.trigger();
+
+        for _ in 0..47 {
+            assert_eq!(envelope.tick(48000.0), 0.0);
+        }
+
+        envelope.tick(48000.0);
+        assert_eq!(envelope.state, State::Attack);
+
+        envelope.tick(48000.0);
+        assert_eq!(envelope.state, State::Hold);
+
+        for _ in 0..47 {
+            assert_eq!(envelope.tick(48000.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn zero_delay_and_hold_match_plain_adsr() {
+        let mut envelope = Envelope::new();
+        envelope.adsr(0.0, 0.0, 0.5, 0.0);
+        envelope.trigger();
+
+        assert_eq!(envelope.tick(48000.0), 1.0);
+        assert_eq!(envelope.tick(48000.0), 0.5);
+        assert_eq!(envelope.state, State::Sustain);
+    }
+
+    #[test]
+    fn release_reaches_idle() {
+        let mut envelope = Envelope::new();
+        envelope.adsr(0.0, 0.0, 1.0, 0.01);
+        envelope.trigger();
+        envelope.tick(48000.0);
+        envelope.release();
+
+        assert!(envelope.is_releasing());
+
+        for _ in 0..480 {
+            envelope.tick(48000.0);
+        }
+
+        assert!(envelope.is_idle());
+        assert_eq!(envelope.level(), 0.0);
+    }
+}
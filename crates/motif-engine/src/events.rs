@@ -1,5 +1,5 @@
 use motif_core::id::TrackId;
-use wmidi::{Note, Velocity};
+use wmidi::{ControlFunction, ControlValue, Note, ProgramNumber, Velocity};
 
 /// Unscheduled event — what happened, not when. Nodes see these
 /// via handle_event(); timing is stripped by evaluate_node().
@@ -10,8 +10,21 @@ pub enum Event {
 
 #[derive(Debug, Clone)]
 pub enum MidiEvent {
-    NoteOn { note: Note, velocity: Velocity },
-    NoteOff { note: Note },
+    NoteOn {
+        note: Note,
+        velocity: Velocity,
+    },
+    NoteOff {
+        note: Note,
+    },
+    ControlChange {
+        control: ControlFunction,
+        value: ControlValue,
+    },
+    /// Switch the node to a preset from its own bank. Nodes without a bank ignore it.
+    ProgramChange {
+        program: ProgramNumber,
+    },
 }
 
 #[derive(Debug)]
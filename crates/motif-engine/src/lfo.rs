@@ -0,0 +1,116 @@
+use std::f64::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+/// LFO waveform.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Shape {
+    #[default]
+    Sine,
+    Triangle,
+    /// Rising ramp.
+    Saw,
+    Square,
+}
+
+/// Low-frequency oscillator for modulation. Output is -1.0..=1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Lfo {
+    /// Cycles per second.
+    pub rate: f64,
+    pub shape: Shape,
+    /// Current position in the cycle (0.0..1.0).
+    #[serde(skip)]
+    pub phase: f64,
+}
+
+impl Default for Lfo {
+    fn default() -> Self {
+        Self {
+            rate: 1.0,
+            shape: Shape::Sine,
+            phase: 0.0,
+        }
+    }
+}
+
+impl Lfo {
+    /// Value at the current phase, without advancing.
+    pub fn value(&self) -> f64 {
+        let phase = self.phase;
+
+        match self.shape {
+            Shape::Sine => (TAU * phase).sin(),
+            // Starts at zero rising, in phase with the sine.
+            Shape::Triangle => 4.0 * ((phase + 0.75).rem_euclid(1.0) - 0.5).abs() - 1.0,
+            Shape::Saw => 2.0 * phase - 1.0,
+            Shape::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+        }
+    }
+
+    /// Return the current value and advance one sample.
+    pub fn tick(&mut self, sample_rate: f64) -> f64 {
+        let value = self.value();
+        self.phase = (self.phase + self.rate / sample_rate).rem_euclid(1.0);
+
+        value
+    }
+
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(shape: Shape, phase: f64) -> f64 {
+        Lfo {
+            shape,
+            phase,
+            ..Lfo::default()
+        }
+        .value()
+    }
+
+    #[test]
+    fn sine_and_triangle_share_phase() {
+        for shape in [Shape::Sine, Shape::Triangle] {
+            assert!(at(shape, 0.0).abs() < 1e-12);
+            assert!((at(shape, 0.25) - 1.0).abs() < 1e-12);
+            assert!(at(shape, 0.5).abs() < 1e-12);
+            assert!((at(shape, 0.75) + 1.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn saw_and_square_span_full_range() {
+        assert_eq!(at(Shape::Saw, 0.0), -1.0);
+        assert_eq!(at(Shape::Saw, 0.5), 0.0);
+        assert_eq!(at(Shape::Square, 0.1), 1.0);
+        assert_eq!(at(Shape::Square, 0.6), -1.0);
+    }
+
+    #[test]
+    fn tick_advances_by_rate() {
+        let mut lfo = Lfo {
+            rate: 2.0,
+            ..Lfo::default()
+        };
+
+        for _ in 0..100 {
+            lfo.tick(400.0);
+        }
+
+        // 100 samples at 2 Hz / 400 Hz = half a cycle.
+        assert!((lfo.phase - 0.5).abs() < 1e-9);
+    }
+}
@@ -1,7 +1,14 @@
+pub mod biquad;
 pub mod buffer;
 pub mod clock;
 pub mod control;
+pub mod envelope;
 pub mod error;
 pub mod events;
 pub mod graph;
+pub mod lfo;
 pub mod node;
+pub mod steal;
+pub mod swap;
+#[cfg(feature = "test-util")]
+pub mod test_util;
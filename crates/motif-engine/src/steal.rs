@@ -0,0 +1,391 @@
+use serde::{Deserialize, Serialize};
+use wmidi::Note;
+
+/// What voice allocation needs to know about an instrument's voice.
+/// Instrument voices get it by implementing [`StealVoice`], so every
+/// instrument shares the same stealing behavior.
+pub trait StealCandidate {
+    fn is_active(&self) -> bool;
+    fn is_releasing(&self) -> bool;
+    /// Current output amplitude, used to find the quietest voice.
+    fn level(&self) -> f64;
+    /// Monotonic trigger counter (higher = newer).
+    fn age(&self) -> u64;
+    fn note(&self) -> Option<Note>;
+}
+
+/// How a NoteOn picks a voice once every voice is busy. Free voices are
+/// always preferred; the policy only decides who gets stolen.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StealPolicy {
+    /// Steal the voice triggered longest ago.
+    #[default]
+    Oldest,
+    /// Steal the voice with the lowest current amplitude.
+    Quietest,
+    /// Steal the oldest releasing voice, falling back to the oldest held one.
+    ReleasingFirst,
+    /// Retrigger a voice already playing the same note, even if free voices
+    /// exist. Falls back to `Oldest` when the note isn't sounding.
+    SameNote,
+}
+
+impl StealPolicy {
+    /// Pick the voice index for a new note. `voices` must not be empty.
+    pub fn pick<V: StealCandidate>(self, voices: &[V], note: Note) -> usize {
+        if self == StealPolicy::SameNote
+            && let Some(index) = voices
+                .iter()
+                .position(|v| v.is_active() && v.note() == Some(note))
+        {
+            return index;
+        }
+
+        if let Some(index) = voices.iter().position(|v| !v.is_active()) {
+            return index;
+        }
+
+        match self {
+            StealPolicy::Oldest | StealPolicy::SameNote => oldest(voices, |_| true),
+            StealPolicy::Quietest => voices
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.level().total_cmp(&b.level()))
+                .map(|(i, _)| i)
+                // UNWRAP SAFETY: Caller guarantees voices is non-empty.
+                .unwrap(),
+            StealPolicy::ReleasingFirst => {
+                if voices.iter().any(|v| v.is_releasing()) {
+                    oldest(voices, V::is_releasing)
+                } else {
+                    oldest(voices, |_| true)
+                }
+            }
+        }
+    }
+}
+
+/// Length of the fade-out applied to a stolen voice before the new note
+/// starts. Long enough to hide the discontinuity, short enough to not smear attacks.
+pub const STEAL_FADE_SECONDS: f64 = 0.003;
+
+/// A NoteOn waiting for its voice, with whether its NoteOff already came.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PendingTrigger<T> {
+    pub strike: T,
+    pub released: bool,
+}
+
+/// Steal handling for one voice: fades the old note out and holds the new
+/// one until it reaches silence. `T` is the instrument's per-note values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StealFade<T> {
+    // Gain ramp for a stolen voice. None outside of a steal.
+    fade: Option<f64>,
+    pending: Option<PendingTrigger<T>>,
+}
+
+impl<T> Default for StealFade<T> {
+    fn default() -> Self {
+        Self {
+            fade: None,
+            pending: None,
+        }
+    }
+}
+
+impl<T> StealFade<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fade out the sounding note and queue `strike` behind it. Stealing
+    /// again mid-fade replaces the queued note without restarting the fade.
+    pub fn steal(&mut self, strike: T) {
+        self.fade.get_or_insert(1.0);
+        self.defer(strike);
+    }
+
+    /// Queue `strike` without fading anything, for voices that start notes
+    /// later than NoteOn.
+    pub fn defer(&mut self, strike: T) {
+        self.pending = Some(PendingTrigger {
+            strike,
+            released: false,
+        });
+    }
+
+    /// Mark the queued note released. `false` when nothing is queued, so the
+    /// voice should release its own note.
+    pub fn release(&mut self) -> bool {
+        match &mut self.pending {
+            Some(pending) => {
+                pending.released = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Whether the queued note was released. None when nothing is queued.
+    pub fn pending_released(&self) -> Option<bool> {
+        self.pending.as_ref().map(|pending| pending.released)
+    }
+
+    /// Gain to apply to the voice's output. 1.0 outside of a steal.
+    pub fn gain(&self) -> f64 {
+        self.fade.unwrap_or(1.0)
+    }
+
+    /// Advance the fade by one sample. Returns the queued note once the old
+    /// one has faded to silence.
+    pub fn tick(&mut self, sample_rate: f64) -> Option<PendingTrigger<T>> {
+        let fade = self.fade.as_mut()?;
+        *fade -= 1.0 / (STEAL_FADE_SECONDS * sample_rate);
+
+        if *fade > 0.0 {
+            return None;
+        }
+
+        self.take()
+    }
+
+    /// End any fade and hand back the queued note, as when the old note has
+    /// stopped on its own.
+    pub fn take(&mut self) -> Option<PendingTrigger<T>> {
+        self.fade = None;
+        self.pending.take()
+    }
+
+    pub fn reset(&mut self) {
+        self.fade = None;
+        self.pending = None;
+    }
+}
+
+/// A voice that starts its notes through a [`StealFade`]. Instruments
+/// describe the note that's sounding; the steal bookkeeping, and with it
+/// [`StealCandidate`], is shared.
+pub trait StealVoice {
+    /// The instrument's per-note values.
+    type Strike;
+
+    fn steal_fade(&self) -> &StealFade<Self::Strike>;
+    fn steal_fade_mut(&mut self) -> &mut StealFade<Self::Strike>;
+    /// Monotonic trigger counter (higher = newer).
+    fn age(&self) -> u64;
+    fn note(&self) -> Option<Note>;
+    /// The current note still sounds. Ignores a queued note.
+    fn is_sounding(&self) -> bool;
+    /// The current note is in its release.
+    fn is_sounding_released(&self) -> bool;
+    /// Amplitude of the current note before the steal fade.
+    fn sounding_level(&self) -> f64;
+    /// Start `strike` right away, cutting off whatever is sounding.
+    fn start(&mut self, strike: Self::Strike);
+    /// Release the current note.
+    fn release_sounding(&mut self);
+
+    fn is_active(&self) -> bool {
+        self.is_sounding() || self.steal_fade().is_pending()
+    }
+
+    fn is_releasing(&self) -> bool {
+        self.steal_fade()
+            .pending_released()
+            .unwrap_or_else(|| self.is_sounding_released())
+    }
+
+    fn level(&self) -> f64 {
+        self.sounding_level() * self.steal_fade().gain()
+    }
+
+    /// Start `strike`. If the voice is still sounding (a steal), the old note
+    /// fades out first and the new one starts once it reaches silence.
+    fn play(&mut self, strike: Self::Strike) {
+        if self.is_active() {
+            self.steal_fade_mut().steal(strike);
+            return;
+        }
+
+        self.start(strike);
+    }
+
+    /// Release the queued note if there is one, otherwise the current note.
+    fn release(&mut self) {
+        if !self.steal_fade_mut().release() {
+            self.release_sounding();
+        }
+    }
+
+    /// Advance the steal fade by one sample, starting the queued note once
+    /// the old one is silent.
+    fn tick_steal(&mut self, sample_rate: f64) {
+        if let Some(pending) = self.steal_fade_mut().tick(sample_rate) {
+            self.start_pending(pending);
+        }
+    }
+
+    fn start_pending(&mut self, pending: PendingTrigger<Self::Strike>) {
+        self.start(pending.strike);
+
+        if pending.released {
+            self.release_sounding();
+        }
+    }
+}
+
+impl<V: StealVoice> StealCandidate for V {
+    fn is_active(&self) -> bool {
+        StealVoice::is_active(self)
+    }
+
+    fn is_releasing(&self) -> bool {
+        StealVoice::is_releasing(self)
+    }
+
+    fn level(&self) -> f64 {
+        StealVoice::level(self)
+    }
+
+    fn age(&self) -> u64 {
+        StealVoice::age(self)
+    }
+
+    fn note(&self) -> Option<Note> {
+        StealVoice::note(self)
+    }
+}
+
+/// Index of the lowest-age voice matching `filter`. At least one voice must match.
+fn oldest<V: StealCandidate>(voices: &[V], filter: impl Fn(&V) -> bool) -> usize {
+    voices
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| filter(v))
+        .min_by_key(|(_, v)| v.age())
+        .map(|(i, _)| i)
+        // UNWRAP SAFETY: Caller guarantees at least one voice matches.
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct TestVoice {
+        active: bool,
+        releasing: bool,
+        level: f64,
+        age: u64,
+        note: Option<Note>,
+    }
+
+    impl StealCandidate for TestVoice {
+        fn is_active(&self) -> bool {
+            self.active
+        }
+
+        fn is_releasing(&self) -> bool {
+            self.releasing
+        }
+
+        fn level(&self) -> f64 {
+            self.level
+        }
+
+        fn age(&self) -> u64 {
+            self.age
+        }
+
+        fn note(&self) -> Option<Note> {
+            self.note
+        }
+    }
+
+    fn busy(age: u64, note: Note) -> TestVoice {
+        TestVoice {
+            active: true,
+            level: 1.0,
+            age,
+            note: Some(note),
+            ..TestVoice::default()
+        }
+    }
+
+    #[test]
+    fn free_voice_preferred() {
+        let voices = [busy(0, Note::C4), TestVoice::default()];
+
+        assert_eq!(StealPolicy::Oldest.pick(&voices, Note::D4), 1);
+    }
+
+    #[test]
+    fn oldest_steals_lowest_age() {
+        let voices = [busy(5, Note::C4), busy(2, Note::D4), busy(9, Note::E4)];
+
+        assert_eq!(StealPolicy::Oldest.pick(&voices, Note::F4), 1);
+    }
+
+    #[test]
+    fn quietest_steals_lowest_level() {
+        let mut voices = [busy(0, Note::C4), busy(1, Note::D4)];
+        voices[1].level = 0.1;
+
+        assert_eq!(StealPolicy::Quietest.pick(&voices, Note::F4), 1);
+    }
+
+    #[test]
+    fn releasing_first_skips_held_voices() {
+        let mut voices = [busy(0, Note::C4), busy(1, Note::D4), busy(2, Note::E4)];
+        voices[2].releasing = true;
+
+        assert_eq!(StealPolicy::ReleasingFirst.pick(&voices, Note::F4), 2);
+    }
+
+    #[test]
+    fn steal_fade_queues_until_silent() {
+        let mut steal = StealFade::new();
+
+        steal.steal(1);
+        assert!(steal.release());
+        steal.steal(2);
+        assert_eq!(steal.pending_released(), Some(false));
+
+        let mut samples = 1;
+
+        let started = loop {
+            if let Some(started) = steal.tick(48000.0) {
+                break started;
+            }
+
+            assert!(steal.gain() < 1.0);
+            samples += 1;
+        };
+
+        // 3 ms at 48 kHz.
+        assert!((144..=145).contains(&samples));
+        assert_eq!(
+            started,
+            PendingTrigger {
+                strike: 2,
+                released: false
+            }
+        );
+        assert_eq!(steal.gain(), 1.0);
+        assert!(!steal.release());
+    }
+
+    #[test]
+    fn same_note_retriggers_even_with_free_voices() {
+        let voices = [TestVoice::default(), busy(3, Note::C4)];
+
+        assert_eq!(StealPolicy::SameNote.pick(&voices, Note::C4), 1);
+        assert_eq!(StealPolicy::SameNote.pick(&voices, Note::D4), 0);
+    }
+}
@@ -0,0 +1,131 @@
+use rtrb::{Consumer, Producer, RingBuffer};
+
+use crate::error::EngineError;
+
+/// Create a channel for replacing a node's boxed data while it plays.
+/// `capacity` is how many replacements can be queued at once.
+///
+/// Values are built and dropped on the sending side; the audio thread only
+/// moves boxes, so it never allocates or frees.
+pub fn channel<T: Send>(capacity: usize) -> (SwapSender<T>, SwapReceiver<T>) {
+    let (outgoing, incoming) = RingBuffer::new(capacity);
+    // One spare slot so a swap is never blocked by a full garbage queue
+    // while every queued value is still in flight.
+    let (garbage_producer, garbage) = RingBuffer::new(capacity + 1);
+
+    let sender = SwapSender { outgoing, garbage };
+    let receiver = SwapReceiver {
+        incoming,
+        garbage: garbage_producer,
+    };
+
+    (sender, receiver)
+}
+
+/// Non-audio side. Queues replacements and frees the values they replaced.
+pub struct SwapSender<T> {
+    outgoing: Producer<Box<T>>,
+    garbage: Consumer<Box<T>>,
+}
+
+/// Audio side. Owned by the node whose data is being replaced.
+pub struct SwapReceiver<T> {
+    incoming: Consumer<Box<T>>,
+    garbage: Producer<Box<T>>,
+}
+
+impl<T> SwapSender<T> {
+    /// Queue a replacement for the audio thread.
+    pub fn send(&mut self, value: T) -> Result<(), EngineError> {
+        self.outgoing
+            .push(Box::new(value))
+            .map_err(|_| EngineError::BufferFull)
+    }
+
+    /// Drop values the audio thread has replaced. Call regularly from a
+    /// non-audio thread. Returns how many were freed.
+    pub fn collect_garbage(&mut self) -> usize {
+        let mut freed = 0;
+
+        while self.garbage.pop().is_ok() {
+            freed += 1;
+        }
+
+        freed
+    }
+}
+
+impl<T> SwapReceiver<T> {
+    /// Replace `current` with the next queued value, if any, sending the
+    /// old one back for freeing. Returns whether a swap happened.
+    ///
+    /// REAL-TIME SAFETY: Only moves boxes. Safe to call on the audio thread.
+    pub fn receive(&mut self, current: &mut Box<T>) -> bool {
+        // Only take a value if the old one has somewhere to go.
+        if self.garbage.is_full() {
+            return false;
+        }
+
+        let Ok(next) = self.incoming.pop() else {
+            return false;
+        };
+
+        let old = std::mem::replace(current, next);
+        // UNWRAP SAFETY: Checked above that the garbage queue has room.
+        self.garbage.push(old).unwrap();
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn receive_swaps_and_returns_old_value() {
+        let (mut sender, mut receiver) = channel(2);
+        let mut current = Box::new(1);
+
+        sender.send(2).unwrap();
+
+        assert!(receiver.receive(&mut current));
+        assert_eq!(*current, 2);
+        assert_eq!(sender.collect_garbage(), 1);
+    }
+
+    #[test]
+    fn receive_without_pending_value_keeps_current() {
+        let (_sender, mut receiver) = channel::<i32>(2);
+        let mut current = Box::new(1);
+
+        assert!(!receiver.receive(&mut current));
+        assert_eq!(*current, 1);
+    }
+
+    #[test]
+    fn full_queue_reports_buffer_full() {
+        let (mut sender, _receiver) = channel(1);
+
+        sender.send(1).unwrap();
+
+        assert!(matches!(sender.send(2), Err(EngineError::BufferFull)));
+    }
+
+    #[test]
+    fn swaps_wait_while_garbage_is_uncollected() {
+        let (mut sender, mut receiver) = channel(1);
+        let mut current = Box::new(0);
+
+        // Garbage holds capacity + 1 values before swaps have to wait.
+        for value in 1..=3 {
+            sender.send(value).unwrap();
+            receiver.receive(&mut current);
+        }
+
+        assert_eq!(*current, 2);
+        assert_eq!(sender.collect_garbage(), 2);
+        assert!(receiver.receive(&mut current));
+        assert_eq!(*current, 3);
+    }
+}
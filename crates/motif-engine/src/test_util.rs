@@ -0,0 +1,44 @@
+use std::ops::Range;
+
+use wmidi::{Note, Velocity};
+
+use crate::{
+    buffer::AudioBuffer,
+    events::{Event, MidiEvent, ScheduledEvent},
+};
+
+pub const SAMPLE_RATE: f64 = 48000.0;
+
+/// Full-velocity NoteOn at `offset`.
+pub fn note_on(offset: u32, note: Note) -> ScheduledEvent {
+    note_on_velocity(offset, note, Velocity::MAX)
+}
+
+pub fn note_on_velocity(offset: u32, note: Note, velocity: Velocity) -> ScheduledEvent {
+    ScheduledEvent {
+        sample_offset: offset,
+        event: Event::Midi(MidiEvent::NoteOn { note, velocity }),
+    }
+}
+
+pub fn note_off(offset: u32, note: Note) -> ScheduledEvent {
+    ScheduledEvent {
+        sample_offset: offset,
+        event: Event::Midi(MidiEvent::NoteOff { note }),
+    }
+}
+
+/// Any sample of the left channel in `range` is audible.
+pub fn has_signal(buf: &AudioBuffer, range: Range<usize>) -> bool {
+    buf.channel(0)[range].iter().any(|&s| s.abs() > 1e-6)
+}
+
+/// Every sample of the left channel in `range` is silent.
+pub fn is_silent(buf: &AudioBuffer, range: Range<usize>) -> bool {
+    buf.channel(0)[range].iter().all(|&s| s.abs() < 1e-6)
+}
+
+/// Loudest sample of the left channel.
+pub fn peak(buf: &AudioBuffer) -> f32 {
+    buf.channel(0).iter().fold(0.0, |max, s| max.max(s.abs()))
+}
@@ -0,0 +1,335 @@
+use std::ops::Range;
+
+use motif_engine::{
+    buffer::AudioBuffer,
+    events::{Event, MidiEvent},
+    node::AudioNode,
+};
+
+use crate::{
+    kit::{self, PAD_COUNT},
+    pad::Pad,
+    voice::PadVoice,
+};
+
+/// Master output scaling. Leaves headroom when several pads hit at once.
+const GAIN: f64 = 0.5;
+
+/// Drum machine. Implements AudioNode — NoteOn triggers the pad mapped to
+/// that note; NoteOff is ignored because hits are one-shot.
+///
+/// Every pad mixes into the stereo pair of its `output` bus. Give the node
+/// a wider output buffer (2 channels per bus) to split pads into separate
+/// mixer channels; with a stereo buffer everything lands on the main pair.
+#[derive(Debug)]
+pub struct Drums {
+    /// At most `PAD_COUNT`. Fixed once the node is on the audio thread.
+    pub pads: Vec<Pad>,
+    /// One voice per pad, same order as `pads`.
+    pub voices: Vec<PadVoice>,
+}
+
+impl Default for Drums {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drums {
+    /// The factory synthesized kit.
+    pub fn new() -> Self {
+        Self::with_pads(kit::factory())
+    }
+
+    /// Panics if there are more than `PAD_COUNT` pads.
+    pub fn with_pads(pads: Vec<Pad>) -> Self {
+        assert!(
+            pads.len() <= PAD_COUNT,
+            "Drums has at most {PAD_COUNT} pads"
+        );
+
+        Self {
+            voices: pads.iter().map(|_| PadVoice::default()).collect(),
+            pads,
+        }
+    }
+}
+
+impl AudioNode for Drums {
+    fn render(
+        &mut self,
+        _inputs: &[&AudioBuffer],
+        output: &mut AudioBuffer,
+        frame_range: Range<usize>,
+        sample_rate: f64,
+    ) {
+        let buses = output.channels() / 2;
+
+        for (pad, voice) in self.pads.iter().zip(&mut self.voices) {
+            if !voice.active {
+                continue;
+            }
+
+            let bus = if pad.output < buses { pad.output } else { 0 };
+            let (gain_l, gain_r) = pad.pan_gains();
+            let (left, right) = output.two_channels_mut(2 * bus, 2 * bus + 1);
+
+            for frame in frame_range.clone() {
+                if !voice.active {
+                    break;
+                }
+
+                let (l, r) = voice.render(pad, sample_rate);
+                left[frame] += (l * gain_l * GAIN) as f32;
+                right[frame] += (r * gain_r * GAIN) as f32;
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::Midi(MidiEvent::NoteOn { note, velocity }) => {
+                let Some(index) = self.pads.iter().position(|p| p.note == *note) else {
+                    return;
+                };
+
+                if let Some(group) = self.pads[index].choke {
+                    for (pad, voice) in self.pads.iter().zip(&mut self.voices) {
+                        if pad.choke == Some(group) && pad.note != *note {
+                            voice.choke();
+                        }
+                    }
+                }
+
+                self.voices[index].trigger(u8::from(*velocity) as f64 / 127.0);
+            }
+            Event::Midi(_) => {}
+        }
+    }
+
+    fn reset(&mut self) {
+        for voice in &mut self.voices {
+            voice.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use motif_engine::{events::ScheduledEvent, graph::evaluate_node};
+    use motif_sampler::sample::Sample;
+    use wmidi::{Note, Velocity};
+
+    use crate::{model::Model, pad::Source};
+
+    const SAMPLE_RATE: f64 = 48000.0;
+
+    fn make_drums() -> Drums {
+        Drums::new()
+    }
+
+    fn note_on(offset: u32, note: Note) -> ScheduledEvent {
+        ScheduledEvent {
+            sample_offset: offset,
+            event: Event::Midi(MidiEvent::NoteOn {
+                note,
+                velocity: Velocity::MAX,
+            }),
+        }
+    }
+
+    fn has_signal(buf: &AudioBuffer, channel: usize, range: Range<usize>) -> bool {
+        buf.channel(channel)[range].iter().any(|&s| s.abs() > 1e-6)
+    }
+
+    fn is_silent(buf: &AudioBuffer, channel: usize, range: Range<usize>) -> bool {
+        buf.channel(channel)[range].iter().all(|&s| s.abs() < 1e-6)
+    }
+
+    fn voice_for(drums: &Drums, name: &str) -> usize {
+        drums.pads.iter().position(|p| p.name == name).unwrap()
+    }
+
+    #[test]
+    fn pad_note_produces_output() {
+        let mut drums = make_drums();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::C2)];
+
+        evaluate_node(&mut drums, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(has_signal(&output, 0, 0..256));
+    }
+
+    #[test]
+    fn unmapped_note_is_silent() {
+        let mut drums = make_drums();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::C6)];
+
+        evaluate_node(&mut drums, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(is_silent(&output, 0, 0..256));
+    }
+
+    #[test]
+    fn hit_decays_to_silence() {
+        let mut drums = make_drums();
+        let mut output = AudioBuffer::new(2, 1024);
+
+        output.prepare(1024);
+        let events = [note_on(0, Note::Gb2)];
+        evaluate_node(&mut drums, &[], &mut output, &events, SAMPLE_RATE);
+
+        // Closed hat decays in 0.08s = 3840 samples.
+        for _ in 0..4 {
+            output.prepare(1024);
+            evaluate_node(&mut drums, &[], &mut output, &[], SAMPLE_RATE);
+        }
+
+        assert!(!drums.voices[voice_for(&drums, "Closed Hat")].active);
+        assert!(is_silent(&output, 0, 0..1024));
+    }
+
+    #[test]
+    fn closed_hat_chokes_open_hat() {
+        let mut drums = make_drums();
+        let mut output = AudioBuffer::new(2, 1024);
+        output.prepare(1024);
+
+        let events = [note_on(0, Note::Bb2), note_on(256, Note::Gb2)];
+        evaluate_node(&mut drums, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(!drums.voices[voice_for(&drums, "Open Hat")].active);
+        assert!(drums.voices[voice_for(&drums, "Closed Hat")].active);
+    }
+
+    #[test]
+    fn other_groups_are_not_choked() {
+        let mut drums = make_drums();
+        let mut output = AudioBuffer::new(2, 1024);
+        output.prepare(1024);
+
+        let events = [note_on(0, Note::C2), note_on(256, Note::Gb2)];
+        evaluate_node(&mut drums, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(drums.voices[voice_for(&drums, "Kick")].active);
+    }
+
+    #[test]
+    fn pan_hard_left_silences_right() {
+        let mut pad = Pad::new("Kick", Note::C2, Source::Synth(Model::Kick));
+        pad.pan = -1.0;
+        let mut drums = Drums::with_pads(vec![pad]);
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::C2)];
+
+        evaluate_node(&mut drums, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(has_signal(&output, 0, 0..256));
+        assert!(is_silent(&output, 1, 0..256));
+    }
+
+    #[test]
+    fn pad_routes_to_its_output_bus() {
+        let mut kick = Pad::new("Kick", Note::C2, Source::Synth(Model::Kick));
+        kick.output = 1;
+        let mut drums = Drums::with_pads(vec![kick]);
+        let mut output = AudioBuffer::new(4, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::C2)];
+
+        evaluate_node(&mut drums, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(is_silent(&output, 0, 0..256));
+        assert!(is_silent(&output, 1, 0..256));
+        assert!(has_signal(&output, 2, 0..256));
+        assert!(has_signal(&output, 3, 0..256));
+    }
+
+    #[test]
+    fn missing_bus_falls_back_to_main() {
+        let mut kick = Pad::new("Kick", Note::C2, Source::Synth(Model::Kick));
+        kick.output = 3;
+        let mut drums = Drums::with_pads(vec![kick]);
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::C2)];
+
+        evaluate_node(&mut drums, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(has_signal(&output, 0, 0..256));
+    }
+
+    #[test]
+    fn sample_pad_tune_changes_speed() {
+        let sample = Arc::new(Sample::new(vec![vec![0.5; 4800]], SAMPLE_RATE));
+        let mut pad = Pad::new("Perc", Note::C2, Source::Sample(sample));
+        pad.tune = 12.0;
+        let mut drums = Drums::with_pads(vec![pad]);
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::C2)];
+
+        evaluate_node(&mut drums, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!((drums.voices[0].position - 512.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn retrigger_fades_out_the_old_hit() {
+        // 100 Hz sine: starts at zero, so only the cut-off can click.
+        let sine = (0..48000)
+            .map(|i| (std::f32::consts::TAU * 100.0 * i as f32 / 48000.0).sin())
+            .collect();
+        let sample = Arc::new(Sample::new(vec![sine], SAMPLE_RATE));
+        let mut pad = Pad::new("Perc", Note::C2, Source::Sample(sample));
+        pad.decay = 2.0;
+        let mut drums = Drums::with_pads(vec![pad]);
+        let mut output = AudioBuffer::new(2, 1024);
+        output.prepare(1024);
+
+        // Retrigger near the sine's peak.
+        let events = [note_on(0, Note::C2), note_on(120, Note::C2)];
+
+        evaluate_node(&mut drums, &[], &mut output, &events, SAMPLE_RATE);
+
+        let largest_jump = output
+            .channel(0)
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .fold(0.0, f32::max);
+
+        assert!(largest_jump < 0.02, "{largest_jump}");
+        assert!(drums.voices[0].active);
+        assert!(has_signal(&output, 0, 1000..1024));
+    }
+
+    #[test]
+    fn sample_pad_stops_at_sample_end() {
+        let sample = Arc::new(Sample::new(vec![vec![0.5; 100]], SAMPLE_RATE));
+        let mut drums = Drums::with_pads(vec![Pad::new("Perc", Note::C2, Source::Sample(sample))]);
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::C2)];
+
+        evaluate_node(&mut drums, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(!drums.voices[0].active);
+        assert!(is_silent(&output, 0, 101..256));
+    }
+}
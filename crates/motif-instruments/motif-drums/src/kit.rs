@@ -0,0 +1,76 @@
+use wmidi::Note;
+
+use crate::{
+    model::Model,
+    pad::{Pad, Source},
+};
+
+/// Pads per kit.
+pub const PAD_COUNT: usize = 16;
+
+/// Choke group shared by the hi-hats in the factory kit.
+pub const HAT_CHOKE: u8 = 1;
+
+/// Synthesized kit on the General MIDI drum notes C2–D#3, so pad order
+/// matches most controllers.
+pub fn factory() -> Vec<Pad> {
+    let pad = |name: &str, note, model, tune, decay, pan| Pad {
+        tune,
+        decay,
+        pan,
+        ..Pad::new(name, note, Source::Synth(model))
+    };
+    let hat = |name: &str, note, decay| Pad {
+        choke: Some(HAT_CHOKE),
+        gain: 0.6,
+        ..pad(name, note, Model::Hat, 0.0, decay, 0.2)
+    };
+
+    vec![
+        pad("Kick", Note::C2, Model::Kick, 0.0, 0.6, 0.0),
+        pad("Rim", Note::Db2, Model::Snare, 12.0, 0.05, 0.0),
+        pad("Snare", Note::D2, Model::Snare, 0.0, 0.25, 0.0),
+        pad("Clap", Note::Eb2, Model::Snare, -5.0, 0.3, 0.0),
+        pad("Snare 2", Note::E2, Model::Snare, 3.0, 0.18, 0.0),
+        pad("Floor Tom", Note::F2, Model::Kick, 7.0, 0.5, -0.4),
+        hat("Closed Hat", Note::Gb2, 0.08),
+        pad("Low Tom", Note::G2, Model::Kick, 10.0, 0.45, -0.25),
+        hat("Pedal Hat", Note::Ab2, 0.12),
+        pad("Mid Tom", Note::A2, Model::Kick, 14.0, 0.4, 0.0),
+        hat("Open Hat", Note::Bb2, 0.6),
+        pad("High Tom", Note::B2, Model::Kick, 17.0, 0.35, 0.25),
+        pad("Higher Tom", Note::C3, Model::Kick, 20.0, 0.3, 0.4),
+        pad("Crash", Note::Db3, Model::Hat, -4.0, 1.8, -0.3),
+        pad("Top Tom", Note::D3, Model::Kick, 23.0, 0.25, 0.5),
+        pad("Ride", Note::Eb3, Model::Hat, -9.0, 1.5, 0.3),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factory_fills_every_pad() {
+        assert_eq!(factory().len(), PAD_COUNT);
+    }
+
+    #[test]
+    fn factory_notes_are_unique() {
+        let kit = factory();
+
+        for (i, pad) in kit.iter().enumerate() {
+            assert!(!kit[i + 1..].iter().any(|p| p.note == pad.note));
+        }
+    }
+
+    #[test]
+    fn hats_share_a_choke_group() {
+        let kit = factory();
+        let choke = |name: &str| kit.iter().find(|p| p.name == name).unwrap().choke;
+
+        assert_eq!(choke("Closed Hat"), Some(HAT_CHOKE));
+        assert_eq!(choke("Open Hat"), Some(HAT_CHOKE));
+        assert_eq!(choke("Kick"), None);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod drums;
+pub mod kit;
+pub mod model;
+pub mod pad;
+pub mod voice;
@@ -0,0 +1,195 @@
+use std::f64::consts::TAU;
+
+/// Synthesized drum sounds in the spirit of analog drum machines. The
+/// overall decay comes from the pad; models only shape the hit itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    /// Sine with a fast downward pitch sweep.
+    Kick,
+    /// Short pitched body plus noise.
+    Snare,
+    /// Six detuned squares and noise through a highpass.
+    Hat,
+}
+
+/// Kick sweep: starts this many Hz above the body and falls exponentially.
+const KICK_SWEEP: f64 = 100.0;
+const KICK_BODY: f64 = 50.0;
+const KICK_SWEEP_SECONDS: f64 = 0.03;
+
+const SNARE_BODY: f64 = 185.0;
+const SNARE_BODY_SECONDS: f64 = 0.05;
+
+/// Square oscillator frequencies of the classic analog hat circuit.
+const HAT_PARTIALS: [f64; 6] = [205.3, 304.4, 369.6, 522.7, 540.0, 800.0];
+const HAT_CUTOFF: f64 = 7000.0;
+
+/// Running state of one synthesized hit. Allocation-free.
+#[derive(Debug, Clone)]
+pub struct ModelState {
+    phases: [f64; HAT_PARTIALS.len()],
+    time: f64,
+    noise: Noise,
+    highpass: Highpass,
+}
+
+impl Default for ModelState {
+    fn default() -> Self {
+        Self {
+            phases: [0.0; HAT_PARTIALS.len()],
+            time: 0.0,
+            noise: Noise::default(),
+            highpass: Highpass::default(),
+        }
+    }
+}
+
+impl ModelState {
+    pub fn trigger(&mut self) {
+        self.phases = [0.0; HAT_PARTIALS.len()];
+        self.time = 0.0;
+        self.highpass = Highpass::default();
+    }
+
+    /// Next sample of `model`. `pitch` is a frequency multiplier (1.0 = untuned).
+    pub fn tick(&mut self, model: Model, pitch: f64, sample_rate: f64) -> f64 {
+        let t = self.time;
+        self.time += 1.0 / sample_rate;
+
+        match model {
+            Model::Kick => {
+                let frequency = pitch * (KICK_BODY + KICK_SWEEP * (-t / KICK_SWEEP_SECONDS).exp());
+
+                (TAU * advance(&mut self.phases[0], frequency, sample_rate)).sin()
+            }
+            Model::Snare => {
+                let body = (TAU * advance(&mut self.phases[0], SNARE_BODY * pitch, sample_rate))
+                    .sin()
+                    * (-t / SNARE_BODY_SECONDS).exp();
+
+                0.5 * body + 0.5 * self.noise.next()
+            }
+            Model::Hat => {
+                let mut metal = 0.0;
+
+                for (phase, partial) in self.phases.iter_mut().zip(HAT_PARTIALS) {
+                    let square = if advance(phase, partial * pitch, sample_rate) < 0.5 {
+                        1.0
+                    } else {
+                        -1.0
+                    };
+                    metal += square / HAT_PARTIALS.len() as f64;
+                }
+
+                let cutoff = (HAT_CUTOFF * pitch).min(sample_rate * 0.45);
+
+                self.highpass
+                    .process(0.5 * metal + 0.5 * self.noise.next(), cutoff, sample_rate)
+            }
+        }
+    }
+}
+
+/// Step a 0.0..1.0 phase accumulator, returning the phase before the step.
+fn advance(phase: &mut f64, frequency: f64, sample_rate: f64) -> f64 {
+    let current = *phase;
+    *phase = (*phase + frequency / sample_rate).fract();
+
+    current
+}
+
+/// Xorshift white noise in -1.0..1.0. Deterministic and allocation-free.
+#[derive(Debug, Clone)]
+struct Noise(u32);
+
+impl Default for Noise {
+    fn default() -> Self {
+        Self(0x1234_5678)
+    }
+}
+
+impl Noise {
+    fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+
+        self.0 as f64 / u32::MAX as f64 * 2.0 - 1.0
+    }
+}
+
+/// One-pole highpass.
+#[derive(Debug, Default, Clone)]
+struct Highpass {
+    input: f64,
+    output: f64,
+}
+
+impl Highpass {
+    fn process(&mut self, input: f64, cutoff: f64, sample_rate: f64) -> f64 {
+        let rc = 1.0 / (TAU * cutoff);
+        let a = rc / (rc + 1.0 / sample_rate);
+
+        self.output = a * (self.output + input - self.input);
+        self.input = input;
+
+        self.output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f64 = 48000.0;
+
+    fn render(model: Model, pitch: f64, frames: usize) -> Vec<f64> {
+        let mut state = ModelState::default();
+        state.trigger();
+
+        (0..frames)
+            .map(|_| state.tick(model, pitch, SAMPLE_RATE))
+            .collect()
+    }
+
+    fn zero_crossings(signal: &[f64]) -> usize {
+        signal
+            .windows(2)
+            .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+            .count()
+    }
+
+    #[test]
+    fn every_model_is_bounded() {
+        for model in [Model::Kick, Model::Snare, Model::Hat] {
+            let signal = render(model, 1.0, 4800);
+
+            assert!(signal.iter().all(|s| s.is_finite() && s.abs() <= 1.5));
+            assert!(signal.iter().any(|s| s.abs() > 0.1));
+        }
+    }
+
+    #[test]
+    fn kick_pitch_falls() {
+        let signal = render(Model::Kick, 1.0, 9600);
+
+        // Same window length early and late; the sweep makes early faster.
+        assert!(zero_crossings(&signal[..2400]) > zero_crossings(&signal[7200..]));
+    }
+
+    #[test]
+    fn pitch_scales_kick_frequency() {
+        let low = render(Model::Kick, 1.0, 9600);
+        let high = render(Model::Kick, 2.0, 9600);
+
+        assert!(zero_crossings(&high) > zero_crossings(&low) * 3 / 2);
+    }
+
+    #[test]
+    fn hat_has_no_dc() {
+        let signal = render(Model::Hat, 1.0, 48000);
+        let mean = signal.iter().sum::<f64>() / signal.len() as f64;
+
+        assert!(mean.abs() < 0.01);
+    }
+}
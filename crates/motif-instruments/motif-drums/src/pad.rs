@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use motif_sampler::sample::Sample;
+use wmidi::Note;
+
+use crate::model::Model;
+
+/// What a pad plays.
+#[derive(Debug, Clone)]
+pub enum Source {
+    /// A decoded sample, loaded off the audio thread before the kit is built.
+    Sample(Arc<Sample>),
+    Synth(Model),
+}
+
+/// One drum pad: a source triggered by a single MIDI note.
+#[derive(Debug, Clone)]
+pub struct Pad {
+    pub name: String,
+    pub note: Note,
+    pub source: Source,
+    /// Pitch offset in semitones.
+    pub tune: f64,
+    /// Time in seconds for the hit to fall by 60 dB.
+    pub decay: f64,
+    /// -1.0 (hard left) to 1.0 (hard right).
+    pub pan: f64,
+    /// Linear gain.
+    pub gain: f64,
+    /// Pads sharing a choke group cut each other off, e.g. closed hat
+    /// silencing open hat.
+    pub choke: Option<u8>,
+    /// Output bus. Bus `n` writes channels `2n` and `2n + 1`; buses the
+    /// output buffer doesn't have fall back to the main pair (bus 0).
+    pub output: usize,
+}
+
+impl Pad {
+    pub fn new(name: impl Into<String>, note: Note, source: Source) -> Self {
+        Self {
+            name: name.into(),
+            note,
+            source,
+            tune: 0.0,
+            decay: 0.5,
+            pan: 0.0,
+            gain: 1.0,
+            choke: None,
+            output: 0,
+        }
+    }
+
+    /// Frequency multiplier for `tune`.
+    pub fn pitch(&self) -> f64 {
+        (self.tune / 12.0).exp2()
+    }
+
+    /// Left and right gains under the balance law: centre is unity in both.
+    pub fn pan_gains(&self) -> (f64, f64) {
+        let pan = self.pan.clamp(-1.0, 1.0);
+
+        ((1.0 - pan).min(1.0), (1.0 + pan).min(1.0))
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{
+    model::ModelState,
+    pad::{Pad, Source},
+};
+
+/// Length of the fade applied when a choke group cuts a pad off, or a
+/// retrigger cuts off the pad's own hit. Long enough to avoid a click,
+/// short enough to sound like a hard cut.
+const CHOKE_SECONDS: f64 = 0.005;
+
+/// 60 dB expressed as a natural-log amplitude ratio (ln 1000).
+const DECAY_60DB: f64 = 6.907_755_278_982_137;
+
+/// Playback state of one pad. Each pad has exactly one voice, so a
+/// retrigger restarts the hit like a hardware drum machine, once the old
+/// hit has faded out.
+#[derive(Debug, Default)]
+pub struct PadVoice {
+    pub active: bool,
+    /// Seconds since the hit.
+    pub time: f64,
+    /// Read position in sample frames, for sample pads.
+    pub position: f64,
+    /// Velocity gain fixed at trigger.
+    pub gain: f64,
+    // Remaining gain while a choke fades the voice out.
+    choke: Option<f64>,
+    // Gain of a retrigger waiting for the choke fade to finish.
+    pending: Option<f64>,
+    model: ModelState,
+}
+
+impl PadVoice {
+    /// Start a hit. A hit that's still sounding is choked first.
+    pub fn trigger(&mut self, gain: f64) {
+        if self.active {
+            self.choke.get_or_insert(1.0);
+            self.pending = Some(gain);
+            return;
+        }
+
+        self.start(gain);
+    }
+
+    /// Fade out quickly, dropping any queued retrigger. Does nothing to an
+    /// idle voice.
+    pub fn choke(&mut self) {
+        if self.active {
+            self.choke.get_or_insert(1.0);
+            self.pending = None;
+        }
+    }
+
+    fn start(&mut self, gain: f64) {
+        self.active = true;
+        self.time = 0.0;
+        self.position = 0.0;
+        self.gain = gain;
+        self.choke = None;
+        self.model.trigger();
+    }
+
+    /// Render one stereo sample before panning. Stereo samples keep their
+    /// image; mono sources play on both channels.
+    pub fn render(&mut self, pad: &Pad, sample_rate: f64) -> (f64, f64) {
+        let decay = pad.decay.max(1e-3);
+        let mut amplitude = (-self.time * DECAY_60DB / decay).exp() * self.gain * pad.gain;
+
+        if let Some(choke) = &mut self.choke {
+            amplitude *= *choke;
+            *choke -= 1.0 / (CHOKE_SECONDS * sample_rate);
+
+            if *choke <= 0.0 {
+                self.active = false;
+            }
+        }
+
+        let (left, right) = match &pad.source {
+            Source::Sample(sample) => {
+                let frame = (
+                    sample.read(0, self.position, None),
+                    sample.read(1, self.position, None),
+                );
+
+                self.position += pad.pitch() * sample.sample_rate / sample_rate;
+
+                if self.position >= sample.frames() as f64 {
+                    self.active = false;
+                }
+
+                frame
+            }
+            Source::Synth(model) => {
+                let value = self.model.tick(*model, pad.pitch(), sample_rate);
+
+                (value, value)
+            }
+        };
+
+        self.time += 1.0 / sample_rate;
+
+        if self.time >= decay {
+            self.active = false;
+        }
+
+        if !self.active
+            && let Some(gain) = self.pending.take()
+        {
+            self.start(gain);
+        }
+
+        (left * amplitude, right * amplitude)
+    }
+
+    pub fn reset(&mut self) {
+        self.active = false;
+        self.choke = None;
+        self.pending = None;
+    }
+}
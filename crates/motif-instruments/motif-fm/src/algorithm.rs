@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+/// Operators per voice.
+pub const OPERATORS: usize = 4;
+
+/// How the four operators are wired. Operator 1 is the top of every stack
+/// and the only one with feedback; modulators always have a lower number
+/// than what they modulate, so operators render in order 1–4.
+///
+/// Diagrams read top to bottom; `+` mixes outputs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Algorithm {
+    /// 1 → 2 → 3 → 4. One deep stack.
+    #[default]
+    Stack,
+    /// (1 + 2) → 3 → 4.
+    TwoIntoStack,
+    /// (1 + (2 → 3)) → 4.
+    BranchIntoFour,
+    /// ((1 → 2) + 3) → 4.
+    StackAndThreeIntoFour,
+    /// (1 → 2) + (3 → 4). Two independent pairs.
+    TwoPairs,
+    /// 1 → (2 + 3 + 4). One modulator driving three carriers.
+    OneIntoThree,
+    /// (1 → 2) + 3 + 4.
+    PairAndTwo,
+    /// 1 + 2 + 3 + 4. Additive, no modulation.
+    Additive,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 8] = [
+        Algorithm::Stack,
+        Algorithm::TwoIntoStack,
+        Algorithm::BranchIntoFour,
+        Algorithm::StackAndThreeIntoFour,
+        Algorithm::TwoPairs,
+        Algorithm::OneIntoThree,
+        Algorithm::PairAndTwo,
+        Algorithm::Additive,
+    ];
+
+    /// `modulators()[target][source]` is true when `source` modulates
+    /// `target`. Indices are zero-based.
+    pub fn modulators(self) -> [[bool; OPERATORS]; OPERATORS] {
+        let mut matrix = [[false; OPERATORS]; OPERATORS];
+
+        let edges: &[(usize, usize)] = match self {
+            Algorithm::Stack => &[(0, 1), (1, 2), (2, 3)],
+            Algorithm::TwoIntoStack => &[(0, 2), (1, 2), (2, 3)],
+            Algorithm::BranchIntoFour => &[(0, 3), (1, 2), (2, 3)],
+            Algorithm::StackAndThreeIntoFour => &[(0, 1), (1, 3), (2, 3)],
+            Algorithm::TwoPairs => &[(0, 1), (2, 3)],
+            Algorithm::OneIntoThree => &[(0, 1), (0, 2), (0, 3)],
+            Algorithm::PairAndTwo => &[(0, 1)],
+            Algorithm::Additive => &[],
+        };
+
+        for &(source, target) in edges {
+            matrix[target][source] = true;
+        }
+
+        matrix
+    }
+
+    /// Operators whose output is heard.
+    pub fn carriers(self) -> [bool; OPERATORS] {
+        let matrix = self.modulators();
+
+        // A carrier modulates nothing.
+        std::array::from_fn(|source| !matrix.iter().any(|sources| sources[source]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modulators_always_render_first() {
+        for algorithm in Algorithm::ALL {
+            let matrix = algorithm.modulators();
+
+            for (target, sources) in matrix.iter().enumerate() {
+                for (source, &modulates) in sources.iter().enumerate() {
+                    assert!(!modulates || source < target, "{algorithm:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_algorithm_has_a_carrier() {
+        for algorithm in Algorithm::ALL {
+            assert!(algorithm.carriers().contains(&true), "{algorithm:?}");
+        }
+    }
+
+    #[test]
+    fn carrier_counts() {
+        let count = |a: Algorithm| a.carriers().iter().filter(|&&c| c).count();
+
+        assert_eq!(count(Algorithm::Stack), 1);
+        assert_eq!(count(Algorithm::TwoPairs), 2);
+        assert_eq!(count(Algorithm::OneIntoThree), 3);
+        assert_eq!(count(Algorithm::Additive), 4);
+    }
+}
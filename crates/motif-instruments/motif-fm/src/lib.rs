@@ -0,0 +1,6 @@
+pub mod algorithm;
+pub mod error;
+pub mod operator;
+pub mod preset;
+pub mod synth;
+pub mod voice;
@@ -0,0 +1,85 @@
+use motif_engine::envelope::Curve;
+use serde::{Deserialize, Serialize};
+use wmidi::Velocity;
+
+/// One sine operator's settings, shared by every voice.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Operator {
+    /// Frequency as a multiple of the note frequency.
+    pub ratio: f64,
+    /// Fixed offset in Hz added after the ratio, for inharmonic beating.
+    pub detune: f64,
+    /// Output level (0.0–1.0). For modulators this sets modulation depth.
+    pub level: f64,
+    /// How much velocity reduces the level (0.0 = ignore velocity,
+    /// 1.0 = silent at velocity 0).
+    pub velocity: f64,
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+    pub attack_curve: Curve,
+    pub decay_curve: Curve,
+    pub release_curve: Curve,
+}
+
+impl Default for Operator {
+    fn default() -> Self {
+        Self {
+            ratio: 1.0,
+            detune: 0.0,
+            level: 1.0,
+            velocity: 1.0,
+            attack: 0.01,
+            decay: 0.1,
+            sustain: 0.7,
+            release: 0.15,
+            attack_curve: Curve::Linear,
+            decay_curve: Curve::Exponential,
+            release_curve: Curve::Exponential,
+        }
+    }
+}
+
+impl Operator {
+    /// Level after velocity scaling.
+    pub fn scaled_level(&self, velocity: Velocity) -> f64 {
+        let velocity = u8::from(velocity) as f64 / 127.0;
+        let sensitivity = self.velocity.clamp(0.0, 1.0);
+
+        self.level * (1.0 - sensitivity * (1.0 - velocity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_velocity_keeps_level() {
+        let operator = Operator {
+            level: 0.8,
+            ..Operator::default()
+        };
+
+        assert_eq!(operator.scaled_level(Velocity::MAX), 0.8);
+    }
+
+    #[test]
+    fn sensitivity_scales_with_velocity() {
+        let soft = Velocity::try_from(0).unwrap();
+        let insensitive = Operator {
+            velocity: 0.0,
+            ..Operator::default()
+        };
+        let half = Operator {
+            velocity: 0.5,
+            ..Operator::default()
+        };
+
+        assert_eq!(insensitive.scaled_level(soft), 1.0);
+        assert_eq!(half.scaled_level(soft), 0.5);
+        assert_eq!(Operator::default().scaled_level(soft), 0.0);
+    }
+}
@@ -0,0 +1,220 @@
+use std::path::Path;
+
+use motif_engine::{envelope::Curve, steal::StealPolicy};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    algorithm::{Algorithm, OPERATORS},
+    error::PresetError,
+    operator::Operator,
+};
+
+/// A named snapshot of every Fm sound parameter. Polyphony is not
+/// included — it sizes the voice pool at construction.
+///
+/// Stored as RON. Missing fields fall back to the `Init` values so older
+/// preset files keep loading as parameters are added.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FmPreset {
+    pub name: String,
+    pub algorithm: Algorithm,
+    pub feedback: f64,
+    pub operators: [Operator; OPERATORS],
+    pub steal_policy: StealPolicy,
+}
+
+impl Default for FmPreset {
+    fn default() -> Self {
+        // A plain sine: only the final carrier of the stack is heard, and
+        // the modulators above it are silent.
+        let silent = Operator {
+            level: 0.0,
+            ..Operator::default()
+        };
+
+        Self {
+            name: "Init".to_string(),
+            algorithm: Algorithm::Stack,
+            feedback: 0.0,
+            operators: [silent, silent, silent, Operator::default()],
+            steal_policy: StealPolicy::Oldest,
+        }
+    }
+}
+
+impl FmPreset {
+    pub fn from_ron(source: &str) -> Result<Self, PresetError> {
+        Ok(ron::from_str(source)?)
+    }
+
+    pub fn to_ron(&self) -> Result<String, PresetError> {
+        Ok(ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    /// Read a preset file. Blocking I/O — never call from the audio thread.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PresetError> {
+        Self::from_ron(&std::fs::read_to_string(path)?)
+    }
+
+    /// Write a preset file. Blocking I/O — never call from the audio thread.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PresetError> {
+        std::fs::write(path, self.to_ron()?)?;
+
+        Ok(())
+    }
+}
+
+/// Presets shipped with the crate. Index is the MIDI program number.
+pub fn factory() -> Vec<FmPreset> {
+    let op =
+        |ratio: f64, level: f64, attack: f32, decay: f32, sustain: f32, release: f32| Operator {
+            ratio,
+            level,
+            attack,
+            decay,
+            sustain,
+            release,
+            ..Operator::default()
+        };
+
+    vec![
+        FmPreset::default(),
+        FmPreset {
+            name: "E. Piano".to_string(),
+            algorithm: Algorithm::TwoPairs,
+            operators: [
+                op(14.0, 0.15, 0.0, 0.4, 0.0, 0.2),
+                op(1.0, 1.0, 0.002, 1.8, 0.0, 0.4),
+                Operator {
+                    velocity: 0.8,
+                    ..op(1.0, 0.35, 0.0, 1.2, 0.1, 0.3)
+                },
+                op(1.0, 1.0, 0.002, 2.5, 0.0, 0.5),
+            ],
+            ..FmPreset::default()
+        },
+        FmPreset {
+            name: "Bass".to_string(),
+            algorithm: Algorithm::Stack,
+            feedback: 0.3,
+            operators: [
+                op(1.0, 0.3, 0.0, 0.3, 0.2, 0.1),
+                op(0.5, 0.4, 0.0, 0.2, 0.3, 0.1),
+                op(1.0, 0.5, 0.0, 0.15, 0.2, 0.1),
+                Operator {
+                    velocity: 0.5,
+                    ..op(0.5, 1.0, 0.002, 0.8, 0.6, 0.08)
+                },
+            ],
+            ..FmPreset::default()
+        },
+        FmPreset {
+            name: "Bell".to_string(),
+            algorithm: Algorithm::TwoPairs,
+            operators: [
+                op(3.5, 0.6, 0.0, 3.0, 0.0, 2.0),
+                op(1.0, 1.0, 0.001, 4.0, 0.0, 3.0),
+                Operator {
+                    detune: 1.5,
+                    ..op(7.0, 0.3, 0.0, 1.5, 0.0, 1.0)
+                },
+                op(2.0, 0.5, 0.001, 3.0, 0.0, 2.0),
+            ],
+            ..FmPreset::default()
+        },
+        FmPreset {
+            name: "Brass".to_string(),
+            algorithm: Algorithm::StackAndThreeIntoFour,
+            feedback: 0.4,
+            operators: [
+                Operator {
+                    attack_curve: Curve::Exponential,
+                    ..op(1.0, 0.35, 0.08, 0.3, 0.6, 0.2)
+                },
+                op(1.0, 0.3, 0.06, 0.2, 0.7, 0.2),
+                op(1.0, 0.2, 0.05, 0.2, 0.5, 0.2),
+                op(1.0, 1.0, 0.04, 0.2, 0.85, 0.25),
+            ],
+            ..FmPreset::default()
+        },
+        FmPreset {
+            name: "Organ".to_string(),
+            algorithm: Algorithm::Additive,
+            operators: [
+                Operator {
+                    velocity: 0.0,
+                    ..op(0.5, 0.8, 0.005, 0.0, 1.0, 0.05)
+                },
+                Operator {
+                    velocity: 0.0,
+                    ..op(1.0, 1.0, 0.005, 0.0, 1.0, 0.05)
+                },
+                Operator {
+                    velocity: 0.0,
+                    ..op(2.0, 0.7, 0.005, 0.0, 1.0, 0.05)
+                },
+                Operator {
+                    velocity: 0.0,
+                    ..op(4.0, 0.4, 0.005, 0.0, 1.0, 0.05)
+                },
+            ],
+            ..FmPreset::default()
+        },
+        FmPreset {
+            name: "Pluck".to_string(),
+            algorithm: Algorithm::BranchIntoFour,
+            feedback: 0.2,
+            operators: [
+                op(3.0, 0.4, 0.0, 0.12, 0.0, 0.1),
+                op(5.0, 0.2, 0.0, 0.08, 0.0, 0.1),
+                op(1.0, 0.3, 0.0, 0.2, 0.0, 0.1),
+                op(1.0, 1.0, 0.001, 0.6, 0.0, 0.2),
+            ],
+            ..FmPreset::default()
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ron_round_trip() {
+        for preset in factory() {
+            let ron = preset.to_ron().unwrap();
+
+            assert_eq!(FmPreset::from_ron(&ron).unwrap(), preset);
+        }
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let preset = FmPreset::from_ron("(name: \"Fb\", feedback: 0.5)").unwrap();
+
+        assert_eq!(preset.name, "Fb");
+        assert_eq!(preset.feedback, 0.5);
+        assert_eq!(preset.operators, FmPreset::default().operators);
+    }
+
+    #[test]
+    fn invalid_ron_is_an_error() {
+        assert!(matches!(
+            FmPreset::from_ron("(algorithm: Five)"),
+            Err(PresetError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn factory_names_are_unique() {
+        let bank = factory();
+
+        for (i, preset) in bank.iter().enumerate() {
+            assert!(!bank[i + 1..].iter().any(|p| p.name == preset.name));
+        }
+    }
+}
@@ -0,0 +1,486 @@
+use std::ops::Range;
+
+use motif_core::tuning::Tuning;
+use motif_engine::{
+    buffer::AudioBuffer,
+    events::{Event, MidiEvent},
+    node::AudioNode,
+    steal::{StealPolicy, StealVoice},
+};
+
+use crate::{
+    algorithm::{Algorithm, OPERATORS},
+    operator::Operator,
+    preset::{self, FmPreset},
+    voice::{Routing, Strike, Voice},
+};
+
+/// Scales the summed voices. A carrier at full level is a full-scale sine,
+/// so this leaves room for a chord of them.
+const GAIN: f64 = 0.2;
+
+/// Voice count used by `Fm::new()`.
+pub const DEFAULT_POLYPHONY: usize = 8;
+
+/// Polyphonic 4-operator FM synthesizer. Implements AudioNode —
+/// feed it NoteOn/NoteOff events via evaluate_node() and it produces audio.
+/// Operator settings are shared; each note copies envelope params when it starts.
+/// ProgramChange switches to a preset from `bank`.
+#[derive(Debug)]
+pub struct Fm {
+    /// One per sounding note, sized by `with_polyphony`.
+    pub voices: Vec<Voice>,
+    pub steal_policy: StealPolicy,
+    pub algorithm: Algorithm,
+    /// Operator 1 self-modulation (0.0–1.0).
+    pub feedback: f64,
+    pub operators: [Operator; OPERATORS],
+    /// Presets addressable by ProgramChange. Never resized on the audio thread.
+    pub bank: Vec<FmPreset>,
+    /// Note → frequency table. Unmapped notes are ignored.
+    pub tuning: Tuning,
+    pub next_age: u64,
+}
+
+impl Default for Fm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fm {
+    pub fn new() -> Self {
+        Self::with_polyphony(DEFAULT_POLYPHONY)
+    }
+
+    /// Build a synth with a fixed voice count. Panics if `polyphony` is zero.
+    pub fn with_polyphony(polyphony: usize) -> Self {
+        assert!(polyphony > 0, "Fm needs at least one voice");
+
+        let init = FmPreset::default();
+
+        Self {
+            voices: (0..polyphony).map(|_| Voice::new()).collect(),
+            steal_policy: init.steal_policy,
+            algorithm: init.algorithm,
+            feedback: init.feedback,
+            operators: init.operators,
+            bank: preset::factory(),
+            tuning: Tuning::default(),
+            next_age: 0,
+        }
+    }
+
+    /// Copy every sound parameter from a preset. Voices already sounding
+    /// keep their envelopes until retriggered. Real-time safe.
+    pub fn apply_preset(&mut self, preset: &FmPreset) {
+        self.algorithm = preset.algorithm;
+        self.feedback = preset.feedback;
+        self.operators = preset.operators;
+        self.steal_policy = preset.steal_policy;
+    }
+
+    /// Capture the current parameters as a named preset.
+    pub fn to_preset(&self, name: impl Into<String>) -> FmPreset {
+        FmPreset {
+            name: name.into(),
+            algorithm: self.algorithm,
+            feedback: self.feedback,
+            operators: self.operators,
+            steal_policy: self.steal_policy,
+        }
+    }
+
+    fn routing(&self) -> Routing {
+        let carriers = self.algorithm.carriers();
+        let count = carriers.iter().filter(|&&c| c).count().max(1);
+
+        Routing {
+            modulators: self.algorithm.modulators(),
+            carriers,
+            carrier_gain: 1.0 / count as f64,
+        }
+    }
+}
+
+impl AudioNode for Fm {
+    fn render(
+        &mut self,
+        _inputs: &[&AudioBuffer],
+        output: &mut AudioBuffer,
+        frame_range: Range<usize>,
+        sample_rate: f64,
+    ) {
+        let routing = self.routing();
+        let feedback = self.feedback.clamp(0.0, 1.0);
+        let (left, right) = output.two_channels_mut(0, 1);
+
+        for frame in frame_range {
+            let mut sum = 0.0;
+
+            for voice in &mut self.voices {
+                if voice.is_active() {
+                    sum += voice.render(&self.operators, &routing, feedback, sample_rate);
+                }
+            }
+
+            let sample = (sum * GAIN) as f32;
+            left[frame] = sample;
+            right[frame] = sample;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::Midi(event) => match event {
+                MidiEvent::NoteOn { note, velocity } => {
+                    let Some(frequency) = self.tuning.frequency(*note) else {
+                        return;
+                    };
+
+                    let voice_index = self.steal_policy.pick(&self.voices, *note);
+
+                    let strike = Strike {
+                        velocity: *velocity,
+                        frequency,
+                        levels: self.operators.map(|op| op.scaled_level(*velocity)),
+                        operators: self.operators,
+                    };
+
+                    self.voices[voice_index].trigger(*note, strike, self.next_age);
+                    self.next_age += 1;
+                }
+                MidiEvent::NoteOff { note } => {
+                    for voice in &mut self.voices {
+                        if voice.note == Some(*note) && voice.held {
+                            voice.held = false;
+                            voice.release();
+                        }
+                    }
+                }
+                MidiEvent::ProgramChange { program } => {
+                    // Take the bank so the preset can be borrowed while self is mutated.
+                    // Swapping with an empty Vec doesn't allocate.
+                    let bank = std::mem::take(&mut self.bank);
+
+                    if let Some(preset) = bank.get(u8::from(*program) as usize) {
+                        self.apply_preset(preset);
+                    }
+
+                    self.bank = bank;
+                }
+                MidiEvent::ControlChange { .. } => {}
+            },
+        }
+    }
+
+    fn reset(&mut self) {
+        for voice in &mut self.voices {
+            voice.reset();
+        }
+
+        self.next_age = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use motif_core::tuning::{KeyboardMapping, Scale};
+    use motif_engine::{
+        events::ScheduledEvent,
+        graph::evaluate_node,
+        test_util::{
+            SAMPLE_RATE, has_signal, is_silent, note_off, note_on, note_on_velocity, peak,
+        },
+    };
+    use wmidi::{Note, Velocity};
+
+    fn zero_crossings(buf: &AudioBuffer) -> usize {
+        buf.channel(0)
+            .windows(2)
+            .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+            .count()
+    }
+
+    /// Render a single held A4 for 4800 samples.
+    fn render_a4(synth: &mut Fm) -> AudioBuffer {
+        let mut output = AudioBuffer::new(2, 4800);
+        output.prepare(4800);
+
+        let events = [note_on(0, Note::A4)];
+        evaluate_node(synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        output
+    }
+
+    #[test]
+    fn note_on_produces_output() {
+        let mut synth = Fm::new();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(has_signal(&output, 0..256));
+    }
+
+    #[test]
+    fn no_events_is_silent() {
+        let mut synth = Fm::new();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        evaluate_node(&mut synth, &[], &mut output, &[], SAMPLE_RATE);
+
+        assert!(is_silent(&output, 0..256));
+    }
+
+    #[test]
+    fn note_off_releases_to_silence() {
+        let mut synth = Fm::new();
+        let mut output = AudioBuffer::new(2, 1024);
+
+        output.prepare(512);
+        let events = [note_on(0, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+        assert!(has_signal(&output, 0..512));
+
+        // Release is 0.15s = 7200 samples.
+        output.prepare(1024);
+        let events = [note_off(0, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        for _ in 0..10 {
+            output.prepare(1024);
+            evaluate_node(&mut synth, &[], &mut output, &[], SAMPLE_RATE);
+        }
+
+        assert!(is_silent(&output, 0..1024));
+        assert!(!synth.voices[0].is_active());
+    }
+
+    #[test]
+    fn polyphony_three_notes() {
+        let mut synth = Fm::new();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [
+            note_on(0, Note::C4),
+            note_on(0, Note::E4),
+            note_on(0, Note::G4),
+        ];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        let active = synth.voices.iter().filter(|v| v.is_active()).count();
+        assert_eq!(active, 3);
+    }
+
+    #[test]
+    fn voice_steal_at_capacity() {
+        let mut synth = Fm::with_polyphony(2);
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [
+            note_on(0, Note::C4),
+            note_on(0, Note::E4),
+            note_on(0, Note::G4),
+        ];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        let notes: Vec<_> = synth.voices.iter().map(|v| v.note).collect();
+        assert_eq!(notes, vec![Some(Note::G4), Some(Note::E4)]);
+    }
+
+    #[test]
+    fn steal_keeps_the_old_note_envelope() {
+        let mut synth = Fm::with_polyphony(1);
+        synth.operators.iter_mut().for_each(|op| op.release = 2.0);
+        let mut output = AudioBuffer::new(2, 512);
+
+        output.prepare(512);
+        let events = [note_on(0, Note::C4), note_off(256, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        // A near-instant release must only apply to the incoming note.
+        synth.operators.iter_mut().for_each(|op| op.release = 0.001);
+        output.prepare(64);
+        let events = [note_on(0, Note::E4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        let envelope = &synth.voices[0].envelopes[0];
+        assert!(envelope.is_releasing());
+        assert!(envelope.level() > 0.5, "{}", envelope.level());
+    }
+
+    #[test]
+    fn output_is_mono_both_channels_equal() {
+        let mut synth = Fm::new();
+        let output = render_a4(&mut synth);
+
+        assert_eq!(output.channel(0), output.channel(1));
+    }
+
+    #[test]
+    fn init_is_a_pure_sine_at_note_frequency() {
+        let mut synth = Fm::new();
+        let output = render_a4(&mut synth);
+
+        // 440 Hz over 0.1s crosses zero 88 times.
+        assert!((86..=90).contains(&zero_crossings(&output)));
+    }
+
+    #[test]
+    fn carrier_ratio_multiplies_frequency() {
+        let mut synth = Fm::new();
+        synth.operators[3].ratio = 2.0;
+        let output = render_a4(&mut synth);
+
+        assert!((174..=178).contains(&zero_crossings(&output)));
+    }
+
+    #[test]
+    fn modulation_adds_harmonics() {
+        let mut plain = Fm::new();
+        let mut modulated = Fm::new();
+        modulated.operators[2].level = 1.0;
+        modulated.operators[2].ratio = 3.0;
+
+        let plain = render_a4(&mut plain);
+        let modulated = render_a4(&mut modulated);
+
+        assert!(zero_crossings(&modulated) > zero_crossings(&plain));
+    }
+
+    #[test]
+    fn silent_modulators_do_not_change_algorithm_output() {
+        // With operators 1–3 at zero level, every algorithm that has 4 as a
+        // carrier plays the same sine, scaled by its carrier count.
+        let mut stack = Fm::new();
+        let mut pairs = Fm::new();
+        pairs.algorithm = Algorithm::TwoPairs;
+        pairs.operators[1].level = 0.0;
+
+        let stack = render_a4(&mut stack);
+        let pairs = render_a4(&mut pairs);
+
+        for (a, b) in stack.channel(0).iter().zip(pairs.channel(0)) {
+            assert!((a / 2.0 - b).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn feedback_changes_the_sound() {
+        // Operator 1 alone as a carrier, with and without self-modulation.
+        let additive = |feedback| {
+            let mut synth = Fm::new();
+            synth.algorithm = Algorithm::Additive;
+            synth.feedback = feedback;
+            synth.operators[0].level = 1.0;
+            synth.operators[3].level = 0.0;
+            synth
+        };
+
+        let dry = render_a4(&mut additive(0.0));
+        let fed = render_a4(&mut additive(0.8));
+
+        assert!(has_signal(&dry, 0..4800));
+        assert_ne!(dry.channel(0), fed.channel(0));
+    }
+
+    #[test]
+    fn velocity_scales_carrier_level() {
+        let soft = Velocity::try_from(32).unwrap();
+
+        let mut loud = Fm::new();
+        let mut quiet = Fm::new();
+        let mut output = AudioBuffer::new(2, 1024);
+
+        output.prepare(1024);
+        let events = [note_on(0, Note::A4)];
+        evaluate_node(&mut loud, &[], &mut output, &events, SAMPLE_RATE);
+        let loud_peak = peak(&output);
+
+        output.prepare(1024);
+        let events = [note_on_velocity(0, Note::A4, soft)];
+        evaluate_node(&mut quiet, &[], &mut output, &events, SAMPLE_RATE);
+        let quiet_peak = peak(&output);
+
+        assert!(quiet_peak < loud_peak * 0.5);
+    }
+
+    #[test]
+    fn zero_sensitivity_ignores_velocity() {
+        let soft = Velocity::try_from(32).unwrap();
+
+        let mut synth = Fm::new();
+        synth.operators[3].velocity = 0.0;
+        let mut output = AudioBuffer::new(2, 1024);
+
+        output.prepare(1024);
+        let events = [note_on(0, Note::A4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+        let loud_peak = peak(&output);
+
+        synth.reset();
+        output.prepare(1024);
+        let events = [note_on_velocity(0, Note::A4, soft)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert_eq!(peak(&output), loud_peak);
+    }
+
+    fn program_change(offset: u32, program: u8) -> ScheduledEvent {
+        ScheduledEvent {
+            sample_offset: offset,
+            event: Event::Midi(MidiEvent::ProgramChange {
+                program: wmidi::ProgramNumber::new(program).unwrap(),
+            }),
+        }
+    }
+
+    #[test]
+    fn program_change_applies_bank_preset() {
+        let mut synth = Fm::new();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [program_change(0, 1)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert_eq!(synth.to_preset("E. Piano"), synth.bank[1]);
+    }
+
+    #[test]
+    fn every_factory_preset_plays() {
+        for preset in preset::factory() {
+            let mut synth = Fm::new();
+            synth.apply_preset(&preset);
+            let output = render_a4(&mut synth);
+
+            assert!(has_signal(&output, 0..4800), "{}", preset.name);
+            assert!(peak(&output) <= 1.0, "{}", preset.name);
+        }
+    }
+
+    #[test]
+    fn unmapped_note_is_silent() {
+        let mut synth = Fm::new();
+        let mapping = KeyboardMapping {
+            first_note: 60,
+            ..KeyboardMapping::default()
+        };
+        synth.tuning = Tuning::new(&Scale::default(), &mapping).unwrap();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::C3)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(is_silent(&output, 0..256));
+    }
+}
@@ -0,0 +1,185 @@
+use std::f64::consts::TAU;
+
+use motif_engine::{
+    envelope::Envelope,
+    steal::{StealFade, StealVoice},
+};
+use wmidi::{Note, Velocity};
+
+use crate::{algorithm::OPERATORS, operator::Operator};
+
+/// Phase deviation, in cycles, of a modulator at full level. Around the
+/// brightness of a classic FM modulator turned all the way up.
+const MODULATION_INDEX: f64 = 2.0;
+
+/// Single voice of polyphony. Owns a phase accumulator and an envelope per
+/// operator. Fm allocates a fixed pool of these; idle voices are skipped
+/// during render.
+#[derive(Debug, Default)]
+pub struct Voice {
+    pub phases: [f64; OPERATORS],
+    pub envelopes: [Envelope; OPERATORS],
+    pub frequency: f64,
+    pub velocity: Velocity,
+    /// Operator levels after velocity scaling.
+    pub levels: [f64; OPERATORS],
+    pub note: Option<Note>,
+    // NoteOn count when this note started, for stealing.
+    pub age: u64,
+    /// Key is physically down.
+    pub held: bool,
+    // Last two outputs of operator 1, averaged for feedback.
+    history: [f64; 2],
+    steal: StealFade<Strike>,
+}
+
+/// Per-note values fixed at NoteOn, after tuning and velocity scaling.
+#[derive(Debug, Clone, Copy)]
+pub struct Strike {
+    pub velocity: Velocity,
+    pub frequency: f64,
+    pub levels: [f64; OPERATORS],
+    /// Operator settings at NoteOn. Only their envelope params are copied,
+    /// so a note being stolen keeps its own.
+    pub operators: [Operator; OPERATORS],
+}
+
+/// Algorithm wiring, resolved once per render call.
+#[derive(Debug, Clone, Copy)]
+pub struct Routing {
+    pub modulators: [[bool; OPERATORS]; OPERATORS],
+    pub carriers: [bool; OPERATORS],
+    /// 1/carrier count, so algorithms with more carriers aren't louder.
+    pub carrier_gain: f64,
+}
+
+impl Voice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render one mono sample. `feedback` (0.0–1.0) is operator 1's
+    /// self-modulation depth.
+    pub fn render(
+        &mut self,
+        operators: &[Operator; OPERATORS],
+        routing: &Routing,
+        feedback: f64,
+        sample_rate: f64,
+    ) -> f64 {
+        let mut outputs = [0.0; OPERATORS];
+
+        for index in 0..OPERATORS {
+            let envelope = self.envelopes[index].tick(sample_rate);
+
+            let mut modulation: f64 = outputs[..index]
+                .iter()
+                .zip(routing.modulators[index])
+                .filter(|(_, modulates)| *modulates)
+                .map(|(output, _)| output)
+                .sum();
+
+            if index == 0 {
+                modulation += feedback * (self.history[0] + self.history[1]) / 2.0;
+            }
+
+            let operator = &operators[index];
+            let phase = self.phases[index];
+
+            outputs[index] = (TAU * (phase + modulation * MODULATION_INDEX)).sin()
+                * envelope
+                * self.levels[index];
+
+            let frequency = self.frequency * operator.ratio + operator.detune;
+            self.phases[index] = (phase + frequency / sample_rate).rem_euclid(1.0);
+        }
+
+        self.history = [outputs[0], self.history[0]];
+
+        let mut sum: f64 = outputs
+            .iter()
+            .zip(routing.carriers)
+            .filter(|(_, carrier)| *carrier)
+            .map(|(output, _)| output)
+            .sum();
+        sum *= routing.carrier_gain * self.steal.gain();
+
+        self.tick_steal(sample_rate);
+
+        sum
+    }
+
+    /// Start a note on all four operators.
+    pub fn trigger(&mut self, note: Note, strike: Strike, age: u64) {
+        self.note = Some(note);
+        self.age = age;
+        self.held = true;
+        self.play(strike);
+    }
+
+    pub fn reset(&mut self) {
+        self.note = None;
+        self.held = false;
+        self.steal.reset();
+        self.history = [0.0; 2];
+        self.envelopes.iter_mut().for_each(Envelope::reset);
+    }
+}
+
+impl StealVoice for Voice {
+    type Strike = Strike;
+
+    fn steal_fade(&self) -> &StealFade<Strike> {
+        &self.steal
+    }
+
+    fn steal_fade_mut(&mut self) -> &mut StealFade<Strike> {
+        &mut self.steal
+    }
+
+    fn age(&self) -> u64 {
+        self.age
+    }
+
+    fn note(&self) -> Option<Note> {
+        self.note
+    }
+
+    /// Sounding while any operator's envelope runs.
+    fn is_sounding(&self) -> bool {
+        self.envelopes.iter().any(|e| !e.is_idle())
+    }
+
+    fn is_sounding_released(&self) -> bool {
+        self.envelopes
+            .iter()
+            .all(|e| e.is_idle() || e.is_releasing())
+    }
+
+    /// Loudest operator amplitude.
+    fn sounding_level(&self) -> f64 {
+        self.envelopes
+            .iter()
+            .zip(self.levels)
+            .map(|(envelope, level)| envelope.level() * level)
+            .fold(0.0, f64::max)
+    }
+
+    fn start(&mut self, strike: Strike) {
+        self.phases = [0.0; OPERATORS];
+        self.history = [0.0; 2];
+        self.velocity = strike.velocity;
+        self.frequency = strike.frequency;
+        self.levels = strike.levels;
+
+        for (envelope, op) in self.envelopes.iter_mut().zip(&strike.operators) {
+            envelope.adsr(op.attack, op.decay, op.sustain, op.release);
+            envelope.curves(op.attack_curve, op.decay_curve, op.release_curve);
+            envelope.trigger();
+        }
+    }
+
+    fn release_sounding(&mut self) {
+        self.envelopes.iter_mut().for_each(Envelope::release);
+    }
+}
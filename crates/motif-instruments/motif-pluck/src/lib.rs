@@ -0,0 +1,3 @@
+pub mod string;
+pub mod synth;
+pub mod voice;
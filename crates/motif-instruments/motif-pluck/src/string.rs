@@ -0,0 +1,363 @@
+use std::f64::consts::TAU;
+
+/// Delay line length in samples. Holds one period of C-1 at 48 kHz with
+/// room to spare; lower pitches are clamped to it.
+const BUFFER_SIZE: usize = 16384;
+
+const MASK: usize = BUFFER_SIZE - 1;
+
+/// Shortest delay line the loop can run with.
+const MIN_DELAY: usize = 2;
+
+/// Lower bound of the allpass delay. Coefficients near a zero delay put the
+/// allpass pole on the unit circle.
+const MIN_FRACTION: f64 = 0.1;
+
+/// Highest loop gain, so a string never rings forever.
+const MAX_LOOP_GAIN: f64 = 0.99999;
+
+/// How the string is set in motion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Excitation {
+    /// Peak amplitude of the burst.
+    pub gain: f64,
+    /// Where along the string it is plucked (0.0 = bridge, 0.5 = middle).
+    /// Harmonics with a node at that point are cancelled.
+    pub pick_position: f64,
+    /// Noise brightness (0.0 = dark, 1.0 = white).
+    pub color: f64,
+}
+
+/// Extended Karplus-Strong string: a delay line fed back through a one-zero
+/// damping filter and a first-order allpass that supplies the fractional
+/// part of the period, so pitch is exact rather than rounded to a sample.
+///
+/// The delay line and burst scratch are allocated once in `new()`.
+/// Everything else is real-time safe.
+#[derive(Debug)]
+pub struct StringModel {
+    buffer: Vec<f64>,
+    // Raw noise for the next burst, kept so plucking doesn't allocate.
+    scratch: Vec<f64>,
+    write: usize,
+    /// Integer part of the loop delay.
+    delay: usize,
+    /// Weight of the previous sample in the damping filter (0.0–0.5).
+    smoothing: f64,
+    /// Damping filter gain at the fundamental.
+    smoothing_gain: f64,
+    allpass: f64,
+    loop_gain: f64,
+    frequency: f64,
+    previous: f64,
+    allpass_input: f64,
+    allpass_output: f64,
+    noise: Noise,
+}
+
+impl Default for StringModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StringModel {
+    pub fn new() -> Self {
+        Self {
+            buffer: vec![0.0; BUFFER_SIZE],
+            scratch: vec![0.0; BUFFER_SIZE],
+            write: 0,
+            delay: MIN_DELAY,
+            smoothing: 0.0,
+            smoothing_gain: 1.0,
+            allpass: 0.0,
+            loop_gain: 0.0,
+            frequency: 0.0,
+            previous: 0.0,
+            allpass_input: 0.0,
+            allpass_output: 0.0,
+            noise: Noise::default(),
+        }
+    }
+
+    /// Set the loop up to ring at `frequency`. `damping` (0.0–1.0) is how
+    /// fast high harmonics die away relative to the fundamental; `decay` is
+    /// the fundamental's time to fall 60 dB, in seconds.
+    pub fn tune(&mut self, frequency: f64, damping: f64, decay: f64, sample_rate: f64) {
+        let frequency = frequency.clamp(1.0, sample_rate / 4.0);
+        let omega = TAU * frequency / sample_rate;
+        let period = sample_rate / frequency;
+
+        // One-zero lowpass (1 - s) + s·z⁻¹. Its phase delay at the
+        // fundamental is part of the period.
+        let s = damping.clamp(0.0, 1.0) * 0.5;
+        let re = (1.0 - s) + s * omega.cos();
+        let im = s * omega.sin();
+        let smoothing_delay = im.atan2(re) / omega;
+
+        let delay = (period - smoothing_delay - MIN_FRACTION).floor() as usize;
+        let delay = delay.clamp(MIN_DELAY, BUFFER_SIZE - 1);
+        let fraction = (period - smoothing_delay - delay as f64).max(MIN_FRACTION);
+
+        // Exact allpass coefficient for `fraction` samples of phase delay
+        // at the fundamental.
+        self.allpass =
+            (omega * (1.0 - fraction) / 2.0).sin() / (omega * (1.0 + fraction) / 2.0).sin();
+
+        self.delay = delay;
+        self.smoothing = s;
+        self.smoothing_gain = re.hypot(im);
+        self.frequency = frequency;
+        self.set_decay(decay);
+    }
+
+    /// Change the fundamental's 60 dB decay time without retuning. Used to
+    /// damp the string on release.
+    pub fn set_decay(&mut self, decay: f64) {
+        let periods = decay.max(1e-3) * self.frequency;
+        // The damping filter already loses some gain at the fundamental;
+        // make up for it so `decay` holds regardless of damping.
+        let gain = 0.001_f64.powf(1.0 / periods) / self.smoothing_gain;
+
+        self.loop_gain = gain.min(MAX_LOOP_GAIN);
+    }
+
+    /// Loop length in whole samples.
+    pub fn period(&self) -> usize {
+        self.delay
+    }
+
+    /// Fill one period of the delay line with a noise burst and restart the
+    /// loop filters. Call after `tune()`.
+    pub fn pluck(&mut self, excitation: &Excitation) {
+        let len = self.delay;
+        let raw = &mut self.scratch[..len];
+
+        // One-pole lowpass colors the noise.
+        let coefficient = excitation.color.clamp(0.01, 1.0);
+        let mut colored = 0.0;
+
+        for slot in raw.iter_mut() {
+            colored += coefficient * (self.noise.next() - colored);
+            *slot = colored;
+        }
+
+        // Comb: subtracting a copy shifted by the pick distance cancels
+        // harmonics with a node at the pick point. Circular, since the burst
+        // is one period of what the string repeats.
+        let offset = ((excitation.pick_position.clamp(0.0, 1.0) * len as f64).round() as usize)
+            .clamp(1, len - 1);
+
+        let burst = &mut self.buffer[BUFFER_SIZE - len..];
+
+        for (index, slot) in burst.iter_mut().enumerate() {
+            *slot = raw[index] - raw[(index + len - offset) % len];
+        }
+
+        // DC would ring in the loop as an offset.
+        let mean = burst.iter().sum::<f64>() / len as f64;
+        let peak = burst
+            .iter()
+            .map(|s| (s - mean).abs())
+            .fold(0.0, f64::max)
+            .max(1e-12);
+
+        for slot in burst.iter_mut() {
+            *slot = (*slot - mean) / peak * excitation.gain;
+        }
+
+        self.write = 0;
+        self.previous = 0.0;
+        self.allpass_input = 0.0;
+        self.allpass_output = 0.0;
+    }
+
+    /// Advance one sample.
+    pub fn tick(&mut self) -> f64 {
+        let delayed = self.buffer[self.write.wrapping_sub(self.delay) & MASK];
+
+        let smoothed = (1.0 - self.smoothing) * delayed + self.smoothing * self.previous;
+        self.previous = delayed;
+
+        let output =
+            self.allpass * smoothed + self.allpass_input - self.allpass * self.allpass_output;
+        self.allpass_input = smoothed;
+        self.allpass_output = output;
+
+        let output = output * self.loop_gain;
+        self.buffer[self.write] = output;
+        self.write = (self.write + 1) & MASK;
+
+        output
+    }
+}
+
+/// Xorshift noise, deterministic so renders repeat.
+#[derive(Debug)]
+struct Noise(u32);
+
+impl Default for Noise {
+    fn default() -> Self {
+        Self(0x1234_5678)
+    }
+}
+
+impl Noise {
+    fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+
+        self.0 as f64 / u32::MAX as f64 * 2.0 - 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f64 = 48000.0;
+
+    const PLUCK: Excitation = Excitation {
+        gain: 1.0,
+        pick_position: 0.15,
+        color: 1.0,
+    };
+
+    fn render(frequency: f64, damping: f64, excitation: Excitation, frames: usize) -> Vec<f64> {
+        let mut string = StringModel::new();
+        string.tune(frequency, damping, 4.0, SAMPLE_RATE);
+        string.pluck(&excitation);
+
+        (0..frames).map(|_| string.tick()).collect()
+    }
+
+    /// Period in samples, from the autocorrelation peak near `expected`
+    /// refined with a parabola through its neighbours.
+    fn measure_period(signal: &[f64], expected: f64) -> f64 {
+        let correlation = |lag: usize| -> f64 {
+            signal[..signal.len() - lag]
+                .iter()
+                .zip(&signal[lag..])
+                .map(|(a, b)| a * b)
+                .sum()
+        };
+
+        let guess = expected.round() as usize;
+        let best = (guess - 2..=guess + 2)
+            .max_by(|&a, &b| correlation(a).total_cmp(&correlation(b)))
+            .unwrap();
+
+        let (left, centre, right) = (
+            correlation(best - 1),
+            correlation(best),
+            correlation(best + 1),
+        );
+        best as f64 + 0.5 * (left - right) / (left - 2.0 * centre + right)
+    }
+
+    fn cents(measured: f64, expected: f64) -> f64 {
+        1200.0 * (measured / expected).log2().abs()
+    }
+
+    /// Energy of the first difference over energy of the signal. Higher
+    /// means brighter.
+    fn brightness(signal: &[f64]) -> f64 {
+        let energy: f64 = signal.iter().map(|s| s * s).sum();
+        let difference: f64 = signal.windows(2).map(|w| (w[1] - w[0]).powi(2)).sum();
+
+        difference / energy
+    }
+
+    #[test]
+    fn pitch_is_exact_between_samples() {
+        // Periods of 109.09 and 22.93 samples: rounding to whole samples
+        // would be off by up to 8 and 38 cents.
+        for frequency in [440.0, 2093.0] {
+            let signal = render(frequency, 1.0, PLUCK, 24000);
+            let period = measure_period(&signal[12000..], SAMPLE_RATE / frequency);
+
+            assert!(
+                cents(SAMPLE_RATE / period, frequency) < 1.0,
+                "{frequency} Hz"
+            );
+        }
+    }
+
+    #[test]
+    fn decay_sets_time_to_minus_60_db() {
+        let mut string = StringModel::new();
+        string.tune(220.0, 1.0, 0.5, SAMPLE_RATE);
+        string.pluck(&PLUCK);
+
+        let signal: Vec<f64> = (0..48000).map(|_| string.tick()).collect();
+        let peak = |range: std::ops::Range<usize>| {
+            signal[range].iter().fold(0.0, |a: f64, s| a.max(s.abs()))
+        };
+
+        // Compare two windows half a second apart, once the upper
+        // harmonics have died away.
+        let ratio = peak(36000..37000) / peak(12000..13000);
+        let db = 20.0 * ratio.log10();
+
+        assert!((db + 60.0).abs() < 3.0, "{db} dB");
+    }
+
+    #[test]
+    fn damping_darkens_the_tone() {
+        let bright = render(220.0, 0.0, PLUCK, 9600);
+        let dark = render(220.0, 1.0, PLUCK, 9600);
+
+        assert!(brightness(&dark[4800..]) < brightness(&bright[4800..]) / 2.0);
+    }
+
+    #[test]
+    fn color_darkens_the_burst() {
+        let white = render(110.0, 0.0, PLUCK, 436);
+        let dark = render(
+            110.0,
+            0.0,
+            Excitation {
+                color: 0.05,
+                ..PLUCK
+            },
+            436,
+        );
+
+        assert!(brightness(&dark) < brightness(&white) / 4.0);
+    }
+
+    #[test]
+    fn pick_in_the_middle_cancels_even_harmonics() {
+        let mut string = StringModel::new();
+        // A 240-sample loop, so half a period is a whole number of samples.
+        string.tune(SAMPLE_RATE / 240.5, 0.0, 1.0, SAMPLE_RATE);
+        string.pluck(&Excitation {
+            pick_position: 0.5,
+            ..PLUCK
+        });
+
+        // Only odd harmonics left: the second half of the period is the
+        // first half inverted.
+        let burst = &string.buffer[BUFFER_SIZE - string.period()..];
+        assert_eq!(burst.len(), 240);
+
+        for i in 0..120 {
+            assert!((burst[i] + burst[i + 120]).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn burst_peaks_at_gain_without_dc() {
+        let mut string = StringModel::new();
+        string.tune(100.0, 0.5, 1.0, SAMPLE_RATE);
+        string.pluck(&Excitation { gain: 0.5, ..PLUCK });
+
+        let burst = &string.buffer[BUFFER_SIZE - string.period()..];
+        let peak = burst.iter().fold(0.0, |a: f64, s| a.max(s.abs()));
+        let mean = burst.iter().sum::<f64>() / burst.len() as f64;
+
+        assert!((peak - 0.5).abs() < 1e-9);
+        assert!(mean.abs() < 1e-9);
+    }
+}
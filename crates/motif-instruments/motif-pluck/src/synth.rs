@@ -0,0 +1,330 @@
+use std::ops::Range;
+
+use motif_core::tuning::Tuning;
+use motif_engine::{
+    buffer::AudioBuffer,
+    events::{Event, MidiEvent},
+    node::AudioNode,
+    steal::{StealPolicy, StealVoice},
+};
+
+use crate::{
+    string::Excitation,
+    voice::{Strike, Voice},
+};
+
+/// Scales the summed strings. A fresh pluck peaks near its velocity gain,
+/// so this leaves headroom for a full strummed chord.
+const GAIN: f64 = 0.3;
+
+/// Voice count used by `Pluck::new()`.
+pub const DEFAULT_POLYPHONY: usize = 8;
+
+/// Polyphonic plucked-string instrument. Implements AudioNode — feed it
+/// NoteOn/NoteOff events via evaluate_node() and it produces audio.
+/// String settings are shared; each voice copies them on trigger.
+#[derive(Debug)]
+pub struct Pluck {
+    /// One string per sounding note, sized by `with_polyphony`.
+    pub voices: Vec<Voice>,
+    pub steal_policy: StealPolicy,
+    /// High-frequency loss per trip round the string (0.0 = none, 1.0 = most).
+    pub damping: f64,
+    /// Seconds for a held note to fall 60 dB.
+    pub decay: f64,
+    /// Seconds for a released note to fall 60 dB.
+    pub release: f64,
+    /// Where the string is plucked (0.0 = bridge, 0.5 = middle).
+    pub pick_position: f64,
+    /// Excitation noise brightness (0.0 = dark, 1.0 = white).
+    pub color: f64,
+    /// Sets each string's pitch. Notes the tuning leaves unmapped aren't plucked.
+    pub tuning: Tuning,
+    pub next_age: u64,
+}
+
+impl Default for Pluck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pluck {
+    pub fn new() -> Self {
+        Self::with_polyphony(DEFAULT_POLYPHONY)
+    }
+
+    /// Build a synth with a fixed voice count. Panics if `polyphony` is zero.
+    pub fn with_polyphony(polyphony: usize) -> Self {
+        assert!(polyphony > 0, "Pluck needs at least one voice");
+
+        Self {
+            voices: (0..polyphony).map(|_| Voice::new()).collect(),
+            // Plucking a ringing string again restarts that string.
+            steal_policy: StealPolicy::SameNote,
+            damping: 0.5,
+            decay: 3.0,
+            release: 0.08,
+            pick_position: 0.15,
+            color: 0.8,
+            tuning: Tuning::default(),
+            next_age: 0,
+        }
+    }
+}
+
+impl AudioNode for Pluck {
+    fn render(
+        &mut self,
+        _inputs: &[&AudioBuffer],
+        output: &mut AudioBuffer,
+        frame_range: Range<usize>,
+        sample_rate: f64,
+    ) {
+        let (left, right) = output.two_channels_mut(0, 1);
+
+        for frame in frame_range {
+            let mut sum = 0.0;
+
+            for voice in &mut self.voices {
+                if voice.is_active() {
+                    sum += voice.render(sample_rate);
+                }
+            }
+
+            let sample = (sum * GAIN) as f32;
+            left[frame] = sample;
+            right[frame] = sample;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::Midi(event) => match event {
+                MidiEvent::NoteOn { note, velocity } => {
+                    let Some(frequency) = self.tuning.frequency(*note) else {
+                        return;
+                    };
+
+                    let voice_index = self.steal_policy.pick(&self.voices, *note);
+
+                    let strike = Strike {
+                        frequency,
+                        excitation: Excitation {
+                            gain: u8::from(*velocity) as f64 / 127.0,
+                            pick_position: self.pick_position,
+                            color: self.color,
+                        },
+                        damping: self.damping,
+                        decay: self.decay,
+                        release: self.release,
+                    };
+
+                    self.voices[voice_index].trigger(*note, strike, self.next_age);
+                    self.next_age += 1;
+                }
+                MidiEvent::NoteOff { note } => {
+                    for voice in &mut self.voices {
+                        if voice.note == Some(*note) && voice.held {
+                            voice.held = false;
+                            voice.release();
+                        }
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    fn reset(&mut self) {
+        for voice in &mut self.voices {
+            voice.reset();
+        }
+
+        self.next_age = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use motif_core::tuning::{KeyboardMapping, Scale};
+    use motif_engine::{
+        graph::evaluate_node,
+        test_util::{
+            SAMPLE_RATE, has_signal, is_silent, note_off, note_on, note_on_velocity, peak,
+        },
+    };
+    use wmidi::{Note, Velocity};
+
+    #[test]
+    fn note_on_produces_output() {
+        let mut synth = Pluck::new();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(has_signal(&output, 0..256));
+    }
+
+    #[test]
+    fn no_events_is_silent() {
+        let mut synth = Pluck::new();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        evaluate_node(&mut synth, &[], &mut output, &[], SAMPLE_RATE);
+
+        assert!(is_silent(&output, 0..256));
+    }
+
+    #[test]
+    fn note_off_damps_to_silence() {
+        let mut synth = Pluck::new();
+        let mut output = AudioBuffer::new(2, 1024);
+
+        output.prepare(512);
+        let events = [note_on(0, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        output.prepare(1024);
+        let events = [note_off(0, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        for _ in 0..10 {
+            output.prepare(1024);
+            evaluate_node(&mut synth, &[], &mut output, &[], SAMPLE_RATE);
+        }
+
+        assert!(is_silent(&output, 0..1024));
+        assert!(!synth.voices[0].is_active());
+    }
+
+    #[test]
+    fn held_note_rings_on() {
+        let mut synth = Pluck::new();
+        let mut output = AudioBuffer::new(2, 1024);
+
+        output.prepare(1024);
+        let events = [note_on(0, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        for _ in 0..10 {
+            output.prepare(1024);
+            evaluate_node(&mut synth, &[], &mut output, &[], SAMPLE_RATE);
+        }
+
+        assert!(has_signal(&output, 0..1024));
+    }
+
+    #[test]
+    fn string_dies_away_without_note_off() {
+        let mut synth = Pluck::new();
+        synth.decay = 0.05;
+        let mut output = AudioBuffer::new(2, 1024);
+
+        output.prepare(1024);
+        let events = [note_on(0, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        for _ in 0..10 {
+            output.prepare(1024);
+            evaluate_node(&mut synth, &[], &mut output, &[], SAMPLE_RATE);
+        }
+
+        assert!(!synth.voices[0].is_active());
+    }
+
+    #[test]
+    fn polyphony_three_notes() {
+        let mut synth = Pluck::new();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [
+            note_on(0, Note::C4),
+            note_on(0, Note::E4),
+            note_on(0, Note::G4),
+        ];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        let active = synth.voices.iter().filter(|v| v.is_active()).count();
+        assert_eq!(active, 3);
+    }
+
+    #[test]
+    fn voice_steal_at_capacity() {
+        let mut synth = Pluck::with_polyphony(2);
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [
+            note_on(0, Note::C4),
+            note_on(0, Note::E4),
+            note_on(0, Note::G4),
+        ];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        let notes: Vec<_> = synth.voices.iter().map(|v| v.note).collect();
+        assert_eq!(notes, vec![Some(Note::G4), Some(Note::E4)]);
+    }
+
+    #[test]
+    fn retrigger_while_ringing_restarts_string() {
+        let mut synth = Pluck::new();
+        let mut output = AudioBuffer::new(2, 1024);
+
+        output.prepare(1024);
+        let events = [note_on(0, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        output.prepare(1024);
+        let events = [note_on(0, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        let active = synth.voices.iter().filter(|v| v.is_active()).count();
+        assert_eq!(active, 1);
+        assert!(has_signal(&output, 512..1024));
+    }
+
+    #[test]
+    fn velocity_scales_pluck_strength() {
+        let soft = Velocity::try_from(32).unwrap();
+
+        let mut loud = Pluck::new();
+        let mut quiet = Pluck::new();
+        let mut output = AudioBuffer::new(2, 1024);
+
+        output.prepare(1024);
+        let events = [note_on(0, Note::A4)];
+        evaluate_node(&mut loud, &[], &mut output, &events, SAMPLE_RATE);
+        let loud_peak = peak(&output);
+
+        output.prepare(1024);
+        let events = [note_on_velocity(0, Note::A4, soft)];
+        evaluate_node(&mut quiet, &[], &mut output, &events, SAMPLE_RATE);
+        let quiet_peak = peak(&output);
+
+        assert!(quiet_peak < loud_peak * 0.5);
+    }
+
+    #[test]
+    fn unmapped_note_is_silent() {
+        let mut synth = Pluck::new();
+        let mapping = KeyboardMapping {
+            first_note: 60,
+            ..KeyboardMapping::default()
+        };
+        synth.tuning = Tuning::new(&Scale::default(), &mapping).unwrap();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::C3)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(is_silent(&output, 0..256));
+    }
+}
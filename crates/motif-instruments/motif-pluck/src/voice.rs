@@ -0,0 +1,173 @@
+use motif_engine::steal::{StealFade, StealVoice};
+use wmidi::Note;
+
+use crate::string::{Excitation, StringModel};
+
+/// Loudest sample over a whole period below which a string counts as
+/// silent (-80 dB).
+const SILENCE: f64 = 1e-4;
+
+/// Single voice of polyphony. Owns one string. Pluck allocates a fixed pool
+/// of these; idle voices are skipped during render.
+///
+/// The pluck itself waits for the next render, which is the first place
+/// the sample rate is known.
+#[derive(Debug, Default)]
+pub struct Voice {
+    pub string: StringModel,
+    pub note: Option<Note>,
+    // NoteOn count when this string was plucked, for stealing.
+    pub age: u64,
+    /// Key is physically down.
+    pub held: bool,
+    sounding: bool,
+    releasing: bool,
+    // Decay time to switch to on release.
+    release_time: f64,
+    // Rate of the last render, for tuning the string when a note starts.
+    sample_rate: f64,
+    // Loudest sample of the last full period, and the one in progress.
+    peak: f64,
+    period_peak: f64,
+    period_elapsed: usize,
+    // Note waiting to be plucked, after the steal fade if there is one.
+    steal: StealFade<Strike>,
+}
+
+/// Per-note values fixed at NoteOn, after tuning and velocity.
+#[derive(Debug, Clone, Copy)]
+pub struct Strike {
+    pub frequency: f64,
+    pub excitation: Excitation,
+    pub damping: f64,
+    /// Seconds to fall 60 dB while held.
+    pub decay: f64,
+    /// Seconds to fall 60 dB after release.
+    pub release: f64,
+}
+
+impl Voice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render one mono sample.
+    pub fn render(&mut self, sample_rate: f64) -> f64 {
+        self.sample_rate = sample_rate;
+
+        // Pluck a queued note once the string is quiet, including a string
+        // that died on its own mid-fade.
+        if !self.sounding
+            && let Some(pending) = self.steal.take()
+        {
+            self.start_pending(pending);
+        }
+
+        if !self.sounding {
+            return 0.0;
+        }
+
+        let sample = self.string.tick();
+        self.track_level(sample);
+        let sample = sample * self.steal.gain();
+
+        self.tick_steal(sample_rate);
+
+        sample
+    }
+
+    /// Queue a note for the next render. A sounding string is faded out
+    /// first, so the new pluck starts from silence.
+    pub fn trigger(&mut self, note: Note, strike: Strike, age: u64) {
+        self.note = Some(note);
+        self.age = age;
+        self.held = true;
+
+        if self.sounding {
+            self.steal.steal(strike);
+        } else {
+            self.steal.defer(strike);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.note = None;
+        self.held = false;
+        self.sounding = false;
+        self.releasing = false;
+        self.steal.reset();
+        self.peak = 0.0;
+    }
+
+    /// Go idle once a whole period stays below `SILENCE`.
+    fn track_level(&mut self, sample: f64) {
+        self.period_peak = self.period_peak.max(sample.abs());
+        self.period_elapsed += 1;
+
+        if self.period_elapsed >= self.string.period() {
+            self.peak = self.period_peak;
+            self.period_peak = 0.0;
+            self.period_elapsed = 0;
+
+            if self.peak < SILENCE {
+                self.sounding = false;
+            }
+        }
+    }
+}
+
+impl StealVoice for Voice {
+    type Strike = Strike;
+
+    fn steal_fade(&self) -> &StealFade<Strike> {
+        &self.steal
+    }
+
+    fn steal_fade_mut(&mut self) -> &mut StealFade<Strike> {
+        &mut self.steal
+    }
+
+    fn age(&self) -> u64 {
+        self.age
+    }
+
+    fn note(&self) -> Option<Note> {
+        self.note
+    }
+
+    fn is_sounding(&self) -> bool {
+        self.sounding
+    }
+
+    fn is_sounding_released(&self) -> bool {
+        self.releasing
+    }
+
+    /// Recent peak amplitude.
+    fn sounding_level(&self) -> f64 {
+        self.peak
+    }
+
+    fn start(&mut self, strike: Strike) {
+        self.string.tune(
+            strike.frequency,
+            strike.damping,
+            strike.decay,
+            self.sample_rate,
+        );
+        self.string.pluck(&strike.excitation);
+
+        self.release_time = strike.release;
+        self.sounding = true;
+        self.releasing = false;
+        self.peak = strike.excitation.gain;
+        self.period_peak = 0.0;
+        self.period_elapsed = 0;
+    }
+
+    /// Damp the string, like lifting a fretting finger.
+    fn release_sounding(&mut self) {
+        self.releasing = true;
+        self.string.set_decay(self.release_time);
+    }
+}
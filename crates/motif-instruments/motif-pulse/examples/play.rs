@@ -97,15 +97,7 @@ fn main() {
                 evaluate_node(&mut synth, &[], &mut buffer, &events, sample_rate);
 
                 // Write to cpal output. If more than 2 channels, extra channels get silence.
-                for frame in 0..frames {
-                    for ch in 0..channels {
-                        data[frame * channels + ch] = if ch < 2 {
-                            buffer.channel(ch)[frame]
-                        } else {
-                            0.0
-                        };
-                    }
-                }
+                buffer.write_interleaved(data, channels);
 
                 samples_elapsed += frames as u64;
             },
@@ -0,0 +1,11 @@
+#[derive(Debug, thiserror::Error)]
+pub enum PresetError {
+    #[error("Preset file error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid preset: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("Failed to serialize preset: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("Invalid preset value: {0}")]
+    InvalidValue(String),
+}
@@ -0,0 +1,102 @@
+use std::f64::consts::PI;
+
+/// Per-voice resonant lowpass. Topology-preserving state-variable filter
+/// (Zavalishin), stable under per-note cutoff changes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Lowpass {
+    ic1eq: f64,
+    ic2eq: f64,
+}
+
+/// Coefficients shared by every channel of one voice. Computed only when
+/// cutoff, resonance or sample rate change — tan() is too costly per sample.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LowpassCoefficients {
+    a1: f64,
+    a2: f64,
+    a3: f64,
+}
+
+impl LowpassCoefficients {
+    /// `resonance` 0.0–1.0; near 1.0 the filter rings but never self-oscillates.
+    pub fn new(cutoff: f64, resonance: f64, sample_rate: f64) -> Self {
+        let cutoff = cutoff.clamp(10.0, sample_rate * 0.49);
+        let g = (PI * cutoff / sample_rate).tan();
+        let k = 2.0 - 1.98 * resonance.clamp(0.0, 1.0);
+        let a1 = 1.0 / (1.0 + g * (g + k));
+
+        Self {
+            a1,
+            a2: g * a1,
+            a3: g * g * a1,
+        }
+    }
+}
+
+impl Lowpass {
+    pub fn process(&mut self, input: f64, coefficients: &LowpassCoefficients) -> f64 {
+        let LowpassCoefficients { a1, a2, a3 } = *coefficients;
+
+        let v3 = input - self.ic2eq;
+        let v1 = a1 * self.ic1eq + a2 * v3;
+        let v2 = self.ic2eq + a2 * self.ic1eq + a3 * v3;
+
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+
+        v2
+    }
+
+    pub fn reset(&mut self) {
+        self.ic1eq = 0.0;
+        self.ic2eq = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Peak output amplitude for a sine at `frequency` once the filter settles.
+    fn response(cutoff: f64, frequency: f64) -> f64 {
+        let sample_rate = 48000.0;
+        let coefficients = LowpassCoefficients::new(cutoff, 0.0, sample_rate);
+        let mut filter = Lowpass::default();
+        let mut peak: f64 = 0.0;
+
+        for n in 0..48000 {
+            let input = (2.0 * PI * frequency * n as f64 / sample_rate).sin();
+            let output = filter.process(input, &coefficients);
+
+            if n > 24000 {
+                peak = peak.max(output.abs());
+            }
+        }
+
+        peak
+    }
+
+    #[test]
+    fn passes_below_cutoff() {
+        assert!((response(2000.0, 100.0) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn attenuates_above_cutoff() {
+        // Two octaves above cutoff, 12 dB/oct → about -24 dB.
+        assert!(response(1000.0, 4000.0) < 0.08);
+    }
+
+    #[test]
+    fn unity_gain_at_dc() {
+        let coefficients = LowpassCoefficients::new(500.0, 0.5, 48000.0);
+        let mut filter = Lowpass::default();
+        let mut output = 0.0;
+
+        for _ in 0..48000 {
+            output = filter.process(1.0, &coefficients);
+        }
+
+        assert!((output - 1.0).abs() < 1e-6);
+    }
+}
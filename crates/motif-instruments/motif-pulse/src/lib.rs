@@ -1,3 +1,7 @@
-pub mod envelope;
+pub mod error;
+pub mod filter;
+pub mod preset;
 pub mod synth;
+pub mod unison;
+pub mod velocity;
 pub mod voice;
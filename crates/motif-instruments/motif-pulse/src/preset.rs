@@ -0,0 +1,306 @@
+use std::path::Path;
+
+use motif_engine::{envelope::Curve, steal::StealPolicy};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::PresetError,
+    unison::{PhaseMode, Unison},
+    velocity::{MAX_CUTOFF, VelocityCurve, VelocityRouting},
+};
+
+/// A named snapshot of every Pulse sound parameter. Polyphony is not
+/// included — it sizes the voice pool at construction and can't change
+/// on the audio thread.
+///
+/// Stored as RON. Missing fields fall back to the `Init` values so older
+/// preset files keep loading as parameters are added.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PulsePreset {
+    pub name: String,
+    pub duty_cycle: f64,
+    pub delay: f32,
+    pub attack: f32,
+    pub hold: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+    pub attack_curve: Curve,
+    pub decay_curve: Curve,
+    pub release_curve: Curve,
+    pub unison: Unison,
+    pub cutoff: f64,
+    pub resonance: f64,
+    pub velocity: VelocityRouting,
+    pub steal_policy: StealPolicy,
+}
+
+impl Default for PulsePreset {
+    fn default() -> Self {
+        Self {
+            name: "Init".to_string(),
+            duty_cycle: 0.5,
+            delay: 0.0,
+            attack: 0.01,
+            hold: 0.0,
+            decay: 0.1,
+            sustain: 0.7,
+            release: 0.15,
+            attack_curve: Curve::Linear,
+            decay_curve: Curve::Exponential,
+            release_curve: Curve::Exponential,
+            unison: Unison::default(),
+            cutoff: MAX_CUTOFF,
+            resonance: 0.0,
+            velocity: VelocityRouting::default(),
+            steal_policy: StealPolicy::Oldest,
+        }
+    }
+}
+
+impl PulsePreset {
+    /// Parse and validate a preset. Values that would put NaN or negative
+    /// times into the voices are rejected rather than clamped.
+    pub fn from_ron(source: &str) -> Result<Self, PresetError> {
+        let preset: Self = ron::from_str(source)?;
+        preset.validate()?;
+
+        Ok(preset)
+    }
+
+    pub fn to_ron(&self) -> Result<String, PresetError> {
+        Ok(ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    /// Read a preset file. Blocking I/O — never call from the audio thread.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PresetError> {
+        Self::from_ron(&std::fs::read_to_string(path)?)
+    }
+
+    /// Write a preset file. Blocking I/O — never call from the audio thread.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PresetError> {
+        std::fs::write(path, self.to_ron()?)?;
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), PresetError> {
+        let unit =
+            |name: &str, value: f64| check(name, (0.0..=1.0).contains(&value), "must be 0–1");
+        let time = |name: &str, value: f32| {
+            check(
+                name,
+                value.is_finite() && value >= 0.0,
+                "must be a time in seconds",
+            )
+        };
+
+        unit("duty_cycle", self.duty_cycle)?;
+        time("delay", self.delay)?;
+        time("attack", self.attack)?;
+        time("hold", self.hold)?;
+        time("decay", self.decay)?;
+        unit("sustain", self.sustain as f64)?;
+        time("release", self.release)?;
+        check(
+            "cutoff",
+            self.cutoff.is_finite() && self.cutoff > 0.0,
+            "must be a frequency in Hz",
+        )?;
+        unit("resonance", self.resonance)?;
+        check(
+            "unison.detune",
+            self.unison.detune.is_finite() && self.unison.detune >= 0.0,
+            "must be cents",
+        )?;
+        unit("unison.spread", self.unison.spread)?;
+        unit("velocity.envelope_time", self.velocity.envelope_time as f64)?;
+        check(
+            "velocity.cutoff",
+            self.velocity.cutoff.is_finite(),
+            "must be octaves",
+        )
+    }
+}
+
+fn check(name: &str, valid: bool, requirement: &str) -> Result<(), PresetError> {
+    if valid {
+        Ok(())
+    } else {
+        Err(PresetError::InvalidValue(format!("{name} {requirement}")))
+    }
+}
+
+/// Presets shipped with the crate. Index is the MIDI program number.
+pub fn factory() -> Vec<PulsePreset> {
+    let nes_lead = |name: &str, duty_cycle: f64| PulsePreset {
+        name: name.to_string(),
+        duty_cycle,
+        attack: 0.0,
+        decay: 0.2,
+        sustain: 0.6,
+        release: 0.05,
+        attack_curve: Curve::Linear,
+        decay_curve: Curve::Linear,
+        release_curve: Curve::Linear,
+        ..PulsePreset::default()
+    };
+
+    vec![
+        PulsePreset::default(),
+        nes_lead("NES Lead 12.5%", 0.125),
+        nes_lead("NES Lead 25%", 0.25),
+        nes_lead("NES Lead 50%", 0.5),
+        PulsePreset {
+            name: "Pluck".to_string(),
+            duty_cycle: 0.25,
+            attack: 0.002,
+            decay: 0.25,
+            sustain: 0.0,
+            release: 0.1,
+            cutoff: 1200.0,
+            resonance: 0.3,
+            velocity: VelocityRouting {
+                amplitude: VelocityCurve::Soft,
+                envelope_time: 0.0,
+                cutoff: 2.5,
+            },
+            ..PulsePreset::default()
+        },
+        PulsePreset {
+            name: "Soft Pluck".to_string(),
+            duty_cycle: 0.5,
+            attack: 0.005,
+            decay: 0.6,
+            sustain: 0.0,
+            release: 0.3,
+            unison: Unison {
+                count: 2,
+                detune: 8.0,
+                spread: 0.5,
+                phase_mode: PhaseMode::Random,
+            },
+            ..PulsePreset::default()
+        },
+        PulsePreset {
+            name: "Pad".to_string(),
+            duty_cycle: 0.4,
+            attack: 0.6,
+            decay: 1.0,
+            sustain: 0.8,
+            release: 1.2,
+            attack_curve: Curve::Exponential,
+            unison: Unison {
+                count: 4,
+                detune: 18.0,
+                spread: 0.8,
+                phase_mode: PhaseMode::Random,
+            },
+            cutoff: 2500.0,
+            velocity: VelocityRouting {
+                amplitude: VelocityCurve::Soft,
+                envelope_time: 0.5,
+                cutoff: 1.0,
+            },
+            steal_policy: StealPolicy::ReleasingFirst,
+            ..PulsePreset::default()
+        },
+        PulsePreset {
+            name: "Wide Pad".to_string(),
+            duty_cycle: 0.5,
+            attack: 1.2,
+            decay: 1.5,
+            sustain: 0.9,
+            release: 2.0,
+            attack_curve: Curve::Exponential,
+            unison: Unison {
+                count: 7,
+                detune: 30.0,
+                spread: 1.0,
+                phase_mode: PhaseMode::Random,
+            },
+            steal_policy: StealPolicy::ReleasingFirst,
+            ..PulsePreset::default()
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ron_round_trip() {
+        for preset in factory() {
+            let ron = preset.to_ron().unwrap();
+
+            assert_eq!(PulsePreset::from_ron(&ron).unwrap(), preset);
+        }
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let preset = PulsePreset::from_ron("(name: \"Thin\", duty_cycle: 0.1)").unwrap();
+
+        assert_eq!(preset.name, "Thin");
+        assert_eq!(preset.duty_cycle, 0.1);
+        assert_eq!(preset.release, PulsePreset::default().release);
+    }
+
+    #[test]
+    fn invalid_ron_is_an_error() {
+        assert!(matches!(
+            PulsePreset::from_ron("(duty_cycle: \"wide\")"),
+            Err(PresetError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected() {
+        for source in [
+            "(attack: -0.1)",
+            "(release: inf)",
+            "(sustain: 1.5)",
+            "(duty_cycle: NaN)",
+            "(cutoff: 0.0)",
+            "(unison: (detune: NaN))",
+        ] {
+            assert!(
+                matches!(
+                    PulsePreset::from_ron(source),
+                    Err(PresetError::InvalidValue(_))
+                ),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn factory_presets_are_valid() {
+        for preset in factory() {
+            assert!(preset.validate().is_ok(), "{}", preset.name);
+        }
+    }
+
+    #[test]
+    fn factory_has_nes_duty_cycles() {
+        let bank = factory();
+
+        for duty in [0.125, 0.25, 0.5] {
+            assert!(bank.iter().any(|p| p.duty_cycle == duty));
+        }
+    }
+
+    #[test]
+    fn factory_names_are_unique() {
+        let bank = factory();
+
+        for (i, preset) in bank.iter().enumerate() {
+            assert!(!bank[i + 1..].iter().any(|p| p.name == preset.name));
+        }
+    }
+}
@@ -1,28 +1,68 @@
 use std::ops::Range;
 
+use motif_core::tuning::Tuning;
 use motif_engine::{
     buffer::AudioBuffer,
+    envelope::Curve,
     events::{Event, MidiEvent},
     node::AudioNode,
+    steal::{StealPolicy, StealVoice},
 };
+use wmidi::ControlFunction;
 
-use crate::voice::Voice;
+use crate::{
+    preset::{self, PulsePreset},
+    unison::{PhaseRng, Unison},
+    velocity::VelocityRouting,
+    voice::{Strike, Voice},
+};
 
 /// Master output scaling. Prevents clipping when multiple voices are active.
 const GAIN: f64 = 0.15;
 
-/// 8-voice polyphonic pulse wave synthesizer. Implements AudioNode —
+/// Controller values at or above this count as pedal down (MIDI convention).
+const PEDAL_THRESHOLD: u8 = 64;
+
+/// Voice count used by `Pulse::new()`.
+pub const DEFAULT_POLYPHONY: usize = 8;
+
+/// Polyphonic pulse wave synthesizer. Implements AudioNode —
 /// feed it NoteOn/NoteOff events via evaluate_node() and it produces audio.
-/// ADSR params are shared; each voice gets a copy on trigger.
+/// Envelope params are shared; each voice gets a copy on trigger.
+/// ProgramChange switches to a preset from `bank`, so presets change on the
+/// audio thread in event order rather than by mutating the node from outside.
 #[derive(Debug)]
 pub struct Pulse {
-    pub voices: [Voice; 8],
+    /// Allocated once at construction. Never resized on the audio thread.
+    pub voices: Vec<Voice>,
+    pub steal_policy: StealPolicy,
     pub duty_cycle: f64,
+    pub delay: f32,
     pub attack: f32,
+    pub hold: f32,
     pub decay: f32,
     pub sustain: f32,
     pub release: f32,
+    pub attack_curve: Curve,
+    pub decay_curve: Curve,
+    pub release_curve: Curve,
+    pub unison: Unison,
+    /// Lowpass cutoff in Hz before velocity routing. `MAX_CUTOFF` bypasses the filter.
+    pub cutoff: f64,
+    /// Lowpass resonance (0.0–1.0).
+    pub resonance: f64,
+    pub velocity: VelocityRouting,
+    /// Presets addressable by ProgramChange. Built before the synth moves
+    /// to the audio thread; never resized there.
+    pub bank: Vec<PulsePreset>,
+    /// Note → frequency table. Unmapped notes are ignored.
+    pub tuning: Tuning,
+    /// CC64. Released keys keep sounding until the pedal lifts.
+    pub sustain_pedal: bool,
+    /// CC66. Only notes held when the pedal went down keep sounding.
+    pub sostenuto_pedal: bool,
     pub next_age: u64,
+    rng: PhaseRng,
 }
 
 impl Default for Pulse {
@@ -33,15 +73,230 @@ impl Default for Pulse {
 
 impl Pulse {
     pub fn new() -> Self {
+        Self::with_polyphony(DEFAULT_POLYPHONY)
+    }
+
+    /// Build a synth with a fixed voice count. Panics if `polyphony` is zero.
+    pub fn with_polyphony(polyphony: usize) -> Self {
+        assert!(polyphony > 0, "Pulse needs at least one voice");
+
+        let init = PulsePreset::default();
+
         Self {
-            voices: std::array::from_fn(|_| Voice::new()),
-            duty_cycle: 0.5,
-            attack: 0.01,
-            decay: 0.1,
-            sustain: 0.7,
-            release: 0.15,
+            voices: (0..polyphony).map(|_| Voice::new()).collect(),
+            steal_policy: init.steal_policy,
+            duty_cycle: init.duty_cycle,
+            delay: init.delay,
+            attack: init.attack,
+            hold: init.hold,
+            decay: init.decay,
+            sustain: init.sustain,
+            release: init.release,
+            attack_curve: init.attack_curve,
+            decay_curve: init.decay_curve,
+            release_curve: init.release_curve,
+            unison: init.unison,
+            cutoff: init.cutoff,
+            resonance: init.resonance,
+            velocity: init.velocity,
+            bank: preset::factory(),
+            tuning: Tuning::default(),
+            sustain_pedal: false,
+            sostenuto_pedal: false,
             next_age: 0,
+            rng: PhaseRng::default(),
+        }
+    }
+
+    /// Copy every sound parameter from a preset. Voices already sounding
+    /// keep their envelope until retriggered. Real-time safe.
+    pub fn apply_preset(&mut self, preset: &PulsePreset) {
+        self.duty_cycle = preset.duty_cycle;
+        self.delay = preset.delay;
+        self.attack = preset.attack;
+        self.hold = preset.hold;
+        self.decay = preset.decay;
+        self.sustain = preset.sustain;
+        self.release = preset.release;
+        self.attack_curve = preset.attack_curve;
+        self.decay_curve = preset.decay_curve;
+        self.release_curve = preset.release_curve;
+        self.unison = preset.unison;
+        self.cutoff = preset.cutoff;
+        self.resonance = preset.resonance;
+        self.velocity = preset.velocity;
+        self.steal_policy = preset.steal_policy;
+    }
+
+    /// Capture the current parameters as a named preset.
+    pub fn to_preset(&self, name: impl Into<String>) -> PulsePreset {
+        PulsePreset {
+            name: name.into(),
+            duty_cycle: self.duty_cycle,
+            delay: self.delay,
+            attack: self.attack,
+            hold: self.hold,
+            decay: self.decay,
+            sustain: self.sustain,
+            release: self.release,
+            attack_curve: self.attack_curve,
+            decay_curve: self.decay_curve,
+            release_curve: self.release_curve,
+            unison: self.unison,
+            cutoff: self.cutoff,
+            resonance: self.resonance,
+            velocity: self.velocity,
+            steal_policy: self.steal_policy,
+        }
+    }
+
+    /// Release voices whose key is up and that no pedal is holding anymore.
+    fn release_unpedalled(&mut self) {
+        for voice in &mut self.voices {
+            if voice.is_pedal_held() && !self.sustain_pedal && !voice.sostenuto {
+                voice.release();
+            }
+        }
+    }
+}
+
+impl AudioNode for Pulse {
+    fn render(
+        &mut self,
+        _inputs: &[&AudioBuffer],
+        output: &mut AudioBuffer,
+        frame_range: Range<usize>,
+        sample_rate: f64,
+    ) {
+        let layout = self.unison.layout();
+        let oscillators = &layout[..self.unison.count()];
+        let (left, right) = output.two_channels_mut(0, 1);
+
+        for frame in frame_range {
+            let mut sum_l = 0.0;
+            let mut sum_r = 0.0;
+
+            for voice in &mut self.voices {
+                if voice.is_active() {
+                    let (l, r) =
+                        voice.render(self.duty_cycle, self.resonance, sample_rate, oscillators);
+                    sum_l += l;
+                    sum_r += r;
+                }
+            }
+
+            left[frame] = (sum_l * GAIN) as f32;
+            right[frame] = (sum_r * GAIN) as f32;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::Midi(event) => match event {
+                MidiEvent::NoteOn { note, velocity } => {
+                    let Some(frequency) = self.tuning.frequency(*note) else {
+                        return;
+                    };
+
+                    // Re-striking a pedal-held note lets the old strike ring out
+                    // instead of stacking a second sustained copy.
+                    for voice in &mut self.voices {
+                        if voice.note == Some(*note) && voice.is_pedal_held() {
+                            voice.release();
+                        }
+                    }
+
+                    let voice_index = self.steal_policy.pick(&self.voices, *note);
+
+                    let strike = Strike {
+                        velocity: *velocity,
+                        gain: self.velocity.amplitude.apply(*velocity),
+                        frequency,
+                        cutoff: self.velocity.cutoff(self.cutoff, *velocity),
+                        phases: self.unison.start_phases(&mut self.rng),
+                    };
+                    let time_scale = self.velocity.time_scale(*velocity);
+
+                    let voice = &mut self.voices[voice_index];
+                    voice.trigger(*note, strike, self.next_age);
+                    voice.envelope.adsr(
+                        self.attack * time_scale,
+                        self.decay * time_scale,
+                        self.sustain,
+                        self.release,
+                    );
+                    voice.envelope.delay_hold(self.delay, self.hold);
+                    voice
+                        .envelope
+                        .curves(self.attack_curve, self.decay_curve, self.release_curve);
+
+                    self.next_age += 1;
+                }
+                MidiEvent::NoteOff { note } => {
+                    // Find the voice(s) matching the note and trigger release,
+                    // unless a pedal is keeping them alive.
+                    for voice in &mut self.voices {
+                        if voice.note == Some(*note) && voice.held {
+                            voice.held = false;
+
+                            if !self.sustain_pedal && !voice.sostenuto {
+                                voice.release();
+                            }
+                        }
+                    }
+                }
+                MidiEvent::ControlChange { control, value } => {
+                    let down = u8::from(*value) >= PEDAL_THRESHOLD;
+
+                    match *control {
+                        ControlFunction::DAMPER_PEDAL => {
+                            self.sustain_pedal = down;
+                        }
+                        ControlFunction::SOSTENUTO => {
+                            // Latch only on the down edge so keys pressed while
+                            // the pedal is already down aren't captured.
+                            if down && !self.sostenuto_pedal {
+                                for voice in &mut self.voices {
+                                    voice.sostenuto = voice.held && !voice.is_releasing();
+                                }
+                            }
+
+                            if !down {
+                                for voice in &mut self.voices {
+                                    voice.sostenuto = false;
+                                }
+                            }
+
+                            self.sostenuto_pedal = down;
+                        }
+                        _ => {}
+                    }
+
+                    self.release_unpedalled();
+                }
+                MidiEvent::ProgramChange { program } => {
+                    // Take the bank so the preset can be borrowed while self is mutated.
+                    // Swapping with an empty Vec doesn't allocate.
+                    let bank = std::mem::take(&mut self.bank);
+
+                    if let Some(preset) = bank.get(u8::from(*program) as usize) {
+                        self.apply_preset(preset);
+                    }
+
+                    self.bank = bank;
+                }
+            },
+        }
+    }
+
+    fn reset(&mut self) {
+        for voice in &mut self.voices {
+            voice.reset();
         }
+
+        self.sustain_pedal = false;
+        self.sostenuto_pedal = false;
+        self.next_age = 0;
     }
 }
 
@@ -49,6 +304,11 @@ impl Pulse {
 mod tests {
     use super::*;
 
+    use crate::{
+        unison::PhaseMode,
+        velocity::{MAX_CUTOFF, VelocityCurve},
+    };
+    use motif_core::tuning::{KeyboardMapping, Scale};
     use motif_engine::{events::ScheduledEvent, graph::evaluate_node};
     use wmidi::{Note, Velocity};
 
@@ -220,76 +480,412 @@ mod tests {
         assert!(is_silent(&output, 0..128));
         assert!(has_signal(&output, 128..256));
     }
-}
 
-impl AudioNode for Pulse {
-    fn render(
-        &mut self,
-        _inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
-        frame_range: Range<usize>,
-        sample_rate: f64,
-    ) {
-        for frame in frame_range {
-            let mut sum = 0.0;
+    #[test]
+    fn with_polyphony_sets_voice_count() {
+        let synth = Pulse::with_polyphony(3);
 
-            for voice in &mut self.voices {
-                if voice.is_active() {
-                    sum += voice.render(self.duty_cycle, sample_rate);
-                }
-            }
+        assert_eq!(synth.voices.len(), 3);
+    }
 
-            let out = (sum * GAIN) as f32;
-            output.channel_mut(0)[frame] = out;
-            output.channel_mut(1)[frame] = out;
-        }
+    #[test]
+    #[should_panic]
+    fn with_polyphony_rejects_zero() {
+        Pulse::with_polyphony(0);
     }
 
-    fn handle_event(&mut self, event: &Event) {
-        match event {
-            Event::Midi(event) => match event {
-                MidiEvent::NoteOn { note, velocity } => {
-                    // Find an inactive voice first.
-                    let voice_index = self
-                        .voices
-                        .iter()
-                        .position(|v| !v.is_active())
-                        .unwrap_or_else(|| {
-                            // If no inactive voice, find the oldest one.
-                            self.voices
-                                .iter()
-                                .enumerate()
-                                .min_by_key(|(_, v)| v.age)
-                                .map(|(i, _)| i)
-                                // UNWRAP SAFETY: The voices array is never empty.
-                                .unwrap()
-                        });
+    #[test]
+    fn releasing_first_steals_released_voice() {
+        let mut synth = Pulse::with_polyphony(2);
+        synth.steal_policy = StealPolicy::ReleasingFirst;
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
 
-                    let voice = &mut self.voices[voice_index];
-                    voice.trigger(*note, *velocity, note.to_freq_f64(), self.next_age);
-                    voice
-                        .envelope
-                        .adsr(self.attack, self.decay, self.sustain, self.release);
+        // C4 is oldest but still held; E4 is newer but releasing.
+        let events = [
+            note_on(0, Note::C4),
+            note_on(0, Note::E4),
+            note_off(0, Note::E4),
+            note_on(0, Note::G4),
+        ];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
 
-                    self.next_age += 1;
-                }
-                MidiEvent::NoteOff { note } => {
-                    // Find the voice(s) matching the note and trigger release.
-                    for voice in &mut self.voices {
-                        if voice.note == Some(*note) && !voice.envelope.is_releasing() {
-                            voice.release();
-                        }
-                    }
-                }
+        assert!(synth.voices.iter().any(|v| v.note == Some(Note::C4)));
+        assert!(!synth.voices.iter().any(|v| v.note == Some(Note::E4)));
+    }
+
+    #[test]
+    fn quietest_steals_lowest_level() {
+        let mut synth = Pulse::with_polyphony(2);
+        synth.steal_policy = StealPolicy::Quietest;
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        // E4 is newer but much softer than C4.
+        let events = [
+            note_on(0, Note::C4),
+            ScheduledEvent {
+                sample_offset: 0,
+                event: Event::Midi(MidiEvent::NoteOn {
+                    note: Note::E4,
+                    velocity: Velocity::MIN,
+                }),
             },
+        ];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        output.prepare(256);
+        let events = [note_on(0, Note::G4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(synth.voices.iter().any(|v| v.note == Some(Note::C4)));
+        assert!(!synth.voices.iter().any(|v| v.note == Some(Note::E4)));
+    }
+
+    #[test]
+    fn same_note_retriggers_existing_voice() {
+        let mut synth = make_synth();
+        synth.steal_policy = StealPolicy::SameNote;
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::C4), note_on(64, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        let active = synth.voices.iter().filter(|v| v.is_active()).count();
+        assert_eq!(active, 1);
+    }
+
+    #[test]
+    fn stolen_voice_fades_instead_of_cutting() {
+        let mut synth = Pulse::with_polyphony(1);
+        let mut output = AudioBuffer::new(2, 1024);
+
+        // Let C4 reach sustain.
+        output.prepare(1024);
+        let events = [note_on(0, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+        for _ in 0..10 {
+            output.prepare(1024);
+            evaluate_node(&mut synth, &[], &mut output, &[], SAMPLE_RATE);
+        }
+
+        output.prepare(1024);
+        let events = [note_on(0, Note::E4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        // The old note is still audible right after the steal, then ramps down
+        // before the new attack begins.
+        assert!(output.channel(0)[0].abs() > 0.05);
+        assert!(output.channel(0)[100].abs() < output.channel(0)[0].abs());
+        assert!(has_signal(&output, 512..1024));
+        assert_eq!(synth.voices[0].note, Some(Note::E4));
+    }
+
+    #[test]
+    fn unison_spread_makes_channels_differ() {
+        let mut synth = make_synth();
+        synth.unison = Unison {
+            count: 4,
+            detune: 20.0,
+            spread: 1.0,
+            phase_mode: PhaseMode::Random,
+        };
+        let mut output = AudioBuffer::new(2, 1024);
+        output.prepare(1024);
+
+        let events = [note_on(0, Note::A4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(has_signal(&output, 0..1024));
+        assert_ne!(output.channel(0), output.channel(1));
+    }
+
+    #[test]
+    fn unison_without_spread_stays_mono() {
+        let mut synth = make_synth();
+        synth.unison = Unison {
+            count: 3,
+            detune: 15.0,
+            ..Unison::default()
+        };
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::A4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert_eq!(output.channel(0), output.channel(1));
+    }
+
+    fn program_change(offset: u32, program: u8) -> ScheduledEvent {
+        ScheduledEvent {
+            sample_offset: offset,
+            event: Event::Midi(MidiEvent::ProgramChange {
+                program: wmidi::ProgramNumber::new(program).unwrap(),
+            }),
         }
     }
 
-    fn reset(&mut self) {
-        for voice in &mut self.voices {
-            voice.reset();
+    #[test]
+    fn program_change_applies_bank_preset() {
+        let mut synth = make_synth();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [program_change(0, 1)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert_eq!(synth.to_preset("NES Lead 12.5%"), synth.bank[1]);
+    }
+
+    #[test]
+    fn program_change_out_of_range_is_ignored() {
+        let mut synth = make_synth();
+        let before = synth.to_preset("Init");
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [program_change(0, 127)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert_eq!(synth.to_preset("Init"), before);
+        assert_eq!(synth.bank.len(), preset::factory().len());
+    }
+
+    fn control_change(offset: u32, control: ControlFunction, value: u8) -> ScheduledEvent {
+        ScheduledEvent {
+            sample_offset: offset,
+            event: Event::Midi(MidiEvent::ControlChange {
+                control,
+                value: wmidi::ControlValue::new(value).unwrap(),
+            }),
         }
+    }
 
-        self.next_age = 0;
+    fn sounding(synth: &Pulse, note: Note) -> usize {
+        synth
+            .voices
+            .iter()
+            .filter(|v| v.note == Some(note) && v.is_active() && !v.is_releasing())
+            .count()
+    }
+
+    #[test]
+    fn sustain_pedal_holds_released_notes_until_lifted() {
+        let mut synth = make_synth();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [
+            note_on(0, Note::C4),
+            control_change(10, ControlFunction::DAMPER_PEDAL, 127),
+            note_off(20, Note::C4),
+        ];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+        assert_eq!(sounding(&synth, Note::C4), 1);
+
+        output.prepare(256);
+        let events = [control_change(0, ControlFunction::DAMPER_PEDAL, 0)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+        assert_eq!(sounding(&synth, Note::C4), 0);
+    }
+
+    #[test]
+    fn sustained_note_restrike_does_not_stack() {
+        let mut synth = make_synth();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [
+            control_change(0, ControlFunction::DAMPER_PEDAL, 127),
+            note_on(0, Note::C4),
+            note_off(10, Note::C4),
+            note_on(20, Note::C4),
+            note_off(30, Note::C4),
+            note_on(40, Note::C4),
+        ];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert_eq!(sounding(&synth, Note::C4), 1);
+    }
+
+    #[test]
+    fn sostenuto_holds_only_notes_down_at_press() {
+        let mut synth = make_synth();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [
+            note_on(0, Note::C4),
+            control_change(10, ControlFunction::SOSTENUTO, 127),
+            note_on(20, Note::E4),
+            note_off(30, Note::C4),
+            note_off(30, Note::E4),
+        ];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+        assert_eq!(sounding(&synth, Note::C4), 1);
+        assert_eq!(sounding(&synth, Note::E4), 0);
+
+        output.prepare(256);
+        let events = [control_change(0, ControlFunction::SOSTENUTO, 0)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+        assert_eq!(sounding(&synth, Note::C4), 0);
+    }
+
+    #[test]
+    fn lifting_sostenuto_keeps_notes_under_sustain() {
+        let mut synth = make_synth();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [
+            note_on(0, Note::C4),
+            control_change(10, ControlFunction::SOSTENUTO, 127),
+            control_change(10, ControlFunction::DAMPER_PEDAL, 127),
+            note_off(20, Note::C4),
+            control_change(30, ControlFunction::SOSTENUTO, 0),
+        ];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert_eq!(sounding(&synth, Note::C4), 1);
+    }
+
+    #[test]
+    fn tuning_sets_voice_frequency() {
+        let mut synth = make_synth();
+        synth.tuning = Tuning::equal_temperament(432.0).unwrap();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::A4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        let voice = synth.voices.iter().find(|v| v.is_active()).unwrap();
+        assert!((voice.frequency - 432.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unmapped_note_is_silent() {
+        let mut synth = make_synth();
+        let mapping = KeyboardMapping {
+            first_note: 60,
+            ..KeyboardMapping::default()
+        };
+        synth.tuning = Tuning::new(&Scale::default(), &mapping).unwrap();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::C3)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        assert!(is_silent(&output, 0..256));
+    }
+
+    fn note_on_velocity(offset: u32, note: Note, velocity: u8) -> ScheduledEvent {
+        ScheduledEvent {
+            sample_offset: offset,
+            event: Event::Midi(MidiEvent::NoteOn {
+                note,
+                velocity: Velocity::new(velocity).unwrap(),
+            }),
+        }
+    }
+
+    fn peak(buf: &AudioBuffer) -> f32 {
+        buf.channel(0).iter().fold(0.0, |max, s| max.max(s.abs()))
+    }
+
+    #[test]
+    fn velocity_curve_shapes_amplitude() {
+        let render_peak = |curve: VelocityCurve| {
+            let mut synth = make_synth();
+            synth.velocity.amplitude = curve;
+            let mut output = AudioBuffer::new(2, 1024);
+            output.prepare(1024);
+
+            let events = [note_on_velocity(0, Note::A4, 64)];
+            evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+            peak(&output)
+        };
+
+        let linear = render_peak(VelocityCurve::Linear);
+
+        assert!(render_peak(VelocityCurve::Soft) > linear);
+        assert!(render_peak(VelocityCurve::Hard) < linear);
+        assert!(render_peak(VelocityCurve::Fixed) > render_peak(VelocityCurve::Soft));
+    }
+
+    #[test]
+    fn velocity_routes_to_cutoff() {
+        let mut synth = make_synth();
+        synth.cutoff = 500.0;
+        synth.velocity.cutoff = 3.0;
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [
+            note_on_velocity(0, Note::C4, 0),
+            note_on_velocity(0, Note::E4, 127),
+        ];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        let cutoff_of = |note| {
+            synth
+                .voices
+                .iter()
+                .find(|v| v.note == Some(note))
+                .unwrap()
+                .cutoff
+        };
+        assert_eq!(cutoff_of(Note::C4), 500.0);
+        assert!((cutoff_of(Note::E4) - 4000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn velocity_shortens_attack() {
+        let level_after = |velocity: u8| {
+            let mut synth = make_synth();
+            synth.attack = 0.1;
+            synth.velocity.envelope_time = 0.9;
+            let mut output = AudioBuffer::new(2, 480);
+            output.prepare(480);
+
+            let events = [note_on_velocity(0, Note::A4, velocity)];
+            evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+            synth
+                .voices
+                .iter()
+                .find(|v| v.is_active())
+                .unwrap()
+                .envelope
+                .level()
+        };
+
+        assert!(level_after(127) > 2.0 * level_after(20));
+    }
+
+    #[test]
+    fn lowpass_darkens_output() {
+        let energy = |cutoff: f64| {
+            let mut synth = make_synth();
+            synth.cutoff = cutoff;
+            let mut output = AudioBuffer::new(2, 4096);
+            output.prepare(4096);
+
+            let events = [note_on(0, Note::A4)];
+            evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+            // Sum of squared differences approximates high-frequency content.
+            output
+                .channel(0)
+                .windows(2)
+                .map(|w| (w[1] - w[0]).powi(2))
+                .sum::<f32>()
+        };
+
+        assert!(energy(300.0) < energy(MAX_CUTOFF) * 0.1);
     }
 }
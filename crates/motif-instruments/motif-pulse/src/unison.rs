@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+
+/// Upper bound on sub-oscillators per voice. Voices store phases in a fixed
+/// array of this size so unison never allocates.
+pub const MAX_UNISON: usize = 8;
+
+/// Where each sub-oscillator's phase starts on trigger.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PhaseMode {
+    /// Every sub-oscillator starts at phase 0. Punchy, repeatable attacks.
+    #[default]
+    Fixed,
+    /// Each sub-oscillator starts at a random phase. Avoids the phasing
+    /// "swoosh" of aligned detuned oscillators on every note.
+    Random,
+}
+
+/// Unison stack shared by every voice. Sub-oscillators are spread evenly
+/// across the detune range and the stereo field, outermost at the extremes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Unison {
+    /// Sub-oscillators per voice, clamped to 1..=MAX_UNISON.
+    pub count: usize,
+    /// Distance between the outermost sub-oscillators, in cents.
+    pub detune: f64,
+    /// Stereo width (0.0 = mono, 1.0 = outermost hard left/right).
+    pub spread: f64,
+    pub phase_mode: PhaseMode,
+}
+
+/// Per-sub-oscillator constants, computed once per render call.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Oscillator {
+    pub ratio: f64,
+    pub gain_l: f64,
+    pub gain_r: f64,
+}
+
+impl Default for Unison {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            detune: 0.0,
+            spread: 0.0,
+            phase_mode: PhaseMode::Fixed,
+        }
+    }
+}
+
+impl Unison {
+    pub fn count(&self) -> usize {
+        self.count.clamp(1, MAX_UNISON)
+    }
+
+    /// Frequency ratio and pan gains for each active sub-oscillator. Only
+    /// the first `count()` entries are meaningful. Gains are scaled by
+    /// 1/sqrt(count) so the stack stays roughly as loud as a single oscillator.
+    pub fn layout(&self) -> [Oscillator; MAX_UNISON] {
+        let count = self.count();
+        let normalize = 1.0 / (count as f64).sqrt();
+        let mut layout = [Oscillator::default(); MAX_UNISON];
+
+        for (index, oscillator) in layout.iter_mut().take(count).enumerate() {
+            // -1.0 (lowest/leftmost) to 1.0 (highest/rightmost).
+            let position = if count == 1 {
+                0.0
+            } else {
+                2.0 * index as f64 / (count - 1) as f64 - 1.0
+            };
+
+            let cents = position * self.detune / 2.0;
+            let pan = (position * self.spread).clamp(-1.0, 1.0);
+
+            // Balance law: centre stays at unity in both channels.
+            *oscillator = Oscillator {
+                ratio: (cents / 1200.0).exp2(),
+                gain_l: (1.0 - pan).min(1.0) * normalize,
+                gain_r: (1.0 + pan).min(1.0) * normalize,
+            };
+        }
+
+        layout
+    }
+
+    /// Starting phases for a new note.
+    pub fn start_phases(&self, rng: &mut PhaseRng) -> [f64; MAX_UNISON] {
+        match self.phase_mode {
+            PhaseMode::Fixed => [0.0; MAX_UNISON],
+            PhaseMode::Random => std::array::from_fn(|_| rng.next_phase()),
+        }
+    }
+}
+
+/// Xorshift generator for random start phases. Deterministic and
+/// allocation-free, so it's safe to call from handle_event().
+#[derive(Debug, Clone)]
+pub struct PhaseRng(u64);
+
+impl Default for PhaseRng {
+    fn default() -> Self {
+        Self(0x9E37_79B9_7F4A_7C15)
+    }
+}
+
+impl PhaseRng {
+    /// Uniform value in 0.0..1.0.
+    pub fn next_phase(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_oscillator_is_centred_and_in_tune() {
+        let unison = Unison {
+            detune: 50.0,
+            spread: 1.0,
+            ..Unison::default()
+        };
+
+        let layout = unison.layout();
+
+        assert_eq!(layout[0].ratio, 1.0);
+        assert_eq!(layout[0].gain_l, 1.0);
+        assert_eq!(layout[0].gain_r, 1.0);
+    }
+
+    #[test]
+    fn detune_is_symmetric() {
+        let unison = Unison {
+            count: 3,
+            detune: 100.0,
+            ..Unison::default()
+        };
+
+        let layout = unison.layout();
+
+        assert!((layout[0].ratio - (-50.0_f64 / 1200.0).exp2()).abs() < 1e-12);
+        assert_eq!(layout[1].ratio, 1.0);
+        assert!((layout[0].ratio * layout[2].ratio - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn full_spread_pans_outermost_hard() {
+        let unison = Unison {
+            count: 4,
+            spread: 1.0,
+            ..Unison::default()
+        };
+
+        let layout = unison.layout();
+
+        assert_eq!(layout[0].gain_r, 0.0);
+        assert_eq!(layout[3].gain_l, 0.0);
+    }
+
+    #[test]
+    fn count_is_clamped() {
+        let unison = Unison {
+            count: 100,
+            ..Unison::default()
+        };
+
+        assert_eq!(unison.count(), MAX_UNISON);
+        assert_eq!(Unison { count: 0, ..unison }.count(), 1);
+    }
+
+    #[test]
+    fn random_phases_are_in_range_and_vary() {
+        let unison = Unison {
+            phase_mode: PhaseMode::Random,
+            ..Unison::default()
+        };
+        let mut rng = PhaseRng::default();
+
+        let first = unison.start_phases(&mut rng);
+        let second = unison.start_phases(&mut rng);
+
+        assert!(first.iter().all(|p| (0.0..1.0).contains(p)));
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use wmidi::Velocity;
+
+/// Cutoffs at or above this are treated as fully open and the filter is bypassed.
+pub const MAX_CUTOFF: f64 = 20000.0;
+
+/// Maps NoteOn velocity to amplitude.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VelocityCurve {
+    #[default]
+    Linear,
+    /// Square root. Quiet playing still comes through.
+    Soft,
+    /// Squared. Needs a firm touch to get loud.
+    Hard,
+    /// Always full level, regardless of velocity.
+    Fixed,
+}
+
+impl VelocityCurve {
+    /// Amplitude (0.0–1.0) for a velocity.
+    pub fn apply(self, velocity: Velocity) -> f64 {
+        let normalized = normalize(velocity);
+
+        match self {
+            VelocityCurve::Linear => normalized,
+            VelocityCurve::Soft => normalized.sqrt(),
+            VelocityCurve::Hard => normalized * normalized,
+            VelocityCurve::Fixed => 1.0,
+        }
+    }
+}
+
+/// Where NoteOn velocity goes besides amplitude. Modulation depths use the
+/// raw (linear) velocity so the amplitude curve doesn't change their feel.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VelocityRouting {
+    pub amplitude: VelocityCurve,
+    /// How much harder hits shorten attack and decay (0.0–1.0). At 1.0 a
+    /// full-velocity note has instant attack and decay.
+    pub envelope_time: f32,
+    /// Octaves the filter cutoff opens at full velocity.
+    pub cutoff: f64,
+}
+
+impl Default for VelocityRouting {
+    fn default() -> Self {
+        Self {
+            amplitude: VelocityCurve::Linear,
+            envelope_time: 0.0,
+            cutoff: 0.0,
+        }
+    }
+}
+
+impl VelocityRouting {
+    /// Multiplier for attack and decay times.
+    pub fn time_scale(&self, velocity: Velocity) -> f32 {
+        (1.0 - self.envelope_time.clamp(0.0, 1.0) * normalize(velocity) as f32).max(0.0)
+    }
+
+    /// Filter cutoff in Hz for a base cutoff, capped at `MAX_CUTOFF`.
+    pub fn cutoff(&self, base: f64, velocity: Velocity) -> f64 {
+        (base * (self.cutoff * normalize(velocity)).exp2()).min(MAX_CUTOFF)
+    }
+}
+
+fn normalize(velocity: Velocity) -> f64 {
+    u8::from(velocity) as f64 / 127.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn velocity(value: u8) -> Velocity {
+        Velocity::new(value).unwrap()
+    }
+
+    #[test]
+    fn curves_agree_at_extremes() {
+        for curve in [
+            VelocityCurve::Linear,
+            VelocityCurve::Soft,
+            VelocityCurve::Hard,
+        ] {
+            assert_eq!(curve.apply(Velocity::MIN), 0.0);
+            assert_eq!(curve.apply(Velocity::MAX), 1.0);
+        }
+    }
+
+    #[test]
+    fn soft_is_louder_than_hard_mid_range() {
+        let mid = velocity(64);
+
+        assert!(VelocityCurve::Soft.apply(mid) > VelocityCurve::Linear.apply(mid));
+        assert!(VelocityCurve::Hard.apply(mid) < VelocityCurve::Linear.apply(mid));
+    }
+
+    #[test]
+    fn fixed_ignores_velocity() {
+        assert_eq!(VelocityCurve::Fixed.apply(Velocity::MIN), 1.0);
+        assert_eq!(VelocityCurve::Fixed.apply(velocity(30)), 1.0);
+    }
+
+    #[test]
+    fn time_scale_shortens_hard_hits() {
+        let routing = VelocityRouting {
+            envelope_time: 0.5,
+            ..VelocityRouting::default()
+        };
+
+        assert_eq!(routing.time_scale(Velocity::MIN), 1.0);
+        assert_eq!(routing.time_scale(Velocity::MAX), 0.5);
+        assert_eq!(VelocityRouting::default().time_scale(Velocity::MAX), 1.0);
+    }
+
+    #[test]
+    fn cutoff_opens_by_octaves() {
+        let routing = VelocityRouting {
+            cutoff: 2.0,
+            ..VelocityRouting::default()
+        };
+
+        assert_eq!(routing.cutoff(1000.0, Velocity::MIN), 1000.0);
+        assert!((routing.cutoff(1000.0, Velocity::MAX) - 4000.0).abs() < 1e-9);
+        assert_eq!(routing.cutoff(10000.0, Velocity::MAX), MAX_CUTOFF);
+    }
+}
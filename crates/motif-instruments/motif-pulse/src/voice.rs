@@ -1,18 +1,73 @@
+use motif_engine::{
+    envelope::Envelope,
+    steal::{StealFade, StealVoice},
+};
 use wmidi::{Note, Velocity};
 
-use crate::envelope::Envelope;
+use crate::{
+    filter::{Lowpass, LowpassCoefficients},
+    unison::{MAX_UNISON, Oscillator},
+    velocity::MAX_CUTOFF,
+};
 
-/// Single voice of polyphony. Owns a phase accumulator and ADSR envelope.
-/// PulseSynth allocates 8 of these; idle voices are skipped during render.
-#[derive(Debug, Default)]
+/// Single voice of polyphony. Owns a phase accumulator per unison sub-oscillator
+/// and an envelope. Pulse allocates a fixed pool of these; idle voices are
+/// skipped during render.
+#[derive(Debug)]
 pub struct Voice {
-    pub phase: f64,
+    pub phases: [f64; MAX_UNISON],
     pub frequency: f64,
     pub velocity: Velocity,
+    /// Amplitude after the velocity curve.
+    pub gain: f64,
+    /// Lowpass cutoff in Hz after velocity routing.
+    pub cutoff: f64,
     pub envelope: Envelope,
     pub note: Option<Note>,
     // Monotonic counter for voice-steal ordering (higher = newer).
     pub age: u64,
+    /// Key is physically down. A voice that is active, not releasing, and
+    /// not held is being kept alive by a pedal.
+    pub held: bool,
+    /// Latched by the sostenuto pedal while its key was down.
+    pub sostenuto: bool,
+    steal: StealFade<Strike>,
+    // One filter per output channel.
+    filters: [Lowpass; 2],
+    coefficients: LowpassCoefficients,
+    // (cutoff, resonance, sample_rate) the coefficients were computed for.
+    coefficients_for: (f64, f64, f64),
+}
+
+/// Per-note values fixed at NoteOn, after tuning and velocity routing.
+#[derive(Debug, Clone, Copy)]
+pub struct Strike {
+    pub velocity: Velocity,
+    pub gain: f64,
+    pub frequency: f64,
+    pub cutoff: f64,
+    pub phases: [f64; MAX_UNISON],
+}
+
+impl Default for Voice {
+    fn default() -> Self {
+        Self {
+            phases: [0.0; MAX_UNISON],
+            frequency: 0.0,
+            velocity: Velocity::default(),
+            gain: 0.0,
+            cutoff: MAX_CUTOFF,
+            envelope: Envelope::default(),
+            note: None,
+            age: 0,
+            held: false,
+            sostenuto: false,
+            steal: StealFade::new(),
+            filters: [Lowpass::default(); 2],
+            coefficients: LowpassCoefficients::default(),
+            coefficients_for: (0.0, 0.0, 0.0),
+        }
+    }
 }
 
 impl Voice {
@@ -20,41 +75,125 @@ impl Voice {
         Self::default()
     }
 
-    /// Render one sample. `duty_cycle` (0.0–1.0) controls the fraction of each
-    /// wave cycle spent "high" — 0.5 is a square wave, lower values are thinner.
-    pub fn render(&mut self, duty_cycle: f64, sample_rate: f64) -> f64 {
-        self.phase += self.frequency / sample_rate;
+    /// Render one stereo sample. `duty_cycle` (0.0–1.0) controls the fraction
+    /// of each wave cycle spent "high" — 0.5 is a square wave, lower values are
+    /// thinner. `oscillators` is the unison layout; extra phases are ignored.
+    pub fn render(
+        &mut self,
+        duty_cycle: f64,
+        resonance: f64,
+        sample_rate: f64,
+        oscillators: &[Oscillator],
+    ) -> (f64, f64) {
+        let amplitude = self.envelope.tick(sample_rate) * self.gain * self.steal.gain();
+
+        let mut left = 0.0;
+        let mut right = 0.0;
+
+        for (phase, oscillator) in self.phases.iter_mut().zip(oscillators) {
+            *phase += self.frequency * oscillator.ratio / sample_rate;
 
-        while self.phase >= 1.0 {
-            self.phase -= 1.0;
+            while *phase >= 1.0 {
+                *phase -= 1.0;
+            }
+
+            let pulse = if *phase > duty_cycle { 1.0 } else { -1.0 };
+
+            left += pulse * oscillator.gain_l;
+            right += pulse * oscillator.gain_r;
         }
 
-        let pulse = if self.phase > duty_cycle { 1.0 } else { -1.0 };
+        self.tick_steal(sample_rate);
 
-        pulse * self.envelope.tick(sample_rate) * (u8::from(self.velocity) as f64 / 127.0)
-    }
+        if self.cutoff < MAX_CUTOFF {
+            let key = (self.cutoff, resonance, sample_rate);
 
-    pub fn trigger(&mut self, note: Note, velocity: Velocity, frequency: f64, age: u64) {
-        self.phase = 0.0;
+            if self.coefficients_for != key {
+                self.coefficients = LowpassCoefficients::new(self.cutoff, resonance, sample_rate);
+                self.coefficients_for = key;
+            }
 
-        self.note = Some(note);
-        self.velocity = velocity;
-        self.frequency = frequency;
-        self.age = age;
+            left = self.filters[0].process(left, &self.coefficients);
+            right = self.filters[1].process(right, &self.coefficients);
+        }
 
-        self.envelope.trigger();
+        (left * amplitude, right * amplitude)
     }
 
-    pub fn release(&mut self) {
-        self.envelope.release();
+    /// Start a note, dropping any sostenuto latch from the voice's last note.
+    pub fn trigger(&mut self, note: Note, strike: Strike, age: u64) {
+        self.note = Some(note);
+        self.age = age;
+        self.held = true;
+        self.sostenuto = false;
+        self.play(strike);
     }
 
-    pub fn is_active(&self) -> bool {
-        !self.envelope.is_idle()
+    /// Sounding only because a pedal is down.
+    pub fn is_pedal_held(&self) -> bool {
+        self.is_active() && !self.held && !self.is_releasing()
     }
 
     pub fn reset(&mut self) {
         self.note = None;
+        self.held = false;
+        self.sostenuto = false;
+        self.steal.reset();
         self.envelope.reset();
+
+        for filter in &mut self.filters {
+            filter.reset();
+        }
+    }
+}
+
+impl StealVoice for Voice {
+    type Strike = Strike;
+
+    fn steal_fade(&self) -> &StealFade<Strike> {
+        &self.steal
+    }
+
+    fn steal_fade_mut(&mut self) -> &mut StealFade<Strike> {
+        &mut self.steal
+    }
+
+    fn age(&self) -> u64 {
+        self.age
+    }
+
+    fn note(&self) -> Option<Note> {
+        self.note
+    }
+
+    fn is_sounding(&self) -> bool {
+        !self.envelope.is_idle()
+    }
+
+    fn is_sounding_released(&self) -> bool {
+        self.envelope.is_releasing()
+    }
+
+    /// Envelope times velocity gain, before the waveform.
+    fn sounding_level(&self) -> f64 {
+        self.envelope.level() * self.gain
+    }
+
+    fn start(&mut self, strike: Strike) {
+        self.phases = strike.phases;
+        self.velocity = strike.velocity;
+        self.gain = strike.gain;
+        self.frequency = strike.frequency;
+        self.cutoff = strike.cutoff;
+
+        for filter in &mut self.filters {
+            filter.reset();
+        }
+
+        self.envelope.trigger();
+    }
+
+    fn release_sounding(&mut self) {
+        self.envelope.release();
     }
 }
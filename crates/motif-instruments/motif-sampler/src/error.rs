@@ -0,0 +1,13 @@
+#[derive(Debug, thiserror::Error)]
+pub enum SamplerError {
+    #[error("Sample file error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid WAV file: {0}")]
+    Wav(#[from] hound::Error),
+    #[error("Invalid FLAC file: {0}")]
+    Flac(#[from] claxon::Error),
+    #[error("Unsupported sample format: {0}")]
+    Unsupported(String),
+    #[error("Buffer is full")]
+    BufferFull,
+}
@@ -0,0 +1,5 @@
+pub mod error;
+pub mod sample;
+pub mod sampler;
+pub mod voice;
+pub mod zone;
@@ -0,0 +1,222 @@
+use std::{ops::Range, path::Path};
+
+use crate::error::SamplerError;
+
+/// Decoded audio held in memory as planar f32 (-1.0..1.0). Loaded once off
+/// the audio thread and shared between zones through an `Arc`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub channels: Vec<Vec<f32>>,
+    /// Rate the sample was recorded at. Playback resamples to the output rate.
+    pub sample_rate: f64,
+}
+
+impl Sample {
+    /// Build from planar channel data. Panics if `channels` is empty or the
+    /// channels differ in length.
+    pub fn new(channels: Vec<Vec<f32>>, sample_rate: f64) -> Self {
+        assert!(!channels.is_empty(), "Sample needs at least one channel");
+        assert!(
+            channels.iter().all(|c| c.len() == channels[0].len()),
+            "Sample channels must be the same length"
+        );
+
+        Self {
+            channels,
+            sample_rate,
+        }
+    }
+
+    /// Decode a WAV or FLAC file, chosen by extension. Blocking I/O — never
+    /// call from the audio thread.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SamplerError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("wav") => Self::load_wav(path),
+            Some("flac") => Self::load_flac(path),
+            _ => Err(SamplerError::Unsupported(path.display().to_string())),
+        }
+    }
+
+    pub fn load_wav(path: impl AsRef<Path>) -> Result<Self, SamplerError> {
+        let reader = hound::WavReader::open(path)?;
+        let spec = reader.spec();
+
+        let interleaved: Vec<f32> = match spec.sample_format {
+            hound::SampleFormat::Float => reader.into_samples::<f32>().collect::<Result<_, _>>()?,
+            hound::SampleFormat::Int => {
+                let scale = int_scale(spec.bits_per_sample as u32);
+
+                reader
+                    .into_samples::<i32>()
+                    .map(|s| s.map(|s| s as f32 * scale))
+                    .collect::<Result<_, _>>()?
+            }
+        };
+
+        Ok(Self::from_interleaved(
+            &interleaved,
+            spec.channels as usize,
+            spec.sample_rate as f64,
+        ))
+    }
+
+    pub fn load_flac(path: impl AsRef<Path>) -> Result<Self, SamplerError> {
+        let mut reader = claxon::FlacReader::open(path)?;
+        let info = reader.streaminfo();
+        let scale = int_scale(info.bits_per_sample);
+
+        let interleaved: Vec<f32> = reader
+            .samples()
+            .map(|s| s.map(|s| s as f32 * scale))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::from_interleaved(
+            &interleaved,
+            info.channels as usize,
+            info.sample_rate as f64,
+        ))
+    }
+
+    fn from_interleaved(interleaved: &[f32], channels: usize, sample_rate: f64) -> Self {
+        let channels = channels.max(1);
+
+        let planar = (0..channels)
+            .map(|ch| {
+                interleaved
+                    .iter()
+                    .skip(ch)
+                    .step_by(channels)
+                    .copied()
+                    .collect()
+            })
+            .collect();
+
+        Self::new(planar, sample_rate)
+    }
+
+    pub fn frames(&self) -> usize {
+        self.channels[0].len()
+    }
+
+    /// Value of `channel` at a fractional frame position, using 4-point cubic
+    /// Hermite interpolation. Inside `looped`, neighbours past the loop end
+    /// wrap to the loop start so the seam stays smooth. Outside the sample
+    /// the signal is silence. Channels past the last one read the last.
+    pub fn read(&self, channel: usize, position: f64, looped: Option<&Range<usize>>) -> f64 {
+        let data = &self.channels[channel.min(self.channels.len() - 1)];
+        let index = position.floor();
+        let fraction = position - index;
+        let index = index as i64;
+
+        let tap = |offset: i64| {
+            let mut i = index + offset;
+
+            if let Some(range) = looped
+                && i >= range.end as i64
+            {
+                i -= range.len() as i64;
+            }
+
+            if i < 0 {
+                return 0.0;
+            }
+
+            data.get(i as usize).map_or(0.0, |&s| s as f64)
+        };
+
+        hermite(fraction, tap(-1), tap(0), tap(1), tap(2))
+    }
+}
+
+/// Scale factor from a signed integer of `bits` to -1.0..1.0.
+fn int_scale(bits: u32) -> f32 {
+    1.0 / (1u64 << (bits.clamp(1, 32) - 1)) as f32
+}
+
+/// Catmull-Rom spline through `y1` (at 0.0) and `y2` (at 1.0).
+fn hermite(x: f64, y0: f64, y1: f64, y2: f64, y3: f64) -> f64 {
+    let c1 = 0.5 * (y2 - y0);
+    let c2 = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+    let c3 = 0.5 * (y3 - y0) + 1.5 * (y1 - y2);
+
+    ((c3 * x + c2) * x + c1) * x + y1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_wav(name: &str, channels: u16, frames: &[i16]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for &sample in frames {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        path
+    }
+
+    #[test]
+    fn loads_stereo_wav_as_planar() {
+        let path = write_wav("motif-sampler-stereo.wav", 2, &[0, 16384, -16384, 0]);
+
+        let sample = Sample::load(&path).unwrap();
+
+        assert_eq!(sample.sample_rate, 44100.0);
+        assert_eq!(sample.channels, vec![vec![0.0, -0.5], vec![0.5, 0.0]]);
+    }
+
+    #[test]
+    fn unknown_extension_is_unsupported() {
+        assert!(matches!(
+            Sample::load("kick.mp3"),
+            Err(SamplerError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn read_hits_sample_points_exactly() {
+        let sample = Sample::new(vec![vec![0.0, 1.0, 0.5, -1.0]], 48000.0);
+
+        for (i, &expected) in sample.channels[0].iter().enumerate() {
+            assert_eq!(sample.read(0, i as f64, None), expected as f64);
+        }
+    }
+
+    #[test]
+    fn read_interpolates_between_points() {
+        let ramp = Sample::new(vec![(0..8).map(|i| i as f32).collect()], 48000.0);
+
+        // Hermite reproduces straight lines exactly.
+        assert!((ramp.read(0, 2.25, None) - 2.25).abs() < 1e-9);
+        assert!((ramp.read(0, 5.5, None) - 5.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn loop_wraps_neighbours_to_loop_start() {
+        let sample = Sample::new(vec![vec![9.0, 1.0, 2.0, 3.0, 4.0]], 48000.0);
+        let looped = 1..5;
+
+        // Frame 5 doesn't exist; inside the loop it reads frame 1.
+        assert_eq!(sample.read(0, 5.0, Some(&looped)), 1.0);
+        assert_eq!(sample.read(0, 5.0, None), 0.0);
+        assert_ne!(
+            sample.read(0, 4.5, Some(&looped)),
+            sample.read(0, 4.5, None)
+        );
+    }
+}
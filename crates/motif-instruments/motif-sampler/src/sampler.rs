@@ -0,0 +1,433 @@
+use std::ops::Range;
+
+use motif_core::tuning::Tuning;
+use motif_engine::{
+    buffer::AudioBuffer,
+    events::{Event, MidiEvent},
+    node::AudioNode,
+    steal::{StealPolicy, StealVoice},
+    swap::{self, SwapReceiver, SwapSender},
+};
+
+use crate::{
+    error::SamplerError,
+    voice::{Strike, Voice},
+    zone::Keymap,
+};
+
+/// Scales the summed voices. Samples are usually normalized close to full
+/// scale, so a few overlapping hits still fit.
+const GAIN: f64 = 0.5;
+
+/// Voice count used by `Sampler::new()`.
+pub const DEFAULT_POLYPHONY: usize = 16;
+
+/// Keymaps that can be queued for the audio thread at once.
+const KEYMAP_QUEUE: usize = 4;
+
+/// Polyphonic sample player. Implements AudioNode — feed it NoteOn/NoteOff
+/// events via evaluate_node() and it plays the matching zone, repitched from
+/// the zone's root note through `tuning`.
+///
+/// Samples are decoded off the audio thread and handed over as a whole
+/// `Keymap` through `SamplerHandle`. Replaced keymaps travel back to the
+/// handle to be dropped there, so the audio thread never frees sample memory.
+pub struct Sampler {
+    /// One per overlapping sample playback, sized by `with_polyphony`.
+    pub voices: Vec<Voice>,
+    pub keymap: Box<Keymap>,
+    pub steal_policy: StealPolicy,
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+    /// Note → frequency table. Pitch is the ratio between the played note
+    /// and the zone's root. Unmapped notes are ignored.
+    pub tuning: Tuning,
+    pub next_age: u64,
+    keymaps: SwapReceiver<Keymap>,
+}
+
+/// UI-side handle for swapping a Sampler's keymap while it plays.
+pub struct SamplerHandle {
+    keymaps: SwapSender<Keymap>,
+}
+
+impl Sampler {
+    /// Build an empty sampler and the handle that feeds it keymaps.
+    pub fn new() -> (Self, SamplerHandle) {
+        Self::with_polyphony(DEFAULT_POLYPHONY)
+    }
+
+    /// Build with a fixed voice count. Panics if `polyphony` is zero.
+    pub fn with_polyphony(polyphony: usize) -> (Self, SamplerHandle) {
+        assert!(polyphony > 0, "Sampler needs at least one voice");
+
+        let (sender, receiver) = swap::channel(KEYMAP_QUEUE);
+
+        let sampler = Self {
+            voices: (0..polyphony).map(|_| Voice::new()).collect(),
+            keymap: Box::default(),
+            steal_policy: StealPolicy::Oldest,
+            attack: 0.002,
+            decay: 0.0,
+            sustain: 1.0,
+            release: 0.1,
+            tuning: Tuning::default(),
+            next_age: 0,
+            keymaps: receiver,
+        };
+
+        let handle = SamplerHandle { keymaps: sender };
+
+        (sampler, handle)
+    }
+
+    /// Install the next queued keymap, if any. The old keymap's voices are
+    /// cut because their zone indices no longer mean anything.
+    fn receive_keymap(&mut self) {
+        if !self.keymaps.receive(&mut self.keymap) {
+            return;
+        }
+
+        for voice in &mut self.voices {
+            voice.reset();
+        }
+    }
+}
+
+impl SamplerHandle {
+    /// Queue a keymap for the audio thread. It takes effect at the next
+    /// event or render, whichever comes first.
+    pub fn send_keymap(&mut self, keymap: Keymap) -> Result<(), SamplerError> {
+        self.keymaps
+            .send(keymap)
+            .map_err(|_| SamplerError::BufferFull)
+    }
+
+    /// Drop keymaps the audio thread has replaced. Call regularly from a
+    /// non-audio thread. Returns how many were freed.
+    pub fn collect_garbage(&mut self) -> usize {
+        self.keymaps.collect_garbage()
+    }
+}
+
+impl AudioNode for Sampler {
+    fn render(
+        &mut self,
+        _inputs: &[&AudioBuffer],
+        output: &mut AudioBuffer,
+        frame_range: Range<usize>,
+        sample_rate: f64,
+    ) {
+        self.receive_keymap();
+
+        let zones = &self.keymap.zones;
+        let (left, right) = output.two_channels_mut(0, 1);
+
+        for frame in frame_range {
+            let mut sum_l = 0.0;
+            let mut sum_r = 0.0;
+
+            for voice in &mut self.voices {
+                if voice.is_active() {
+                    let (l, r) = voice.render(zones, sample_rate);
+                    sum_l += l;
+                    sum_r += r;
+                }
+            }
+
+            left[frame] = (sum_l * GAIN) as f32;
+            right[frame] = (sum_r * GAIN) as f32;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        self.receive_keymap();
+
+        match event {
+            Event::Midi(event) => match event {
+                MidiEvent::NoteOn { note, velocity } => {
+                    let Some(index) = self.keymap.find(*note, *velocity) else {
+                        return;
+                    };
+                    let zone = &self.keymap.zones[index];
+
+                    let (Some(frequency), Some(root)) = (
+                        self.tuning.frequency(*note),
+                        self.tuning.frequency(zone.root),
+                    ) else {
+                        return;
+                    };
+
+                    let strike = Strike {
+                        zone: index,
+                        pitch: frequency / root * (zone.tune / 1200.0).exp2(),
+                        gain: zone.gain * u8::from(*velocity) as f64 / 127.0,
+                    };
+
+                    let voice_index = self.steal_policy.pick(&self.voices, *note);
+                    let voice = &mut self.voices[voice_index];
+                    voice.trigger(*note, strike, self.next_age);
+                    voice
+                        .envelope
+                        .adsr(self.attack, self.decay, self.sustain, self.release);
+
+                    self.next_age += 1;
+                }
+                MidiEvent::NoteOff { note } => {
+                    for voice in &mut self.voices {
+                        if voice.note == Some(*note) && !voice.is_releasing() {
+                            voice.release();
+                        }
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    fn reset(&mut self) {
+        for voice in &mut self.voices {
+            voice.reset();
+        }
+
+        self.next_age = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use motif_engine::{
+        graph::evaluate_node,
+        test_util::{SAMPLE_RATE, has_signal, is_silent, note_off, note_on},
+    };
+    use wmidi::Note;
+
+    use crate::{
+        sample::Sample,
+        zone::{LoopMode, Zone},
+    };
+
+    /// 1000-frame ramp recorded at the output rate, so the read position
+    /// after N frames at root pitch is exactly N.
+    fn ramp() -> Arc<Sample> {
+        Arc::new(Sample::new(
+            vec![(0..1000).map(|i| i as f32 / 1000.0).collect()],
+            SAMPLE_RATE,
+        ))
+    }
+
+    fn make_sampler(zone: Zone) -> (Sampler, SamplerHandle) {
+        let (mut sampler, handle) = Sampler::new();
+        sampler.keymap = Box::new(Keymap::new(vec![zone]));
+
+        (sampler, handle)
+    }
+
+    #[test]
+    fn root_note_plays_at_recorded_speed() {
+        let (mut sampler, _handle) = make_sampler(Zone::new(ramp(), Note::C4));
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        evaluate_node(
+            &mut sampler,
+            &[],
+            &mut output,
+            &[note_on(0, Note::C4)],
+            SAMPLE_RATE,
+        );
+
+        assert!((sampler.voices[0].position - 256.0).abs() < 1e-9);
+        assert!(has_signal(&output, 1..256));
+    }
+
+    #[test]
+    fn octave_up_plays_twice_as_fast() {
+        let (mut sampler, _handle) = make_sampler(Zone::new(ramp(), Note::C4));
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        evaluate_node(
+            &mut sampler,
+            &[],
+            &mut output,
+            &[note_on(0, Note::C5)],
+            SAMPLE_RATE,
+        );
+
+        assert!((sampler.voices[0].position - 512.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sample_rate_mismatch_is_resampled() {
+        let sample = Arc::new(Sample::new(vec![vec![0.5; 1000]], SAMPLE_RATE / 2.0));
+        let (mut sampler, _handle) = make_sampler(Zone::new(sample, Note::C4));
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        evaluate_node(
+            &mut sampler,
+            &[],
+            &mut output,
+            &[note_on(0, Note::C4)],
+            SAMPLE_RATE,
+        );
+
+        assert!((sampler.voices[0].position - 128.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn one_shot_stops_at_sample_end() {
+        let (mut sampler, _handle) = make_sampler(Zone::new(ramp(), Note::C4));
+        let mut output = AudioBuffer::new(2, 1024);
+        output.prepare(1024);
+
+        evaluate_node(
+            &mut sampler,
+            &[],
+            &mut output,
+            &[note_on(0, Note::C4)],
+            SAMPLE_RATE,
+        );
+
+        assert!(!sampler.voices[0].is_active());
+        assert!(is_silent(&output, 1001..1024));
+    }
+
+    #[test]
+    fn forward_loop_keeps_playing() {
+        let zone = Zone {
+            loop_mode: LoopMode::Forward,
+            loop_points: 200..800,
+            ..Zone::new(ramp(), Note::C4)
+        };
+        let (mut sampler, _handle) = make_sampler(zone);
+        let mut output = AudioBuffer::new(2, 1024);
+
+        for i in 0..4 {
+            output.prepare(1024);
+            let events = if i == 0 {
+                vec![note_on(0, Note::C4)]
+            } else {
+                vec![]
+            };
+            evaluate_node(&mut sampler, &[], &mut output, &events, SAMPLE_RATE);
+        }
+
+        let voice = &sampler.voices[0];
+        assert!(voice.is_active());
+        assert!((200.0..800.0).contains(&voice.position));
+        assert!(has_signal(&output, 0..1024));
+    }
+
+    #[test]
+    fn note_off_releases_to_silence() {
+        let zone = Zone {
+            loop_mode: LoopMode::Forward,
+            ..Zone::new(ramp(), Note::C4)
+        };
+        let (mut sampler, _handle) = make_sampler(zone);
+        let mut output = AudioBuffer::new(2, 1024);
+
+        output.prepare(512);
+        evaluate_node(
+            &mut sampler,
+            &[],
+            &mut output,
+            &[note_on(0, Note::C4)],
+            SAMPLE_RATE,
+        );
+
+        // Release is 0.1s = 4800 samples.
+        output.prepare(1024);
+        evaluate_node(
+            &mut sampler,
+            &[],
+            &mut output,
+            &[note_off(0, Note::C4)],
+            SAMPLE_RATE,
+        );
+
+        for _ in 0..5 {
+            output.prepare(1024);
+            evaluate_node(&mut sampler, &[], &mut output, &[], SAMPLE_RATE);
+        }
+
+        assert!(is_silent(&output, 0..1024));
+    }
+
+    #[test]
+    fn notes_outside_every_zone_are_ignored() {
+        let zone = Zone {
+            keys: Note::C4..=Note::B4,
+            ..Zone::new(ramp(), Note::C4)
+        };
+        let (mut sampler, _handle) = make_sampler(zone);
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        evaluate_node(
+            &mut sampler,
+            &[],
+            &mut output,
+            &[note_on(0, Note::C2)],
+            SAMPLE_RATE,
+        );
+
+        assert!(is_silent(&output, 0..256));
+    }
+
+    #[test]
+    fn keymap_swap_through_handle() {
+        let (mut sampler, mut handle) = Sampler::new();
+        let mut output = AudioBuffer::new(2, 256);
+
+        // Empty keymap: nothing plays.
+        output.prepare(256);
+        evaluate_node(
+            &mut sampler,
+            &[],
+            &mut output,
+            &[note_on(0, Note::C4)],
+            SAMPLE_RATE,
+        );
+        assert!(is_silent(&output, 0..256));
+
+        handle
+            .send_keymap(Keymap::new(vec![Zone::new(ramp(), Note::C4)]))
+            .unwrap();
+
+        output.prepare(256);
+        evaluate_node(
+            &mut sampler,
+            &[],
+            &mut output,
+            &[note_on(0, Note::C4)],
+            SAMPLE_RATE,
+        );
+        assert!(has_signal(&output, 1..256));
+
+        // The empty keymap came back to be freed off the audio thread.
+        assert_eq!(handle.collect_garbage(), 1);
+    }
+
+    #[test]
+    fn full_queue_reports_buffer_full() {
+        let (_sampler, mut handle) = Sampler::new();
+
+        for _ in 0..KEYMAP_QUEUE {
+            handle.send_keymap(Keymap::default()).unwrap();
+        }
+
+        assert!(matches!(
+            handle.send_keymap(Keymap::default()),
+            Err(SamplerError::BufferFull)
+        ));
+    }
+}
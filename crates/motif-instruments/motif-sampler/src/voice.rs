@@ -0,0 +1,141 @@
+use motif_engine::{
+    envelope::Envelope,
+    steal::{StealFade, StealVoice},
+};
+use wmidi::Note;
+
+use crate::zone::Zone;
+
+/// Single voice of polyphony. Plays one zone's sample from a fractional
+/// read position through an envelope. Voices refer to zones by index into
+/// the Sampler's current keymap.
+#[derive(Debug, Default)]
+pub struct Voice {
+    pub zone: usize,
+    /// Read position in sample frames.
+    pub position: f64,
+    /// Playback speed relative to the sample's recorded pitch and rate,
+    /// before conversion to the output sample rate.
+    pub pitch: f64,
+    pub gain: f64,
+    pub envelope: Envelope,
+    pub note: Option<Note>,
+    // NoteOn count when this playback started, for stealing.
+    pub age: u64,
+    steal: StealFade<Strike>,
+}
+
+/// Per-note values fixed at NoteOn, after zone lookup and velocity.
+#[derive(Debug, Clone, Copy)]
+pub struct Strike {
+    pub zone: usize,
+    pub pitch: f64,
+    pub gain: f64,
+}
+
+impl Voice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render one stereo sample. Mono samples play on both channels.
+    /// `zones` must be the keymap the voice was triggered from.
+    pub fn render(&mut self, zones: &[Zone], sample_rate: f64) -> (f64, f64) {
+        let Some(zone) = zones.get(self.zone) else {
+            self.reset();
+            return (0.0, 0.0);
+        };
+
+        let sample = &zone.sample;
+        let looped = zone.loop_range();
+
+        let amplitude = self.envelope.tick(sample_rate) * self.gain * self.steal.gain();
+        let left = sample.read(0, self.position, looped);
+        let right = sample.read(1, self.position, looped);
+
+        self.position += self.pitch * sample.sample_rate / sample_rate;
+
+        match looped {
+            Some(range) => {
+                while self.position >= range.end as f64 {
+                    self.position -= range.len() as f64;
+                }
+            }
+            None => {
+                if self.position >= sample.frames() as f64 {
+                    // Ran off the end: the voice is done even if the envelope isn't.
+                    self.envelope.reset();
+
+                    match self.steal.take() {
+                        Some(pending) => self.start_pending(pending),
+                        None => self.note = None,
+                    }
+
+                    return (left * amplitude, right * amplitude);
+                }
+            }
+        }
+
+        self.tick_steal(sample_rate);
+
+        (left * amplitude, right * amplitude)
+    }
+
+    /// Start a note on the zone picked at NoteOn.
+    pub fn trigger(&mut self, note: Note, strike: Strike, age: u64) {
+        self.note = Some(note);
+        self.age = age;
+        self.play(strike);
+    }
+
+    pub fn reset(&mut self) {
+        self.note = None;
+        self.steal.reset();
+        self.envelope.reset();
+    }
+}
+
+impl StealVoice for Voice {
+    type Strike = Strike;
+
+    fn steal_fade(&self) -> &StealFade<Strike> {
+        &self.steal
+    }
+
+    fn steal_fade_mut(&mut self) -> &mut StealFade<Strike> {
+        &mut self.steal
+    }
+
+    fn age(&self) -> u64 {
+        self.age
+    }
+
+    fn note(&self) -> Option<Note> {
+        self.note
+    }
+
+    fn is_sounding(&self) -> bool {
+        !self.envelope.is_idle()
+    }
+
+    fn is_sounding_released(&self) -> bool {
+        self.envelope.is_releasing()
+    }
+
+    /// Envelope times velocity gain, before the sample.
+    fn sounding_level(&self) -> f64 {
+        self.envelope.level() * self.gain
+    }
+
+    fn start(&mut self, strike: Strike) {
+        self.zone = strike.zone;
+        self.pitch = strike.pitch;
+        self.gain = strike.gain;
+        self.position = 0.0;
+        self.envelope.trigger();
+    }
+
+    fn release_sounding(&mut self) {
+        self.envelope.release();
+    }
+}
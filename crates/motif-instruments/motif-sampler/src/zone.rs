@@ -0,0 +1,221 @@
+use std::{
+    collections::HashMap,
+    ops::{Range, RangeInclusive},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use wmidi::{Note, Velocity};
+
+use crate::{error::SamplerError, sample::Sample};
+
+/// What happens when playback reaches the end of the loop region.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LoopMode {
+    /// Play through to the end of the sample once.
+    #[default]
+    Off,
+    /// Jump from the loop end back to the loop start for as long as the voice sounds.
+    Forward,
+}
+
+/// A sample mapped onto a key × velocity rectangle.
+#[derive(Debug, Clone)]
+pub struct Zone {
+    pub sample: Arc<Sample>,
+    /// Note at which the sample plays back at its recorded pitch.
+    pub root: Note,
+    pub keys: RangeInclusive<Note>,
+    pub velocities: RangeInclusive<u8>,
+    /// Fine tuning in cents, applied on top of the root mapping.
+    pub tune: f64,
+    /// Linear gain.
+    pub gain: f64,
+    pub loop_mode: LoopMode,
+    /// Loop region in frames. Ignored when `loop_mode` is `Off`.
+    pub loop_points: Range<usize>,
+}
+
+impl Zone {
+    /// A zone covering every key and velocity, looping the whole sample
+    /// when looping is enabled.
+    pub fn new(sample: Arc<Sample>, root: Note) -> Self {
+        let frames = sample.frames();
+
+        Self {
+            sample,
+            root,
+            keys: Note::LOWEST_NOTE..=Note::HIGHEST_NOTE,
+            velocities: 0..=127,
+            tune: 0.0,
+            gain: 1.0,
+            loop_mode: LoopMode::Off,
+            loop_points: 0..frames,
+        }
+    }
+
+    pub fn contains(&self, note: Note, velocity: Velocity) -> bool {
+        self.keys.contains(&note) && self.velocities.contains(&u8::from(velocity))
+    }
+
+    /// Loop region if looping is on and the points are usable.
+    pub fn loop_range(&self) -> Option<&Range<usize>> {
+        let usable = !self.loop_points.is_empty() && self.loop_points.end <= self.sample.frames();
+
+        (self.loop_mode == LoopMode::Forward && usable).then_some(&self.loop_points)
+    }
+}
+
+/// Set of zones played by a Sampler. When zones overlap, the first match wins.
+#[derive(Debug, Clone, Default)]
+pub struct Keymap {
+    pub zones: Vec<Zone>,
+}
+
+/// Where a zone's sample lives on disk, plus its mapping. Resolved into a
+/// `Zone` by `Keymap::load()`.
+#[derive(Debug, Clone)]
+pub struct ZoneSpec {
+    pub path: PathBuf,
+    pub root: Note,
+    pub keys: RangeInclusive<Note>,
+    pub velocities: RangeInclusive<u8>,
+    pub tune: f64,
+    pub gain: f64,
+    pub loop_mode: LoopMode,
+    /// `None` loops the whole sample.
+    pub loop_points: Option<Range<usize>>,
+}
+
+impl Keymap {
+    pub fn new(zones: Vec<Zone>) -> Self {
+        Self { zones }
+    }
+
+    /// Decode every sample and build the keymap. Specs sharing a path share
+    /// one decoded sample. Blocking I/O — never call from the audio thread.
+    pub fn load(specs: &[ZoneSpec]) -> Result<Self, SamplerError> {
+        let mut samples: HashMap<&PathBuf, Arc<Sample>> = HashMap::new();
+        let mut zones = Vec::with_capacity(specs.len());
+
+        for spec in specs {
+            let sample = match samples.get(&spec.path) {
+                Some(sample) => Arc::clone(sample),
+                None => {
+                    let sample = Arc::new(Sample::load(&spec.path)?);
+                    samples.insert(&spec.path, Arc::clone(&sample));
+                    sample
+                }
+            };
+
+            let mut zone = Zone::new(sample, spec.root);
+            zone.keys = spec.keys.clone();
+            zone.velocities = spec.velocities.clone();
+            zone.tune = spec.tune;
+            zone.gain = spec.gain;
+            zone.loop_mode = spec.loop_mode;
+
+            if let Some(points) = &spec.loop_points {
+                zone.loop_points = points.clone();
+            }
+
+            zones.push(zone);
+        }
+
+        Ok(Self::new(zones))
+    }
+
+    /// Index of the zone that plays `note` at `velocity`.
+    pub fn find(&self, note: Note, velocity: Velocity) -> Option<usize> {
+        self.zones.iter().position(|z| z.contains(note, velocity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(keys: RangeInclusive<Note>, velocities: RangeInclusive<u8>) -> Zone {
+        let sample = Arc::new(Sample::new(vec![vec![0.0; 16]], 48000.0));
+
+        Zone {
+            keys,
+            velocities,
+            ..Zone::new(sample, Note::C4)
+        }
+    }
+
+    fn velocity(value: u8) -> Velocity {
+        // UNWRAP SAFETY: Tests only pass values in 0..=127.
+        Velocity::try_from(value).unwrap()
+    }
+
+    #[test]
+    fn find_matches_key_and_velocity() {
+        let keymap = Keymap::new(vec![
+            zone(Note::C2..=Note::B3, 0..=127),
+            zone(Note::C4..=Note::B5, 0..=63),
+            zone(Note::C4..=Note::B5, 64..=127),
+        ]);
+
+        assert_eq!(keymap.find(Note::E2, velocity(100)), Some(0));
+        assert_eq!(keymap.find(Note::E4, velocity(20)), Some(1));
+        assert_eq!(keymap.find(Note::E4, velocity(100)), Some(2));
+        assert_eq!(keymap.find(Note::C7, velocity(100)), None);
+    }
+
+    #[test]
+    fn first_overlapping_zone_wins() {
+        let keymap = Keymap::new(vec![
+            zone(Note::C4..=Note::C5, 0..=127),
+            zone(Note::C3..=Note::C6, 0..=127),
+        ]);
+
+        assert_eq!(keymap.find(Note::G4, velocity(80)), Some(0));
+    }
+
+    #[test]
+    fn loop_range_requires_forward_and_valid_points() {
+        let mut zone = zone(Note::C4..=Note::C4, 0..=127);
+        assert_eq!(zone.loop_range(), None);
+
+        zone.loop_mode = LoopMode::Forward;
+        zone.loop_points = 4..12;
+        assert_eq!(zone.loop_range(), Some(&(4..12)));
+
+        zone.loop_points = 4..99;
+        assert_eq!(zone.loop_range(), None);
+    }
+
+    #[test]
+    fn load_shares_samples_between_zones() {
+        let path = std::env::temp_dir().join("motif-sampler-shared.wav");
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 48000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        writer.write_sample(0i16).unwrap();
+        writer.finalize().unwrap();
+
+        let spec = |keys| ZoneSpec {
+            path: path.clone(),
+            root: Note::C4,
+            keys,
+            velocities: 0..=127,
+            tune: 0.0,
+            gain: 1.0,
+            loop_mode: LoopMode::Off,
+            loop_points: None,
+        };
+
+        let keymap = Keymap::load(&[spec(Note::C3..=Note::B3), spec(Note::C4..=Note::B4)]).unwrap();
+
+        assert!(Arc::ptr_eq(
+            &keymap.zones[0].sample,
+            &keymap.zones[1].sample
+        ));
+    }
+}
@@ -0,0 +1,13 @@
+#[derive(Debug, thiserror::Error)]
+pub enum WavetableError {
+    #[error("Wavetable file error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid WAV file: {0}")]
+    Wav(#[from] hound::Error),
+    #[error("Frame size {0} must be a power of two of at least 4")]
+    FrameSize(usize),
+    #[error("{samples} samples don't divide into frames of {frame_size}")]
+    Length { samples: usize, frame_size: usize },
+    #[error("Buffer is full")]
+    BufferFull,
+}
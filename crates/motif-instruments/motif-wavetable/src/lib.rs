@@ -0,0 +1,4 @@
+pub mod error;
+pub mod synth;
+pub mod table;
+pub mod voice;
@@ -0,0 +1,375 @@
+use std::ops::Range;
+
+use motif_core::tuning::Tuning;
+use motif_engine::{
+    buffer::AudioBuffer,
+    events::{Event, MidiEvent},
+    lfo::Lfo,
+    node::AudioNode,
+    steal::{StealPolicy, StealVoice},
+    swap::{self, SwapReceiver, SwapSender},
+};
+
+use crate::{
+    error::WavetableError,
+    table::Table,
+    voice::{Strike, Voice},
+};
+
+/// Scales the summed voices. Tables are normalized to full scale, so this
+/// leaves room for a chord.
+const GAIN: f64 = 0.2;
+
+/// Voice count used by `Wavetable::new()`.
+pub const DEFAULT_POLYPHONY: usize = 8;
+
+/// Tables that can be queued for the audio thread at once.
+const TABLE_QUEUE: usize = 4;
+
+/// Polyphonic wavetable synthesizer. Implements AudioNode — feed it
+/// NoteOn/NoteOff events via evaluate_node() and it produces audio.
+///
+/// The scan position picks where in the table each voice reads, moved per
+/// voice by the scan envelope and for all voices by the LFO. Tables are
+/// built off the audio thread and handed over through `WavetableHandle`;
+/// sounding voices carry on through the new table.
+pub struct Wavetable {
+    /// One per sounding note, sized by `with_polyphony`.
+    pub voices: Vec<Voice>,
+    pub table: Box<Table>,
+    pub steal_policy: StealPolicy,
+    /// Scan position (0.0 = first frame, 1.0 = last) before modulation.
+    pub position: f64,
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+    pub scan_attack: f32,
+    pub scan_decay: f32,
+    pub scan_sustain: f32,
+    pub scan_release: f32,
+    /// How far the scan envelope moves the position at full level.
+    pub scan_envelope_amount: f64,
+    pub lfo: Lfo,
+    /// How far the LFO moves the position at its peaks.
+    pub lfo_amount: f64,
+    /// Note → frequency table. Unmapped notes are ignored.
+    pub tuning: Tuning,
+    pub next_age: u64,
+    tables: SwapReceiver<Table>,
+}
+
+/// UI-side handle for swapping a Wavetable's table while it plays.
+pub struct WavetableHandle {
+    tables: SwapSender<Table>,
+}
+
+impl Wavetable {
+    /// Build a synth playing `Table::basic()` and the handle that feeds it tables.
+    pub fn new() -> (Self, WavetableHandle) {
+        Self::with_polyphony(DEFAULT_POLYPHONY)
+    }
+
+    /// Build with a fixed voice count. Panics if `polyphony` is zero.
+    pub fn with_polyphony(polyphony: usize) -> (Self, WavetableHandle) {
+        assert!(polyphony > 0, "Wavetable needs at least one voice");
+
+        let (sender, receiver) = swap::channel(TABLE_QUEUE);
+
+        let synth = Self {
+            voices: (0..polyphony).map(|_| Voice::new()).collect(),
+            table: Box::new(Table::basic()),
+            steal_policy: StealPolicy::Oldest,
+            position: 0.0,
+            attack: 0.01,
+            decay: 0.1,
+            sustain: 0.7,
+            release: 0.15,
+            scan_attack: 0.0,
+            scan_decay: 0.5,
+            scan_sustain: 0.0,
+            scan_release: 0.15,
+            scan_envelope_amount: 0.0,
+            lfo: Lfo::default(),
+            lfo_amount: 0.0,
+            tuning: Tuning::default(),
+            next_age: 0,
+            tables: receiver,
+        };
+
+        (synth, WavetableHandle { tables: sender })
+    }
+}
+
+impl WavetableHandle {
+    /// Queue a table for the audio thread. It takes effect at the next
+    /// render.
+    pub fn send_table(&mut self, table: Table) -> Result<(), WavetableError> {
+        self.tables
+            .send(table)
+            .map_err(|_| WavetableError::BufferFull)
+    }
+
+    /// Drop tables the audio thread has replaced. Call regularly from a
+    /// non-audio thread. Returns how many were freed.
+    pub fn collect_garbage(&mut self) -> usize {
+        self.tables.collect_garbage()
+    }
+}
+
+impl AudioNode for Wavetable {
+    fn render(
+        &mut self,
+        _inputs: &[&AudioBuffer],
+        output: &mut AudioBuffer,
+        frame_range: Range<usize>,
+        sample_rate: f64,
+    ) {
+        self.tables.receive(&mut self.table);
+
+        let (left, right) = output.two_channels_mut(0, 1);
+
+        for frame in frame_range {
+            let position = self.position + self.lfo.tick(sample_rate) * self.lfo_amount;
+            let mut sum = 0.0;
+
+            for voice in &mut self.voices {
+                if voice.is_active() {
+                    sum += voice.render(
+                        &self.table,
+                        position,
+                        self.scan_envelope_amount,
+                        sample_rate,
+                    );
+                }
+            }
+
+            let sample = (sum * GAIN) as f32;
+            left[frame] = sample;
+            right[frame] = sample;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::Midi(event) => match event {
+                MidiEvent::NoteOn { note, velocity } => {
+                    let Some(frequency) = self.tuning.frequency(*note) else {
+                        return;
+                    };
+
+                    let voice_index = self.steal_policy.pick(&self.voices, *note);
+
+                    let strike = Strike {
+                        frequency,
+                        gain: u8::from(*velocity) as f64 / 127.0,
+                        adsr: [self.attack, self.decay, self.sustain, self.release],
+                        scan_adsr: [
+                            self.scan_attack,
+                            self.scan_decay,
+                            self.scan_sustain,
+                            self.scan_release,
+                        ],
+                    };
+
+                    self.voices[voice_index].trigger(*note, strike, self.next_age);
+
+                    self.next_age += 1;
+                }
+                MidiEvent::NoteOff { note } => {
+                    for voice in &mut self.voices {
+                        if voice.note == Some(*note) && voice.held {
+                            voice.held = false;
+                            voice.release();
+                        }
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    fn reset(&mut self) {
+        for voice in &mut self.voices {
+            voice.reset();
+        }
+
+        self.lfo.reset();
+        self.next_age = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use motif_engine::{
+        graph::evaluate_node,
+        test_util::{SAMPLE_RATE, has_signal, is_silent, note_off, note_on},
+    };
+    use wmidi::Note;
+
+    fn make_synth() -> Wavetable {
+        Wavetable::new().0
+    }
+
+    /// Render a single held A4 for 2048 samples.
+    fn render_a4(synth: &mut Wavetable) -> AudioBuffer {
+        let mut output = AudioBuffer::new(2, 2048);
+        output.prepare(2048);
+
+        let events = [note_on(0, Note::A4)];
+        evaluate_node(synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        output
+    }
+
+    #[test]
+    fn note_on_produces_output() {
+        let mut synth = make_synth();
+        let output = render_a4(&mut synth);
+
+        assert!(has_signal(&output, 0..2048));
+    }
+
+    #[test]
+    fn no_events_is_silent() {
+        let mut synth = make_synth();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        evaluate_node(&mut synth, &[], &mut output, &[], SAMPLE_RATE);
+
+        assert!(is_silent(&output, 0..256));
+    }
+
+    #[test]
+    fn note_off_releases_to_silence() {
+        let mut synth = make_synth();
+        let mut output = AudioBuffer::new(2, 1024);
+
+        output.prepare(512);
+        let events = [note_on(0, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        output.prepare(1024);
+        let events = [note_off(0, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        for _ in 0..10 {
+            output.prepare(1024);
+            evaluate_node(&mut synth, &[], &mut output, &[], SAMPLE_RATE);
+        }
+
+        assert!(is_silent(&output, 0..1024));
+    }
+
+    #[test]
+    fn polyphony_three_notes() {
+        let mut synth = make_synth();
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [
+            note_on(0, Note::C4),
+            note_on(0, Note::E4),
+            note_on(0, Note::G4),
+        ];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        let active = synth.voices.iter().filter(|v| v.is_active()).count();
+        assert_eq!(active, 3);
+    }
+
+    #[test]
+    fn steal_keeps_the_old_note_envelope() {
+        let (mut synth, _handle) = Wavetable::with_polyphony(1);
+        synth.release = 2.0;
+        let mut output = AudioBuffer::new(2, 512);
+
+        output.prepare(512);
+        let events = [note_on(0, Note::C4), note_off(256, Note::C4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        // A near-instant release must only apply to the incoming note.
+        synth.release = 0.001;
+        output.prepare(64);
+        let events = [note_on(0, Note::E4)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        let envelope = &synth.voices[0].envelope;
+        assert!(envelope.is_releasing());
+        assert!(envelope.level() > 0.5, "{}", envelope.level());
+    }
+
+    #[test]
+    fn scan_position_changes_timbre() {
+        let mut sine = make_synth();
+        let mut square = make_synth();
+        square.position = 1.0;
+
+        let sine = render_a4(&mut sine);
+        let square = render_a4(&mut square);
+
+        assert_ne!(sine.channel(0), square.channel(0));
+    }
+
+    #[test]
+    fn scan_envelope_moves_position_over_time() {
+        let mut still = make_synth();
+        let mut swept = make_synth();
+        swept.scan_envelope_amount = 1.0;
+
+        let still = render_a4(&mut still);
+        let swept = render_a4(&mut swept);
+
+        // Both start on frame 0; the envelope attack is instant, so the
+        // swept voice starts at the far end and decays back.
+        assert_ne!(still.channel(0)[..64], swept.channel(0)[..64]);
+    }
+
+    #[test]
+    fn lfo_modulates_position() {
+        let mut still = make_synth();
+        let mut wobble = make_synth();
+        wobble.lfo.rate = 20.0;
+        wobble.lfo_amount = 0.5;
+        wobble.position = 0.5;
+        still.position = 0.5;
+
+        let still = render_a4(&mut still);
+        let wobble = render_a4(&mut wobble);
+
+        assert_ne!(still.channel(0), wobble.channel(0));
+    }
+
+    #[test]
+    fn table_swap_through_handle() {
+        let (mut synth, mut handle) = Wavetable::new();
+        let silent = Table::from_samples(&[0.0; 64], 64).unwrap();
+
+        handle.send_table(silent).unwrap();
+        let output = render_a4(&mut synth);
+
+        assert!(is_silent(&output, 0..2048));
+        assert_eq!(synth.table.frame_size(), 64);
+        assert_eq!(handle.collect_garbage(), 1);
+    }
+
+    #[test]
+    fn high_notes_read_band_limited_levels() {
+        let mut synth = make_synth();
+        synth.position = 0.66;
+        let mut output = AudioBuffer::new(2, 256);
+        output.prepare(256);
+
+        let events = [note_on(0, Note::C8)];
+        evaluate_node(&mut synth, &[], &mut output, &events, SAMPLE_RATE);
+
+        let level = synth
+            .table
+            .level_for(synth.voices[0].frequency, SAMPLE_RATE);
+        assert!(synth.table.harmonics(level) as f64 * synth.voices[0].frequency <= 24000.0);
+        assert!(has_signal(&output, 0..256));
+    }
+}
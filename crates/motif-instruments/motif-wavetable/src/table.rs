@@ -0,0 +1,382 @@
+use std::{f64::consts::TAU, path::Path};
+
+use realfft::{RealFftPlanner, num_complex::Complex};
+
+use crate::error::WavetableError;
+
+/// Frame length used for multi-frame files when none is given.
+pub const DEFAULT_FRAME_SIZE: usize = 2048;
+
+/// A band-limited wavetable: one or more single-cycle frames, each stored
+/// as a mip chain with one level per octave. Level 0 keeps every harmonic
+/// the frame can hold; each level above keeps half as many, so a note can
+/// always read a level with nothing above Nyquist.
+///
+/// Built off the audio thread (FFT planning and mipmaps allocate). DC is
+/// removed from every frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    frame_size: usize,
+    frames: usize,
+    /// `levels[level][frame * frame_size + index]`.
+    levels: Vec<Vec<f32>>,
+}
+
+impl Table {
+    /// Split `samples` into frames of `frame_size` and build the mipmaps.
+    pub fn from_samples(samples: &[f32], frame_size: usize) -> Result<Self, WavetableError> {
+        if frame_size < 4 || !frame_size.is_power_of_two() {
+            return Err(WavetableError::FrameSize(frame_size));
+        }
+
+        if samples.is_empty() || !samples.len().is_multiple_of(frame_size) {
+            return Err(WavetableError::Length {
+                samples: samples.len(),
+                frame_size,
+            });
+        }
+
+        let frames = samples.len() / frame_size;
+        let level_count = frame_size.trailing_zeros() as usize;
+        let mut levels = vec![vec![0.0; samples.len()]; level_count];
+
+        let mut planner = RealFftPlanner::<f64>::new();
+        let forward = planner.plan_fft_forward(frame_size);
+        let inverse = planner.plan_fft_inverse(frame_size);
+
+        let mut input = forward.make_input_vec();
+        let mut spectrum = forward.make_output_vec();
+        let mut limited = inverse.make_input_vec();
+        let mut output = inverse.make_output_vec();
+
+        for (frame, chunk) in samples.chunks_exact(frame_size).enumerate() {
+            for (slot, &sample) in input.iter_mut().zip(chunk) {
+                *slot = sample as f64;
+            }
+
+            // UNWRAP SAFETY: Buffers come from the plan, so lengths match.
+            forward.process(&mut input, &mut spectrum).unwrap();
+
+            for (level, data) in levels.iter_mut().enumerate() {
+                let harmonics = (frame_size / 2) >> level;
+
+                for (bin, slot) in limited.iter_mut().enumerate() {
+                    *slot = if bin == 0 || bin > harmonics {
+                        Complex::default()
+                    } else {
+                        spectrum[bin]
+                    };
+                }
+
+                // The inverse real FFT needs a purely real Nyquist bin.
+                if let Some(nyquist) = limited.last_mut() {
+                    nyquist.im = 0.0;
+                }
+
+                // UNWRAP SAFETY: Buffers come from the plan, and DC and
+                // Nyquist are purely real.
+                inverse.process(&mut limited, &mut output).unwrap();
+
+                let start = frame * frame_size;
+                for (slot, &sample) in data[start..start + frame_size].iter_mut().zip(&output) {
+                    *slot = (sample / frame_size as f64) as f32;
+                }
+            }
+        }
+
+        Ok(Self {
+            frame_size,
+            frames,
+            levels,
+        })
+    }
+
+    /// Load a WAV wavetable, mixing channels to mono. A file shorter than
+    /// `frame_size` is one single-cycle frame, resampled up to the next
+    /// power of two if needed (AKWF cycles are 600 samples); longer files
+    /// are split into frames of `frame_size`. Blocking I/O — never call
+    /// from the audio thread.
+    pub fn load(path: impl AsRef<Path>, frame_size: usize) -> Result<Self, WavetableError> {
+        let reader = hound::WavReader::open(path)?;
+        let spec = reader.spec();
+        let channels = spec.channels.max(1) as usize;
+
+        let interleaved: Vec<f32> = match spec.sample_format {
+            hound::SampleFormat::Float => reader.into_samples::<f32>().collect::<Result<_, _>>()?,
+            hound::SampleFormat::Int => {
+                let scale = 1.0 / (1u64 << (spec.bits_per_sample.clamp(1, 32) - 1)) as f32;
+
+                reader
+                    .into_samples::<i32>()
+                    .map(|s| s.map(|s| s as f32 * scale))
+                    .collect::<Result<_, _>>()?
+            }
+        };
+
+        let mono: Vec<f32> = interleaved
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect();
+
+        if !mono.is_empty() && mono.len() < frame_size {
+            let cycle = resample_cycle(&mono);
+            return Self::from_samples(&cycle, cycle.len());
+        }
+
+        Self::from_samples(&mono, frame_size)
+    }
+
+    /// Four frames morphing sine → triangle → saw → square.
+    pub fn basic() -> Self {
+        let size = DEFAULT_FRAME_SIZE;
+        let shapes: [fn(f64) -> f64; 4] = [
+            |p| (TAU * p).sin(),
+            |p| 4.0 * ((p + 0.75).fract() - 0.5).abs() - 1.0,
+            |p| 2.0 * (p + 0.5).fract() - 1.0,
+            |p| if p < 0.5 { 1.0 } else { -1.0 },
+        ];
+
+        let samples: Vec<f32> = shapes
+            .iter()
+            .flat_map(|shape| (0..size).map(move |i| shape(i as f64 / size as f64) as f32))
+            .collect();
+
+        // UNWRAP SAFETY: The size is a power of two and samples fill whole frames.
+        Self::from_samples(&samples, size).unwrap()
+    }
+
+    pub fn frame_size(&self) -> usize {
+        self.frame_size
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    pub fn levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Harmonics kept at `level`.
+    pub fn harmonics(&self, level: usize) -> usize {
+        (self.frame_size / 2) >> level
+    }
+
+    /// Lowest mip level that plays `frequency` without harmonics above Nyquist.
+    pub fn level_for(&self, frequency: f64, sample_rate: f64) -> usize {
+        let allowed = (sample_rate / 2.0 / frequency.max(1e-3)).floor().max(1.0);
+        let needed = ((self.frame_size / 2) as f64 / allowed).log2().ceil();
+
+        (needed.max(0.0) as usize).min(self.levels.len() - 1)
+    }
+
+    /// Value at `phase` (0.0..1.0 through the cycle) and scan `position`
+    /// (0.0 = first frame, 1.0 = last), crossfading between neighbouring
+    /// frames. Linear interpolation within a frame.
+    pub fn read(&self, level: usize, position: f64, phase: f64) -> f64 {
+        let data = &self.levels[level.min(self.levels.len() - 1)];
+        let size = self.frame_size;
+
+        let scan = position.clamp(0.0, 1.0) * (self.frames - 1) as f64;
+        let frame_a = scan.floor() as usize;
+        let frame_b = (frame_a + 1).min(self.frames - 1);
+        let blend = scan - frame_a as f64;
+
+        let index = phase.rem_euclid(1.0) * size as f64;
+        let i0 = (index.floor() as usize).min(size - 1);
+        let i1 = (i0 + 1) % size;
+        let fraction = index - i0 as f64;
+
+        let sample = |frame: usize| {
+            let a = data[frame * size + i0] as f64;
+            let b = data[frame * size + i1] as f64;
+
+            a + (b - a) * fraction
+        };
+
+        let a = sample(frame_a);
+
+        if blend == 0.0 {
+            return a;
+        }
+
+        a + (sample(frame_b) - a) * blend
+    }
+}
+
+/// Stretch one cycle to the next power of two of at least 4 samples by
+/// zero-padding its spectrum, so the harmonics are unchanged. The source's
+/// Nyquist bin is dropped; it has no well-defined phase.
+fn resample_cycle(cycle: &[f32]) -> Vec<f32> {
+    let size = cycle.len().next_power_of_two().max(4);
+
+    if size == cycle.len() {
+        return cycle.to_vec();
+    }
+
+    let mut planner = RealFftPlanner::<f64>::new();
+    let forward = planner.plan_fft_forward(cycle.len());
+    let inverse = planner.plan_fft_inverse(size);
+
+    let mut input: Vec<f64> = cycle.iter().map(|&sample| sample as f64).collect();
+    let mut spectrum = forward.make_output_vec();
+    let mut padded = inverse.make_input_vec();
+    let mut output = inverse.make_output_vec();
+
+    // UNWRAP SAFETY: Buffers come from the plan, so lengths match.
+    forward.process(&mut input, &mut spectrum).unwrap();
+
+    let harmonics = (cycle.len() - 1) / 2;
+    padded[..=harmonics].copy_from_slice(&spectrum[..=harmonics]);
+    padded[0].im = 0.0;
+
+    // UNWRAP SAFETY: Buffers come from the plan, and DC and Nyquist are
+    // purely real.
+    inverse.process(&mut padded, &mut output).unwrap();
+
+    output
+        .iter()
+        .map(|&sample| (sample / cycle.len() as f64) as f32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saw(size: usize) -> Vec<f32> {
+        (0..size)
+            .map(|i| 2.0 * i as f32 / size as f32 - 1.0)
+            .collect()
+    }
+
+    #[test]
+    fn rejects_bad_frame_sizes() {
+        assert!(matches!(
+            Table::from_samples(&[0.0; 300], 300),
+            Err(WavetableError::FrameSize(300))
+        ));
+        assert!(matches!(
+            Table::from_samples(&[0.0; 300], 256),
+            Err(WavetableError::Length { .. })
+        ));
+    }
+
+    #[test]
+    fn one_level_per_octave() {
+        let table = Table::from_samples(&saw(256), 256).unwrap();
+
+        assert_eq!(table.levels(), 8);
+        assert_eq!(table.harmonics(0), 128);
+        assert_eq!(table.harmonics(7), 1);
+    }
+
+    #[test]
+    fn top_level_is_the_fundamental_only() {
+        let table = Table::from_samples(&saw(256), 256).unwrap();
+        let top = table.levels() - 1;
+
+        // A saw's fundamental is a sine with amplitude 2/π, inverted. The
+        // sampled ramp lags the ideal one by half a sample.
+        for i in 0..256 {
+            let phase = i as f64 / 256.0;
+            let expected = -2.0 / std::f64::consts::PI * (TAU * (phase + 0.5 / 256.0)).sin();
+
+            assert!((table.read(top, 0.0, phase) - expected).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn dc_is_removed() {
+        let offset: Vec<f32> = saw(64).iter().map(|s| s + 0.5).collect();
+        let table = Table::from_samples(&offset, 64).unwrap();
+
+        let mean = (0..64)
+            .map(|i| table.read(0, 0.0, i as f64 / 64.0))
+            .sum::<f64>()
+            / 64.0;
+
+        assert!(mean.abs() < 1e-6);
+    }
+
+    #[test]
+    fn level_for_drops_harmonics_as_pitch_rises() {
+        let table = Table::basic();
+
+        // 20 Hz fits every harmonic of a 2048-sample frame at 48 kHz.
+        assert_eq!(table.level_for(20.0, 48000.0), 0);
+
+        let low = table.level_for(110.0, 48000.0);
+        let high = table.level_for(1760.0, 48000.0);
+        assert_eq!(high - low, 4);
+
+        for frequency in [110.0, 440.0, 3000.0, 12000.0] {
+            let level = table.level_for(frequency, 48000.0);
+            assert!(table.harmonics(level) as f64 * frequency <= 24000.0);
+        }
+    }
+
+    #[test]
+    fn scan_position_crossfades_frames() {
+        let mut samples = vec![0.0; 2 * 64];
+        samples[..64].copy_from_slice(&saw(64));
+        let table = Table::from_samples(&samples, 64).unwrap();
+
+        let first = table.read(0, 0.0, 0.3);
+        let middle = table.read(0, 0.5, 0.3);
+
+        assert_eq!(table.read(0, 1.0, 0.3), 0.0);
+        assert!((middle - first / 2.0).abs() < 1e-6);
+    }
+
+    fn write_wav(name: &str, samples: &[f32]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 48000,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for &sample in samples {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        path
+    }
+
+    #[test]
+    fn load_splits_multi_frame_wav() {
+        let path = write_wav("motif-wavetable-frames.wav", &saw(64).repeat(3));
+
+        assert_eq!(Table::load(&path, 64).unwrap().frames(), 3);
+    }
+
+    #[test]
+    fn load_short_file_as_single_cycle() {
+        let path = write_wav("motif-wavetable-cycle.wav", &saw(128));
+        let table = Table::load(&path, DEFAULT_FRAME_SIZE).unwrap();
+
+        assert_eq!(table.frames(), 1);
+        assert_eq!(table.frame_size(), 128);
+    }
+
+    #[test]
+    fn load_resamples_odd_length_cycle() {
+        let cycle: Vec<f32> = (0..600)
+            .map(|i| (TAU * i as f64 / 600.0).sin() as f32)
+            .collect();
+        let path = write_wav("motif-wavetable-akwf.wav", &cycle);
+        let table = Table::load(&path, DEFAULT_FRAME_SIZE).unwrap();
+
+        assert_eq!(table.frames(), 1);
+        assert_eq!(table.frame_size(), 1024);
+
+        for i in 0..64 {
+            let phase = i as f64 / 64.0;
+            assert!((table.read(0, 0.0, phase) - (TAU * phase).sin()).abs() < 1e-3);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use motif_engine::{
+    envelope::Envelope,
+    steal::{StealFade, StealVoice},
+};
+use wmidi::Note;
+
+use crate::table::Table;
+
+/// Single voice of polyphony. Owns a phase accumulator, an amplitude
+/// envelope and a scan envelope. Wavetable allocates a fixed pool of these;
+/// idle voices are skipped during render.
+#[derive(Debug, Default)]
+pub struct Voice {
+    pub phase: f64,
+    pub frequency: f64,
+    /// Amplitude from velocity.
+    pub gain: f64,
+    pub envelope: Envelope,
+    /// Modulates the scan position. Triggered and released with `envelope`.
+    pub scan_envelope: Envelope,
+    pub note: Option<Note>,
+    // NoteOn count when this note started, for stealing.
+    pub age: u64,
+    /// Key is physically down.
+    pub held: bool,
+    steal: StealFade<Strike>,
+}
+
+/// Per-note values fixed at NoteOn, after tuning and velocity.
+#[derive(Debug, Clone, Copy)]
+pub struct Strike {
+    pub frequency: f64,
+    pub gain: f64,
+    /// Attack, decay, sustain and release of each envelope. Set when the
+    /// note starts, so a note being stolen keeps its own.
+    pub adsr: [f32; 4],
+    pub scan_adsr: [f32; 4],
+}
+
+impl Voice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render one mono sample. `position` is the scan position before the
+    /// scan envelope; `scan_amount` is how far the envelope moves it.
+    pub fn render(
+        &mut self,
+        table: &Table,
+        position: f64,
+        scan_amount: f64,
+        sample_rate: f64,
+    ) -> f64 {
+        let amplitude = self.envelope.tick(sample_rate) * self.gain * self.steal.gain();
+        let scan = position + self.scan_envelope.tick(sample_rate) * scan_amount;
+
+        let level = table.level_for(self.frequency, sample_rate);
+        let sample = table.read(level, scan, self.phase);
+
+        self.phase = (self.phase + self.frequency / sample_rate).fract();
+
+        self.tick_steal(sample_rate);
+
+        sample * amplitude
+    }
+
+    /// Start a note, retriggering the scan envelope with the amplitude one.
+    pub fn trigger(&mut self, note: Note, strike: Strike, age: u64) {
+        self.note = Some(note);
+        self.age = age;
+        self.held = true;
+        self.play(strike);
+    }
+
+    pub fn reset(&mut self) {
+        self.note = None;
+        self.held = false;
+        self.steal.reset();
+        self.envelope.reset();
+        self.scan_envelope.reset();
+    }
+}
+
+impl StealVoice for Voice {
+    type Strike = Strike;
+
+    fn steal_fade(&self) -> &StealFade<Strike> {
+        &self.steal
+    }
+
+    fn steal_fade_mut(&mut self) -> &mut StealFade<Strike> {
+        &mut self.steal
+    }
+
+    fn age(&self) -> u64 {
+        self.age
+    }
+
+    fn note(&self) -> Option<Note> {
+        self.note
+    }
+
+    fn is_sounding(&self) -> bool {
+        !self.envelope.is_idle()
+    }
+
+    fn is_sounding_released(&self) -> bool {
+        self.envelope.is_releasing()
+    }
+
+    /// Envelope times velocity gain, before the waveform.
+    fn sounding_level(&self) -> f64 {
+        self.envelope.level() * self.gain
+    }
+
+    fn start(&mut self, strike: Strike) {
+        self.phase = 0.0;
+        self.frequency = strike.frequency;
+        self.gain = strike.gain;
+
+        let [attack, decay, sustain, release] = strike.adsr;
+        self.envelope.adsr(attack, decay, sustain, release);
+        let [attack, decay, sustain, release] = strike.scan_adsr;
+        self.scan_envelope.adsr(attack, decay, sustain, release);
+
+        self.envelope.trigger();
+        self.scan_envelope.trigger();
+    }
+
+    fn release_sounding(&mut self) {
+        self.envelope.release();
+        self.scan_envelope.release();
+    }
+}
@@ -1,106 +1,1050 @@
 use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
-use iced::keyboard::{self, Key, Modifiers, key::Named};
+use iced::keyboard::{self, Key, Modifiers};
 use iced::widget::column;
-use iced::{Element, Fill, Subscription, Task, Theme};
-use motif_core::id::TrackId;
+use iced::{Element, Fill, Size, Subscription, Task, Theme};
+use motif_core::edit::{Edit, UndoStack};
+use motif_core::id::{NoteId, TrackId};
+use motif_core::note::NoteEvent;
+use motif_core::project::{Clip, Project};
+use motif_core::tick::Tick;
 use motif_engine::control::PlaybackControl;
 use motif_engine::events::MidiEvent;
-use wmidi::{Note, Velocity};
+use serde::Deserialize;
+use wmidi::{Note, ProgramNumber, Velocity};
 
 use crate::canvas::PianoRollGrid;
-use crate::status_bar;
+use crate::command::{Invocation, Registry};
+use crate::command_line::{CommandLine, History, Outcome};
+use crate::commands;
+use crate::editor::{Cursor, Motion, Operator, Range, Selection, SelectionKind, TextObject};
+use crate::error::{CommandError, KeymapError, RegisterError};
+use crate::keymap::{self, Action, Chord, Keymap, Lookup};
+use crate::macros::{Macros, Step};
+use crate::options::Options;
+use crate::paths;
+use crate::registers::{self, Registers, UNNAMED};
+use crate::status_bar::{self, Status, StatusMessage};
+use crate::viewport::{self, Viewport};
+
+/// Largest count prefix, so `99999999l` can't overflow tick math.
+const MAX_COUNT: u32 = 9999;
+
+/// Commands that drive macros and `.` themselves, so are never part of the
+/// change `.` repeats.
+const UNREPEATABLE: &[&str] = &["record", "repeat", "replay"];
+
+/// Shown when the active track is missing.
+static EMPTY_CLIP: Clip = Clip {
+    notes: std::collections::BTreeMap::new(),
+};
 
 pub struct App {
     mode: Mode,
     grid: PianoRollGrid,
     control: PlaybackControl,
-    /// Tracks currently-held notes so key repeat does not flood NoteOn
-    /// and mode exits can reliably silence everything.
-    active_notes: HashSet<Note>,
+    /// Held piano keys and the note each is sounding, so key repeat does
+    /// not flood NoteOn, a key's NoteOff matches its NoteOn after the
+    /// octave changes, and mode exits can reliably silence everything.
+    active_notes: HashMap<char, Note>,
+    /// Semitones the piano layout is shifted by, from octave and semitone
+    /// commands.
+    transpose: i32,
+    /// Names of the instrument's preset bank, indexed by program number.
+    presets: Vec<String>,
+    program: usize,
+    /// Velocity sent with Play mode NoteOns.
+    velocity: Velocity,
+    keymap: Keymap,
+    /// Keys typed so far towards a multi-key binding.
+    pending: Vec<Chord>,
+    /// Shared so a handler can dispatch further commands while it holds
+    /// `&mut App`.
+    commands: Rc<Registry<App>>,
+    project: Project,
+    /// Where `:w` saves without a path.
+    project_path: Option<PathBuf>,
+    /// Track that edits and commands apply to.
+    track: TrackId,
+    cursor: Cursor,
+    /// Where Visual mode started; the other corner is the cursor.
+    anchor: Cursor,
+    /// Count typed before a command, as in `4l`.
+    count: Option<u32>,
+    /// Operator waiting for a motion or text object, with its count.
+    operator: Option<(Operator, u32)>,
+    undo: UndoStack,
+    registers: Registers,
+    /// Register named with `"x` for the next delete, yank or paste.
+    register: Option<char>,
+    /// Command waiting for the next key to name its register, as after
+    /// `"`, `q` or `@`.
+    awaiting_register: Option<&'static str>,
+    macros: Macros,
+    /// Nesting of macro replays, so a macro calling itself stops.
+    replaying: usize,
+    /// Nesting of sourced scripts, so a script sourcing itself stops.
+    sourcing: usize,
+    /// Steps of the change in progress, from the last time the editor was
+    /// idle.
+    change: Vec<Step>,
+    /// Edits applied so far, so a step can tell whether it changed notes.
+    edit_count: u64,
+    /// `edit_count` when the change in progress began.
+    change_edits: u64,
+    /// What `.` repeats.
+    last_change: Vec<Step>,
+    /// Notes of the chord last written in Step mode, for ties and for
+    /// notes added to it while keys are held.
+    step_chord: Vec<NoteId>,
+    /// Size of the piano roll's note area, for keeping the cursor in view.
+    roll_size: Size,
+    options: Options,
+    /// Open while a `:` command is being typed.
+    command_line: Option<CommandLine>,
+    history: History,
+    /// Shown in the status bar until the next key press.
+    message: Option<StatusMessage>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 pub enum Mode {
     Normal,
     Play,
+    /// Piano keys write notes at the cursor, one step at a time.
+    Step,
+    /// After an operator key, waiting for a motion or text object.
+    Operator,
+    /// Selecting a time range across every pitch.
+    Visual,
+    /// Selecting a rectangle of time and pitch.
+    VisualBlock,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 6] = [
+        Mode::Normal,
+        Mode::Play,
+        Mode::Step,
+        Mode::Operator,
+        Mode::Visual,
+        Mode::VisualBlock,
+    ];
+
+    /// Lowercase name, as used in commands like `:map play`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Normal => "normal",
+            Mode::Play => "play",
+            Mode::Step => "step",
+            Mode::Operator => "operator",
+            Mode::Visual => "visual",
+            Mode::VisualBlock => "visual-block",
+        }
+    }
+
+    /// Whether digits typed here build a count.
+    fn takes_count(&self) -> bool {
+        matches!(
+            self,
+            Mode::Normal | Mode::Operator | Mode::Visual | Mode::VisualBlock
+        )
+    }
+
+    fn selection_kind(&self) -> Option<SelectionKind> {
+        match self {
+            Mode::Visual => Some(SelectionKind::Time),
+            Mode::VisualBlock => Some(SelectionKind::Block),
+            _ => None,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Mode> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     KeyPressed(Key, Modifiers),
     KeyReleased(Key, Modifiers),
+    /// The piano roll's note area changed size.
+    RollResized(Size),
     Tick,
 }
 
 impl App {
-    fn new(control: PlaybackControl) -> (Self, Task<Message>) {
-        (
-            Self {
-                mode: Mode::Normal,
-                grid: PianoRollGrid::new(),
-                control,
-                active_notes: HashSet::new(),
-            },
-            Task::none(),
-        )
+    fn new(control: PlaybackControl, presets: Vec<String>) -> (Self, Task<Message>) {
+        // The engine hosts a single Pulse synth, which the first track drives.
+        let mut project = Project::new();
+        let track = project.add_track("pulse");
+
+        let mut app = Self {
+            mode: Mode::Normal,
+            grid: PianoRollGrid::new(),
+            control,
+            active_notes: HashMap::new(),
+            transpose: 0,
+            presets,
+            program: 0,
+            velocity: Velocity::MAX,
+            commands: Rc::new(commands::registry()),
+            keymap: Keymap::builtin(),
+            pending: Vec::new(),
+            project,
+            project_path: None,
+            track,
+            cursor: Cursor::default(),
+            anchor: Cursor::default(),
+            count: None,
+            operator: None,
+            undo: UndoStack::new(),
+            registers: Registers::new(),
+            register: None,
+            awaiting_register: None,
+            macros: Macros::new(),
+            replaying: 0,
+            sourcing: 0,
+            change: Vec::new(),
+            edit_count: 0,
+            change_edits: 0,
+            last_change: Vec::new(),
+            step_chord: Vec::new(),
+            roll_size: Size::ZERO,
+            options: Options::new(),
+            command_line: None,
+            history: History::new(),
+            message: None,
+        };
+
+        app.load_user_files();
+
+        (app, Task::none())
+    }
+
+    /// Pick up the user's keymap, command history and registers. Problems
+    /// are shown in the status bar and the defaults kept.
+    fn load_user_files(&mut self) {
+        if let Some(path) = paths::keymap_file().filter(|path| path.exists()) {
+            match Self::read_keymap(&path) {
+                Ok(keymap) => self.keymap = keymap,
+                Err(error) => self.show_error(format!("{}: {error}", path.display())),
+            }
+        }
+
+        if let Some(path) = paths::history_file() {
+            match History::load(&path) {
+                Ok(history) => self.history = history,
+                Err(error) => self.show_error(format!("{}: {error}", path.display())),
+            }
+        }
+
+        if let Some(path) = paths::registers_file() {
+            match Registers::load(&path) {
+                Ok(registers) => self.registers = registers,
+                Err(error) => self.show_error(format!("{}: {error}", path.display())),
+            }
+        }
+    }
+
+    /// Load a keymap file and check its commands exist.
+    pub(crate) fn read_keymap(path: &std::path::Path) -> Result<Keymap, KeymapError> {
+        let keymap = Keymap::load(path)?;
+        keymap.validate(&commands::registry())?;
+
+        Ok(keymap)
+    }
+
+    pub(crate) fn commands(&self) -> Rc<Registry<App>> {
+        Rc::clone(&self.commands)
+    }
+
+    /// Run a command by name. Keys, the command line and scripts all come
+    /// through here.
+    pub fn execute(&mut self, invocation: &Invocation) -> Result<(), CommandError> {
+        self.commands().dispatch(self, invocation)
+    }
+
+    /// Run a command the user asked for, remembering it for macros and
+    /// `.`. A step that waits for more input isn't remembered; the one
+    /// that completes it is.
+    fn run(&mut self, step: Step) -> Result<(), CommandError> {
+        let repeatable = !UNREPEATABLE.contains(&step.invocation.name.as_str());
+
+        if repeatable && self.is_idle() {
+            self.change.clear();
+            self.change_edits = self.edit_count;
+        }
+
+        let recording = self.replaying == 0 && self.macros.recording().is_some();
+
+        self.count = step.count;
+        let result = self.execute(&step.invocation);
+
+        if self.awaiting_register.is_some() || self.command_line.is_some() {
+            return result;
+        }
+
+        if recording {
+            self.macros.push(step.clone());
+        }
+
+        if repeatable {
+            self.change.push(step);
+
+            if self.is_idle() && self.edit_count != self.change_edits {
+                self.last_change = std::mem::take(&mut self.change);
+                self.change_edits = self.edit_count;
+            }
+        }
+
+        result
+    }
+
+    /// Nothing is half-typed: no operator, selection or register waiting.
+    fn is_idle(&self) -> bool {
+        self.mode == Mode::Normal && self.operator.is_none() && self.register.is_none()
+    }
+
+    pub(crate) fn show_message(&mut self, text: impl Into<String>) {
+        self.message = Some(StatusMessage::info(text));
     }
 
-    fn key_to_note(key: &Key) -> Option<Note> {
-        let Key::Character(chars) = key.as_ref() else {
-            return None;
+    pub(crate) fn show_error(&mut self, text: impl Into<String>) {
+        self.message = Some(StatusMessage::error(text));
+    }
+
+    pub(crate) fn open_command_line(&mut self) {
+        self.pending.clear();
+        self.command_line = Some(CommandLine::new());
+    }
+
+    /// Handle a key while the command line is open.
+    fn command_line_key(&mut self, chord: Chord) {
+        let commands = self.commands();
+        let Some(line) = &mut self.command_line else {
+            return;
         };
 
-        let mut chars = chars.chars();
-        let char = chars.next()?;
-
-        if chars.next().is_some() {
-            return None;
-        }
-
-        // One-octave "typing keyboard piano": white keys on home row,
-        // black keys on top row.
-        match char.to_ascii_lowercase() {
-            // White keys
-            'a' => Some(Note::C4),
-            's' => Some(Note::D4),
-            'd' => Some(Note::E4),
-            'f' => Some(Note::F4),
-            'g' => Some(Note::G4),
-            'h' => Some(Note::A4),
-            'j' => Some(Note::B4),
-            'k' => Some(Note::C5),
-            'l' => Some(Note::D5),
-            ';' => Some(Note::E5),
-            // Black keys
-            'q' => Some(Note::CSharp4),
-            'w' => Some(Note::DSharp4),
-            'e' => Some(Note::FSharp4),
-            'r' => Some(Note::GSharp4),
-            't' => Some(Note::ASharp4),
-            'y' => Some(Note::CSharp5),
-            'u' => Some(Note::DSharp5),
-            'o' => Some(Note::FSharp5),
-            'p' => Some(Note::GSharp5),
-            _ => None,
+        match line.key(&chord, &commands, &self.history) {
+            Outcome::Editing => {}
+            Outcome::Cancel => self.command_line = None,
+            Outcome::Submit(text) => {
+                self.command_line = None;
+                self.submit(&text);
+            }
+        }
+    }
+
+    /// Run a line entered on the command line, remembering it in history.
+    fn submit(&mut self, text: &str) {
+        if text.trim().is_empty() {
+            return;
+        }
+
+        self.history.push(text);
+
+        if let Some(path) = paths::history_file()
+            && let Err(error) = self.history.save(&path)
+        {
+            self.show_error(format!("{}: {error}", path.display()));
+        }
+
+        let result = Invocation::parse(text).and_then(|invocation| {
+            self.run(Step {
+                count: self.count,
+                invocation,
+            })
+        });
+
+        if let Err(error) = result {
+            self.show_error(error.to_string());
+        }
+    }
+
+    pub(crate) fn project(&self) -> &Project {
+        &self.project
+    }
+
+    pub(crate) fn project_mut(&mut self) -> &mut Project {
+        &mut self.project
+    }
+
+    /// Save to `path`, or to where the project was last saved. The path
+    /// is remembered for the next `:w`.
+    pub(crate) fn save_project(&mut self, path: Option<&Path>) -> Result<PathBuf, CommandError> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| self.project_path.clone())
+            .ok_or_else(|| CommandError::Failed("No file name".to_string()))?;
+
+        self.project
+            .save(&path)
+            .map_err(|error| CommandError::Failed(format!("{}: {error}", path.display())))?;
+        self.project_path = Some(path.clone());
+
+        Ok(path)
+    }
+
+    pub(crate) fn select_track(&mut self, track: TrackId) {
+        self.track = track;
+    }
+
+    /// Move the active track by `step`, wrapping at either end.
+    pub(crate) fn cycle_track(&mut self, step: isize) {
+        let tracks = &self.project.tracks;
+        let Some(index) = tracks.iter().position(|track| track.id == self.track) else {
+            return;
+        };
+
+        let index = (index as isize + step).rem_euclid(tracks.len() as isize) as usize;
+        self.track = tracks[index].id;
+    }
+
+    /// Scroll or zoom the piano roll.
+    pub(crate) fn update_viewport(&mut self, change: impl FnOnce(&mut Viewport)) {
+        self.grid.update_viewport(change);
+    }
+
+    fn active_clip(&self) -> &Clip {
+        self.project
+            .track(self.track)
+            .map_or(&EMPTY_CLIP, |track| &track.clip)
+    }
+
+    /// The count typed before the current command, 1 if none.
+    pub(crate) fn take_count(&mut self) -> u32 {
+        self.count.take().unwrap_or(1)
+    }
+
+    /// Add a digit to the count when this mode takes one and the key isn't
+    /// bound to something else. A leading 0 is never a count.
+    fn push_count_digit(&mut self, chord: &Chord) -> bool {
+        let Some(digit) = chord.as_char().and_then(|c| c.to_digit(10)) else {
+            return false;
+        };
+
+        if !self.mode.takes_count()
+            || (digit == 0 && self.count.is_none())
+            || !matches!(
+                self.keymap.lookup(self.mode, std::slice::from_ref(chord)),
+                Lookup::Unbound
+            )
+        {
+            return false;
+        }
+
+        let count = self.count.unwrap_or(0).saturating_mul(10) + digit;
+        self.count = Some(count.min(MAX_COUNT));
+
+        true
+    }
+
+    fn set_cursor(&mut self, cursor: Cursor) {
+        self.cursor = cursor;
+
+        let size = self.roll_size;
+        self.grid.update_viewport(|viewport| {
+            viewport.reveal(cursor.tick, cursor.pitch, size.width, size.height)
+        });
+    }
+
+    /// Move the cursor, or with an operator pending, apply it to the notes
+    /// the motion passes over.
+    pub(crate) fn move_cursor(&mut self, motion: Motion, count: u32) -> Result<(), CommandError> {
+        let operator = self.operator.take();
+        let count = count * operator.map_or(1, |(_, count)| count);
+        let target = motion.target(self.cursor, count, self.options.grid, self.active_clip());
+
+        match operator {
+            Some((operator, _)) => {
+                let range = Range::motion(self.active_clip(), self.cursor, target, motion);
+                self.operate(operator, range)
+            }
+            None => {
+                self.set_cursor(target);
+                Ok(())
+            }
+        }
+    }
+
+    /// Apply `operator` to the selection in Visual modes; otherwise wait
+    /// for a motion or text object.
+    pub(crate) fn begin_operator(
+        &mut self,
+        operator: Operator,
+        count: u32,
+    ) -> Result<(), CommandError> {
+        if let Some(selection) = self.selection() {
+            let range = selection.range(self.active_clip(), self.options.grid);
+            return self.operate(operator, range);
+        }
+
+        self.operator = Some((operator, count));
+        self.mode = Mode::Operator;
+
+        Ok(())
+    }
+
+    /// The selected area while in a Visual mode.
+    fn selection(&self) -> Option<Selection> {
+        let kind = self.mode.selection_kind()?;
+
+        Some(Selection {
+            anchor: self.anchor,
+            cursor: self.cursor,
+            kind,
+        })
+    }
+
+    /// Toggle a Visual mode. Switching between the two keeps the selection.
+    pub(crate) fn visual(&mut self, mode: Mode) {
+        if self.mode == mode {
+            self.enter_mode(Mode::Normal);
+            return;
+        }
+
+        if self.mode.selection_kind().is_none() {
+            self.anchor = self.cursor;
+        }
+
+        self.enter_mode(mode);
+    }
+
+    /// Jump to the other end of the selection, as vim's `o` does.
+    pub(crate) fn swap_selection_ends(&mut self) {
+        if self.mode.selection_kind().is_some() {
+            let anchor = std::mem::replace(&mut self.anchor, self.cursor);
+            self.set_cursor(anchor);
+        }
+    }
+
+    pub(crate) fn operate_on_object(
+        &mut self,
+        object: TextObject,
+        count: u32,
+    ) -> Result<(), CommandError> {
+        let (operator, operator_count) = self
+            .operator
+            .take()
+            .ok_or_else(|| CommandError::Failed("No operator pending".to_string()))?;
+        let count = count * operator_count;
+        let range = Range::object(self.active_clip(), self.cursor, object, count);
+
+        self.operate(operator, range)
+    }
+
+    fn operate(&mut self, operator: Operator, range: Range) -> Result<(), CommandError> {
+        let register = self.register.take().unwrap_or(UNNAMED);
+        self.enter_mode(Mode::Normal);
+
+        let clip = self.active_clip();
+        let edits = operator.edits(self.track, clip, &range.notes, self.options.grid);
+
+        if operator.yanks() && !range.notes.is_empty() {
+            let notes = range.relative_notes(clip);
+            let yanked = notes.len();
+
+            self.registers
+                .store(register, notes)
+                .map_err(|error| CommandError::Failed(error.to_string()))?;
+            self.save_registers();
+
+            if operator == Operator::Yank {
+                self.show_message(format!("{yanked} notes yanked"));
+            }
+        }
+
+        self.apply_edits(edits)?;
+
+        // Like vim, deleting or yanking leaves the cursor at the start.
+        if operator.yanks() && !range.notes.is_empty() {
+            self.set_cursor(Cursor {
+                tick: range.start,
+                ..self.cursor
+            });
+        }
+
+        // Change writes the replacement straight away.
+        if operator == Operator::Change {
+            self.enter_mode(Mode::Step);
+        }
+
+        Ok(())
+    }
+
+    /// Name the register for the next delete, yank or paste. Without a
+    /// name, the next key gives it.
+    pub(crate) fn select_register(&mut self, name: Option<char>) -> Result<(), CommandError> {
+        match name {
+            Some(name) if Registers::is_valid(name) => self.register = Some(name),
+            Some(name) => {
+                return Err(CommandError::Failed(
+                    RegisterError::InvalidName(name).to_string(),
+                ));
+            }
+            None => self.awaiting_register = Some("register"),
+        }
+
+        Ok(())
+    }
+
+    /// Start recording a macro into `name`, or stop the one in progress.
+    /// Without a name, the next key gives it.
+    pub(crate) fn record(&mut self, name: Option<char>) -> Result<(), CommandError> {
+        if let Some(name) = self.macros.stop() {
+            self.show_message(format!("Recorded @{name}"));
+            return Ok(());
+        }
+
+        match name {
+            Some(name) => self
+                .macros
+                .start(name)
+                .map_err(|error| CommandError::Failed(error.to_string())),
+            None => {
+                self.awaiting_register = Some("record");
+                Ok(())
+            }
+        }
+    }
+
+    /// Replay the macro in `name` `count` times. `@` replays the last one.
+    /// Without a name, the next key gives it.
+    pub(crate) fn replay(&mut self, name: Option<char>, count: u32) -> Result<(), CommandError> {
+        let Some(name) = name else {
+            self.awaiting_register = Some("replay");
+            return Ok(());
+        };
+
+        let steps = self
+            .macros
+            .get(name)
+            .map_err(|error| CommandError::Failed(error.to_string()))?
+            .ok_or_else(|| CommandError::Failed(format!("Nothing recorded in @{name}")))?;
+
+        if self.replaying >= keymap::MAX_DEPTH {
+            return Err(CommandError::Failed(format!("@{name} replays itself")));
+        }
+
+        self.replaying += 1;
+        let result = (0..count)
+            .flat_map(|_| steps.iter().cloned())
+            .try_for_each(|step| self.run(step));
+        self.replaying -= 1;
+
+        result
+    }
+
+    /// Run the script at `path`, one command per line.
+    pub(crate) fn source(&mut self, path: &str) -> Result<(), CommandError> {
+        let script = std::fs::read_to_string(path)
+            .map_err(|error| CommandError::Failed(format!("{path}: {error}")))?;
+
+        if self.sourcing >= keymap::MAX_DEPTH {
+            return Err(CommandError::Failed(format!("{path} sources itself")));
+        }
+
+        self.sourcing += 1;
+        let result = self.commands().run_script(self, &script);
+        self.sourcing -= 1;
+
+        result
+    }
+
+    /// Repeat the last change `count` times, from the cursor.
+    pub(crate) fn repeat(&mut self, count: u32) -> Result<(), CommandError> {
+        let steps = self.last_change.clone();
+
+        for _ in 0..count {
+            for step in &steps {
+                self.count = step.count;
+                self.execute(&step.invocation)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    fn save_registers(&mut self) {
+        if let Some(path) = paths::registers_file()
+            && let Err(error) = self.registers.save(&path)
+        {
+            self.show_error(format!("{}: {error}", path.display()));
+        }
+    }
+
+    /// Paste the named register at the cursor `count` times, back to back,
+    /// with fresh note ids. The cursor then moves past the pasted notes,
+    /// ready for the next paste, unless `before` keeps it where it was.
+    pub(crate) fn paste(&mut self, count: u32, before: bool) -> Result<(), CommandError> {
+        let name = self.register.take().unwrap_or(UNNAMED);
+        let notes = self
+            .registers
+            .get(name)
+            .map_err(|error| CommandError::Failed(error.to_string()))?
+            .ok_or_else(|| CommandError::Failed(format!("Nothing in register {name}")))?
+            .to_vec();
+
+        let grid = self.options.grid;
+        let start = self.cursor.tick;
+        // Copies start on the grid, so repeated material stays aligned.
+        let step = registers::span(&notes).div_ceil(grid).max(1) * grid;
+
+        let mut edits = Vec::new();
+
+        for copy in 0..count as u64 {
+            let offset = start + Tick::from_raw(copy * step);
+
+            for note in &notes {
+                edits.push(Edit::AddNote {
+                    track: self.track,
+                    id: self.project.ids.next_note_id(),
+                    note: NoteEvent {
+                        start_tick: note.start_tick + offset,
+                        ..*note
+                    },
+                });
+            }
+        }
+
+        self.apply_edits(edits)?;
+
+        if !before {
+            self.set_cursor(Cursor {
+                tick: start + Tick::from_raw(count as u64 * step),
+                ..self.cursor
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Write a note at the cursor with the step length and move on a step.
+    /// With `chord`, the note joins the chord last written instead, as
+    /// when keys are held together.
+    pub(crate) fn step_note(&mut self, pitch: u8, chord: bool) -> Result<(), CommandError> {
+        let chord_start = self
+            .step_chord
+            .first()
+            .and_then(|id| self.active_clip().notes.get(id))
+            .map(|note| note.start_tick)
+            .filter(|_| chord);
+
+        let start = match chord_start {
+            Some(start) => start,
+            None => {
+                self.step_chord.clear();
+                self.cursor.tick
+            }
+        };
+
+        let id = self.project.ids.next_note_id();
+        let note = NoteEvent {
+            start_tick: start,
+            length_ticks: self.options.step,
+            note: Note::from_u8_lossy(pitch),
+            velocity: self.velocity,
+        };
+
+        self.apply_edits(vec![Edit::AddNote {
+            track: self.track,
+            id,
+            note,
+        }])?;
+        self.step_chord.push(id);
+
+        self.set_cursor(Cursor {
+            tick: start + Tick::from_raw(self.options.step),
+            pitch,
+        });
+
+        Ok(())
+    }
+
+    /// Leave a step empty.
+    pub(crate) fn step_rest(&mut self) {
+        self.step_chord.clear();
+        self.set_cursor(Cursor {
+            tick: self.cursor.tick + Tick::from_raw(self.options.step),
+            ..self.cursor
+        });
+    }
+
+    /// Hold the chord last written for another step.
+    pub(crate) fn step_tie(&mut self) -> Result<(), CommandError> {
+        let step = self.options.step;
+        let clip = self.active_clip();
+        let edits: Vec<_> = self
+            .step_chord
+            .iter()
+            .filter_map(|id| Some((*id, *clip.notes.get(id)?)))
+            .map(|(id, note)| Edit::ChangeNote {
+                track: self.track,
+                id,
+                before: note,
+                after: NoteEvent {
+                    length_ticks: note.length_ticks + step,
+                    ..note
+                },
+            })
+            .collect();
+
+        if edits.is_empty() {
+            return Err(CommandError::Failed("No notes to tie".to_string()));
+        }
+
+        self.apply_edits(edits)?;
+        self.set_cursor(Cursor {
+            tick: self.cursor.tick + Tick::from_raw(step),
+            ..self.cursor
+        });
+
+        Ok(())
+    }
+
+    /// Apply edits to the project as one undo step.
+    pub(crate) fn apply_edits(&mut self, edits: Vec<Edit>) -> Result<(), CommandError> {
+        if edits.is_empty() {
+            return Ok(());
+        }
+
+        self.undo
+            .apply(&mut self.project, edits)
+            .map_err(|error| CommandError::Failed(error.to_string()))?;
+        self.edit_count += 1;
+
+        Ok(())
+    }
+
+    /// Undo up to `count` steps, or redo them when `redo` is set.
+    pub(crate) fn undo(&mut self, count: u32, redo: bool) -> Result<(), CommandError> {
+        for _ in 0..count {
+            let done = if redo {
+                self.undo.redo(&mut self.project)
+            } else {
+                self.undo.undo(&mut self.project)
+            }
+            .map_err(|error| CommandError::Failed(error.to_string()))?;
+
+            if !done {
+                self.show_message(if redo {
+                    "Already at newest change"
+                } else {
+                    "Already at oldest change"
+                });
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn options(&self) -> &Options {
+        &self.options
+    }
+
+    pub(crate) fn options_mut(&mut self) -> &mut Options {
+        &mut self.options
+    }
+
+    pub(crate) fn enter_mode(&mut self, mode: Mode) {
+        if !matches!(mode, Mode::Play | Mode::Step) {
+            self.all_notes_off();
+        }
+
+        self.step_chord.clear();
+
+        self.operator = None;
+        self.count = None;
+        self.register = None;
+
+        self.mode = mode;
+    }
+
+    pub(crate) fn set_velocity(&mut self, velocity: Velocity) {
+        self.velocity = velocity;
+    }
+
+    /// Raise or lower the Play velocity, staying within 1–127.
+    pub(crate) fn adjust_velocity(&mut self, change: i32) {
+        let level = (u8::from(self.velocity) as i32 + change).clamp(1, 127);
+
+        // UNWRAP SAFETY: clamped to 7 bits.
+        self.velocity = Velocity::try_from(level as u8).unwrap();
+    }
+
+    /// Shift the piano layout by semitones, as far as keeps every key
+    /// within MIDI range. Held keys keep the note they started.
+    pub(crate) fn shift_keys(&mut self, semitones: i32) {
+        let Some(range) = self.keymap.piano_range() else {
+            return;
+        };
+
+        self.transpose =
+            (self.transpose + semitones).clamp(-(*range.start() as i32), 127 - *range.end() as i32);
+    }
+
+    pub(crate) fn reset_keys(&mut self) {
+        self.transpose = 0;
+    }
+
+    /// A layout note moved by the current shift.
+    fn transposed(&self, note: Note) -> Note {
+        Note::from_u8_lossy((u8::from(note) as i32 + self.transpose).clamp(0, 127) as u8)
+    }
+
+    /// Names of the lowest and highest notes the piano keys play.
+    fn key_range(&self) -> Option<String> {
+        let range = self.keymap.piano_range()?;
+        let name =
+            |pitch: u8| viewport::pitch_name(u8::from(self.transposed(Note::from_u8_lossy(pitch))));
+
+        Some(format!("{}–{}", name(*range.start()), name(*range.end())))
+    }
+
+    pub(crate) fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    pub(crate) fn keymap_mut(&mut self) -> &mut Keymap {
+        &mut self.keymap
+    }
+
+    /// Handle one key outside the command line: a register or macro name
+    /// being waited for, a piano key, a count digit, or else the current
+    /// mode's bindings. Keys a mapping types come back through here, so
+    /// they work as if typed; `remap` and `depth` are as for `press`.
+    fn handle_key(&mut self, chord: Chord, remap: bool, depth: usize) -> Result<(), CommandError> {
+        if let Some(command) = self.awaiting_register.take() {
+            // Any key that isn't a character, like <Esc>, cancels.
+            let Some(name) = chord.as_char() else {
+                return Ok(());
+            };
+
+            return self.run(Step {
+                count: self.count,
+                invocation: Invocation::new(command).arg(name),
+            });
+        }
+
+        if self.pending.is_empty()
+            && let Some((key, note)) = self.keymap.note(self.mode, &chord)
+        {
+            let note = self.transposed(note);
+
+            if !self.note_on(key, note) {
+                return Ok(());
+            }
+
+            // In Step mode the note is heard as it's written. Keys already
+            // held make it part of their chord.
+            let result = if self.mode == Mode::Step {
+                let mut invocation = Invocation::new("step-note").arg(u8::from(note).to_string());
+
+                if self.active_notes.len() > 1 {
+                    invocation = invocation.arg("chord");
+                }
+
+                self.run(Step {
+                    count: None,
+                    invocation,
+                })
+            } else {
+                Ok(())
+            };
+
+            // A mapping has no key to let go of, so its notes are tapped.
+            if depth > 0 {
+                self.note_off(key);
+            }
+
+            return result;
         }
+
+        if self.pending.is_empty() && self.push_count_digit(&chord) {
+            return Ok(());
+        }
+
+        self.press(chord, remap, depth)
     }
 
-    fn note_on(&mut self, note: Note) {
-        if self.active_notes.insert(note) {
+    /// Feed one key through the current mode's bindings. Keys a binding
+    /// types go back through `handle_key`; without `remap` only their
+    /// command bindings run.
+    fn press(&mut self, chord: Chord, remap: bool, depth: usize) -> Result<(), CommandError> {
+        self.pending.push(chord);
+
+        let action = match self.keymap.lookup(self.mode, &self.pending) {
+            Lookup::Pending => return Ok(()),
+            Lookup::Unbound => {
+                self.pending.clear();
+
+                // Like vim, a key that isn't a motion cancels the operator.
+                if self.mode == Mode::Operator {
+                    self.enter_mode(Mode::Normal);
+                }
+
+                return Ok(());
+            }
+            Lookup::Bound(action) => action.clone(),
+        };
+
+        let typed = std::mem::take(&mut self.pending);
+
+        match action {
+            Action::Command(invocation) => self.run(Step {
+                count: self.count,
+                invocation,
+            }),
+            Action::Keys { .. } if !remap => Ok(()),
+            Action::Keys { keys, remap } => {
+                if depth >= keymap::MAX_DEPTH {
+                    let keys = keymap::format_keys(&typed);
+                    return Err(CommandError::Failed(
+                        KeymapError::Recursive(keys).to_string(),
+                    ));
+                }
+
+                for chord in keys {
+                    self.handle_key(chord, remap, depth + 1)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Sound a piano key's note, unless the key is already held. `false`
+    /// for a held key, so key repeat can be ignored.
+    fn note_on(&mut self, key: char, note: Note) -> bool {
+        let pressed = !self.active_notes.contains_key(&key);
+
+        if pressed {
+            self.active_notes.insert(key, note);
             let _ = self.control.send_midi(
                 TrackId(0),
                 MidiEvent::NoteOn {
                     note,
-                    velocity: Velocity::MAX,
+                    velocity: self.velocity,
                 },
             );
         }
+
+        pressed
     }
 
-    fn note_off(&mut self, note: Note) {
-        if self.active_notes.remove(&note) {
+    /// Release whatever note the key started, even if the layout has
+    /// been shifted since.
+    fn note_off(&mut self, key: char) {
+        if let Some(note) = self.active_notes.remove(&key) {
             let _ = self
                 .control
                 .send_midi(TrackId(0), MidiEvent::NoteOff { note });
@@ -110,72 +1054,122 @@ impl App {
     /// Send note-offs for all held keys when leaving play mode so no
     /// voices are left hanging.
     fn all_notes_off(&mut self) {
-        for note in self.active_notes.drain() {
+        for (_, note) in self.active_notes.drain() {
             let _ = self
                 .control
                 .send_midi(TrackId(0), MidiEvent::NoteOff { note });
         }
     }
 
+    /// Step through the preset bank, wrapping at either end. The switch
+    /// travels as a ProgramChange so the audio thread applies it in order.
+    pub(crate) fn cycle_preset(&mut self, step: isize) {
+        // Program numbers are 7-bit, so only the first 128 presets are reachable.
+        let count = self.presets.len().min(128);
+
+        if count == 0 {
+            return;
+        }
+
+        self.program = (self.program as isize + step).rem_euclid(count as isize) as usize;
+
+        let program = ProgramNumber::from_u8_lossy(self.program as u8);
+        let _ = self
+            .control
+            .send_midi(TrackId(0), MidiEvent::ProgramChange { program });
+    }
+
+    fn preset_name(&self) -> &str {
+        self.presets.get(self.program).map_or("", String::as_str)
+    }
+
     fn theme(&self) -> Theme {
         Theme::Dark
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
         match message {
-            Message::KeyPressed(key, _modifiers) => {
-                match key.as_ref() {
-                    Key::Character("n") | Key::Character("N") => {
-                        self.mode = Mode::Play;
-                        return Task::none();
-                    }
-                    Key::Named(Named::Escape) => {
-                        self.mode = Mode::Normal;
-                        self.all_notes_off();
-                        return Task::none();
-                    }
-                    _ => {}
+            Message::KeyPressed(key, modifiers) => {
+                let Some(chord) = Chord::from_key(&key, modifiers) else {
+                    return Task::none();
+                };
+
+                if self.command_line.is_some() {
+                    self.command_line_key(chord);
+                    return Task::none();
                 }
 
-                if self.mode == Mode::Play
-                    && let Some(note) = Self::key_to_note(&key)
-                {
-                    self.note_on(note);
+                self.message = None;
+
+                if let Err(error) = self.handle_key(chord, true, 0) {
+                    self.show_error(error.to_string());
                 }
             }
-            Message::KeyReleased(key, _modifiers) => {
-                if self.mode == Mode::Play
-                    && let Some(note) = Self::key_to_note(&key)
+            Message::KeyReleased(key, modifiers) => {
+                if let Some((key, _)) = Chord::from_key(&key, modifiers)
+                    .and_then(|chord| self.keymap.note(self.mode, &chord))
                 {
-                    self.note_off(note);
+                    self.note_off(key);
                 }
             }
+            Message::RollResized(size) => self.roll_size = size,
             Message::Tick => {}
         }
         Task::none()
     }
 
     fn view(&self) -> Element<'_, Message> {
-        let status = status_bar::view(&self.mode);
-        let canvas = self.grid.view();
+        let status = status_bar::view(Status {
+            mode: &self.mode,
+            bpm: self.project.bpm,
+            position: self.cursor.tick,
+            track: self
+                .project
+                .track(self.track)
+                .map_or("", |track| &track.name),
+            preset: self.preset_name(),
+            velocity: matches!(self.mode, Mode::Play | Mode::Step).then_some(self.velocity),
+            keys: matches!(self.mode, Mode::Play | Mode::Step)
+                .then(|| self.key_range())
+                .flatten(),
+            step: (self.mode == Mode::Step)
+                .then(|| self.options.get("step"))
+                .flatten(),
+            recording: self.macros.recording(),
+            command_line: self.command_line.as_ref().map(CommandLine::text),
+            message: self.message.as_ref(),
+        });
+        let selection = self
+            .selection()
+            .map(|selection| (selection, self.mode.color()));
+        let canvas = self.grid.view(
+            self.active_clip(),
+            self.cursor,
+            self.options.grid,
+            selection,
+        );
 
         column![canvas, status].height(Fill).into()
     }
 
     fn subscription(&self) -> Subscription<Message> {
         keyboard::listen().filter_map(|event| match event {
-            keyboard::Event::KeyPressed { key, modifiers, .. } => {
-                Some(Message::KeyPressed(key, modifiers))
-            }
-            keyboard::Event::KeyReleased { key, modifiers, .. } => {
-                Some(Message::KeyReleased(key, modifiers))
-            }
+            keyboard::Event::KeyPressed {
+                modified_key,
+                modifiers,
+                ..
+            } => Some(Message::KeyPressed(modified_key, modifiers)),
+            keyboard::Event::KeyReleased {
+                modified_key,
+                modifiers,
+                ..
+            } => Some(Message::KeyReleased(modified_key, modifiers)),
             _ => None,
         })
     }
 }
 
-pub fn run(control: PlaybackControl) -> iced::Result {
+pub fn run(control: PlaybackControl, presets: Vec<String>) -> iced::Result {
     let control = RefCell::new(Some(control));
 
     iced::application(
@@ -185,7 +1179,7 @@ pub fn run(control: PlaybackControl) -> iced::Result {
                 .take()
                 .expect("application boot called more than once");
 
-            App::new(control)
+            App::new(control, presets.clone())
         },
         App::update,
         App::view,
@@ -196,3 +1190,340 @@ pub fn run(control: PlaybackControl) -> iced::Result {
     .centered()
     .run()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use motif_engine::events::{Event, RoutedEvent};
+    use rtrb::{Consumer, RingBuffer};
+
+    use crate::keymap::KeyCode;
+
+    fn app() -> (App, Consumer<RoutedEvent>) {
+        let (producer, consumer) = RingBuffer::new(64);
+        let (app, _) = App::new(PlaybackControl::new(producer), Vec::new());
+
+        (app, consumer)
+    }
+
+    fn key_event(chord: Chord) -> (Key, Modifiers) {
+        let key = match chord.key {
+            KeyCode::Char(c) => Key::Character(c.to_string().into()),
+            KeyCode::Named(named) => Key::Named(named),
+        };
+
+        let mut modifiers = Modifiers::empty();
+        modifiers.set(Modifiers::CTRL, chord.ctrl);
+        modifiers.set(Modifiers::ALT, chord.alt);
+        modifiers.set(Modifiers::SHIFT, chord.shift);
+        modifiers.set(Modifiers::LOGO, chord.logo);
+
+        (key, modifiers)
+    }
+
+    fn send_key(app: &mut App, chord: Chord, pressed: bool) {
+        let (key, modifiers) = key_event(chord);
+        let message = if pressed {
+            Message::KeyPressed(key, modifiers)
+        } else {
+            Message::KeyReleased(key, modifiers)
+        };
+
+        let _ = app.update(message);
+    }
+
+    /// Press and hold each key in `notation`.
+    fn hold_keys(app: &mut App, notation: &str) {
+        for chord in app.keymap.parse_keys(notation).unwrap() {
+            send_key(app, chord, true);
+        }
+    }
+
+    fn release_keys(app: &mut App, notation: &str) {
+        for chord in app.keymap.parse_keys(notation).unwrap() {
+            send_key(app, chord, false);
+        }
+    }
+
+    /// Press and release each key in `notation` in turn.
+    fn type_keys(app: &mut App, notation: &str) {
+        for chord in app.keymap.parse_keys(notation).unwrap() {
+            send_key(app, chord, true);
+            send_key(app, chord, false);
+        }
+    }
+
+    /// Pitches sent since the last call, `true` for NoteOn.
+    fn sent_notes(consumer: &mut Consumer<RoutedEvent>) -> Vec<(bool, u8)> {
+        std::iter::from_fn(|| consumer.pop().ok())
+            .filter_map(|routed| match routed.event {
+                Event::Midi(MidiEvent::NoteOn { note, .. }) => Some((true, u8::from(note))),
+                Event::Midi(MidiEvent::NoteOff { note }) => Some((false, u8::from(note))),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Start tick, pitch and length of each note on the active track.
+    fn notes(app: &App) -> Vec<(u64, u8, u64)> {
+        app.active_clip()
+            .sorted()
+            .into_iter()
+            .map(|(_, note)| {
+                (
+                    note.start_tick.as_raw(),
+                    u8::from(note.note),
+                    note.length_ticks,
+                )
+            })
+            .collect()
+    }
+
+    fn note(start: u64, pitch: u8) -> NoteEvent {
+        NoteEvent {
+            start_tick: Tick::from_raw(start),
+            length_ticks: 120,
+            note: Note::from_u8_lossy(pitch),
+            velocity: Velocity::MAX,
+        }
+    }
+
+    /// Put notes on the active track at pitch 60, outside the undo history.
+    fn add_notes(app: &mut App, starts: impl IntoIterator<Item = u64>) {
+        for start in starts {
+            let id = app.project.ids.next_note_id();
+            let track = app.project.track_mut(app.track).unwrap();
+            track.clip.notes.insert(id, note(start, 60));
+        }
+    }
+
+    fn starts(app: &App) -> Vec<u64> {
+        notes(app).into_iter().map(|(start, _, _)| start).collect()
+    }
+
+    #[test]
+    fn count_before_operator_deletes_that_many_notes() {
+        let (mut app, _) = app();
+        add_notes(&mut app, [0, 120, 240, 360, 480]);
+
+        type_keys(&mut app, "3dd");
+        assert_eq!(starts(&app), [360, 480]);
+
+        type_keys(&mut app, "u2d2d");
+        assert_eq!(starts(&app), [480]);
+    }
+
+    #[test]
+    fn repeat_deletes_at_the_cursor() {
+        let (mut app, _) = app();
+        add_notes(&mut app, [0, 120, 240]);
+
+        type_keys(&mut app, "ddw.");
+
+        assert_eq!(starts(&app), [240]);
+    }
+
+    #[test]
+    fn repeat_takes_a_count() {
+        let (mut app, _) = app();
+        add_notes(&mut app, [0]);
+
+        type_keys(&mut app, "yyp3.");
+
+        assert_eq!(starts(&app), [0, 0, 120, 240, 360]);
+    }
+
+    #[test]
+    fn paste_goes_at_the_cursor() {
+        let (mut app, _) = app();
+        app.registers.store(UNNAMED, vec![note(0, 64)]).unwrap();
+        app.cursor.tick = Tick::from_raw(240);
+
+        type_keys(&mut app, "P");
+        assert_eq!(notes(&app), [(240, 64, 120)]);
+        assert_eq!(app.cursor.tick, Tick::from_raw(240));
+
+        type_keys(&mut app, "2p");
+        assert_eq!(starts(&app), [240, 240, 360]);
+        assert_eq!(app.cursor.tick, Tick::from_raw(480));
+    }
+
+    #[test]
+    fn repeat_redoes_a_visual_change_from_the_cursor() {
+        let (mut app, _) = app();
+        add_notes(&mut app, [0, 120, 240, 360]);
+
+        type_keys(&mut app, "vld");
+        assert_eq!(starts(&app), [240, 360]);
+
+        type_keys(&mut app, "w.");
+        assert_eq!(starts(&app), []);
+    }
+
+    #[test]
+    fn macros_replay_at_the_cursor() {
+        let (mut app, _) = app();
+        add_notes(&mut app, [0, 120, 240, 360]);
+
+        type_keys(&mut app, "qaddwq");
+        assert_eq!(starts(&app), [120, 240, 360]);
+
+        type_keys(&mut app, "@a");
+        assert_eq!(starts(&app), [240, 360]);
+        assert_eq!(app.cursor.tick, Tick::from_raw(240));
+
+        type_keys(&mut app, "@@");
+        assert_eq!(starts(&app), [360]);
+    }
+
+    #[test]
+    fn macro_replaying_itself_stops() {
+        let (mut app, _) = app();
+        let count = keymap::MAX_DEPTH as u64 + 10;
+        add_notes(&mut app, (0..count).map(|i| i * 120));
+
+        type_keys(&mut app, "qaddw@aq");
+        type_keys(&mut app, "@a");
+
+        assert!(
+            app.message
+                .as_ref()
+                .unwrap()
+                .text
+                .contains("replays itself")
+        );
+        assert_eq!(
+            notes(&app).len() as u64,
+            count - 1 - keymap::MAX_DEPTH as u64
+        );
+    }
+
+    #[test]
+    fn script_sourcing_itself_stops() {
+        let (mut app, _) = app();
+        let path = std::env::temp_dir().join(format!("motif-source-{}.vim", std::process::id()));
+        let path = path.to_str().unwrap();
+        std::fs::write(path, format!("source {path}\n")).unwrap();
+
+        let error = app.source(path).unwrap_err();
+        std::fs::remove_file(path).unwrap();
+
+        assert!(error.to_string().contains("sources itself"), "{error}");
+        assert_eq!(app.sourcing, 0);
+    }
+
+    #[test]
+    fn held_key_releases_the_note_it_started() {
+        let (mut app, mut consumer) = app();
+
+        type_keys(&mut app, "n");
+        hold_keys(&mut app, "a");
+        app.submit("octave up");
+        release_keys(&mut app, "a");
+        type_keys(&mut app, "a");
+
+        assert_eq!(
+            sent_notes(&mut consumer),
+            [(true, 60), (false, 60), (true, 72), (false, 72)]
+        );
+    }
+
+    #[test]
+    fn key_shift_stops_at_the_midi_range() {
+        let (mut app, mut consumer) = app();
+        type_keys(&mut app, "n");
+
+        // The layout spans 60 (`a`) to 80 (`p`).
+        app.shift_keys(1000);
+        type_keys(&mut app, "p");
+        app.shift_keys(-1000);
+        type_keys(&mut app, "a");
+
+        assert_eq!(
+            sent_notes(&mut consumer),
+            [(true, 127), (false, 127), (true, 0), (false, 0)]
+        );
+    }
+
+    #[test]
+    fn velocity_stays_within_range() {
+        let (mut app, _) = app();
+
+        app.adjust_velocity(-1000);
+        assert_eq!(u8::from(app.velocity), 1);
+
+        app.adjust_velocity(1000);
+        assert_eq!(u8::from(app.velocity), 127);
+    }
+
+    #[test]
+    fn step_keys_held_together_write_a_chord() {
+        let (mut app, _) = app();
+
+        type_keys(&mut app, "i");
+        hold_keys(&mut app, "ad");
+        release_keys(&mut app, "ad");
+        type_keys(&mut app, "s");
+
+        assert_eq!(notes(&app), [(0, 60, 120), (0, 64, 120), (120, 62, 120)]);
+        assert_eq!(app.cursor.tick, Tick::from_raw(240));
+    }
+
+    #[test]
+    fn step_rest_leaves_a_gap() {
+        let (mut app, _) = app();
+
+        type_keys(&mut app, "ia0s");
+
+        assert_eq!(starts(&app), [0, 240]);
+    }
+
+    #[test]
+    fn step_tie_lengthens_the_chord() {
+        let (mut app, _) = app();
+
+        type_keys(&mut app, "i");
+        hold_keys(&mut app, "ad");
+        release_keys(&mut app, "ad");
+        type_keys(&mut app, "+s");
+
+        assert_eq!(notes(&app), [(0, 60, 240), (0, 64, 240), (240, 62, 120)]);
+    }
+
+    #[test]
+    fn step_digits_set_the_step() {
+        let (mut app, _) = app();
+
+        type_keys(&mut app, "i5a");
+
+        assert_eq!(app.options.get("step").as_deref(), Some("1/4"));
+        assert_eq!(notes(&app), [(0, 60, 480)]);
+        assert_eq!(app.cursor.tick, Tick::from_raw(480));
+    }
+
+    #[test]
+    fn mapped_keys_can_carry_a_count() {
+        let (mut app, _) = app();
+
+        app.submit("map normal Q 4l");
+        type_keys(&mut app, "Q");
+
+        assert_eq!(app.message, None);
+        assert_eq!(app.cursor.tick, Tick::from_raw(4 * app.options.grid));
+    }
+
+    #[test]
+    fn mapped_keys_can_name_a_register() {
+        let (mut app, _) = app();
+        app.registers.store('a', vec![note(0, 64)]).unwrap();
+
+        let keys = app.keymap.parse_keys("<leader>p").unwrap();
+        let action = Action::parse("\"ap", false, app.keymap.leader()).unwrap();
+        app.keymap.bind(Mode::Normal, keys, action).unwrap();
+        type_keys(&mut app, "<leader>p");
+
+        assert_eq!(app.awaiting_register, None);
+        assert_eq!(notes(&app), [(0, 64, 120)]);
+    }
+}
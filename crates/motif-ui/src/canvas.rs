@@ -1,16 +1,30 @@
-use iced::mouse;
-use iced::widget::canvas::{self, Cache, Canvas, Geometry, Path, Stroke};
-use iced::{Element, Fill, Point, Rectangle, Renderer, Theme};
+use iced::widget::canvas::{
+    self, Action, Cache, Canvas, Event, Frame, Geometry, Path, Stroke, Text,
+};
+use iced::{Color, Element, Fill, Font, Point, Rectangle, Renderer, Size, Theme};
+use iced::{mouse, window};
+use std::collections::HashSet;
+
+use motif_core::id::NoteId;
+use motif_core::project::Clip;
+use motif_core::tick::{TICKS_PER_QUARTER, Tick};
 
 use crate::app::Message;
+use crate::editor::{Cursor, Selection};
 use crate::theme;
+use crate::viewport::{self, Viewport};
 
-const PIXELS_PER_BEAT: f32 = 100.0;
-const LANE_HEIGHT: f32 = 24.0;
-const BEATS_PER_BAR: u32 = 4;
+const BEATS_PER_BAR: u64 = 4;
+/// Width of the pitch keyboard on the left.
+const GUTTER_WIDTH: f32 = 48.0;
+/// Below this lane height only C gets a name in the gutter.
+const MIN_LABELED_LANE: f32 = 14.0;
 
 pub struct PianoRollGrid {
+    /// Lanes, beat lines and the keyboard gutter. Only depends on the
+    /// viewport and size, so it survives note edits and cursor moves.
     grid_cache: Cache,
+    viewport: Viewport,
 }
 
 impl Default for PianoRollGrid {
@@ -23,16 +37,80 @@ impl PianoRollGrid {
     pub fn new() -> Self {
         Self {
             grid_cache: Cache::new(),
+            viewport: Viewport::new(),
+        }
+    }
+
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    /// Change the viewport, redrawing the grid only if it actually moved.
+    pub fn update_viewport(&mut self, change: impl FnOnce(&mut Viewport)) {
+        let before = self.viewport.clone();
+
+        change(&mut self.viewport);
+
+        if self.viewport != before {
+            self.grid_cache.clear();
         }
     }
 
-    pub fn view(&self) -> Element<'_, Message> {
-        Canvas::new(self).width(Fill).height(Fill).into()
+    /// `step` is the cursor's width in ticks. A selection is drawn, with
+    /// its notes, in the given accent color.
+    pub fn view<'a>(
+        &'a self,
+        clip: &'a Clip,
+        cursor: Cursor,
+        step: u64,
+        selection: Option<(Selection, Color)>,
+    ) -> Element<'a, Message> {
+        Canvas::new(PianoRoll {
+            grid: self,
+            clip,
+            cursor,
+            step,
+            selection,
+        })
+        .width(Fill)
+        .height(Fill)
+        .into()
     }
 }
 
-impl canvas::Program<Message> for PianoRollGrid {
-    type State = ();
+/// One frame of the piano roll: the cached grid plus what changes often.
+struct PianoRoll<'a> {
+    grid: &'a PianoRollGrid,
+    clip: &'a Clip,
+    cursor: Cursor,
+    step: u64,
+    selection: Option<(Selection, Color)>,
+}
+
+impl canvas::Program<Message> for PianoRoll<'_> {
+    /// Last size reported to the app.
+    type State = Size;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: &Event,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Option<Action<Message>> {
+        let Event::Window(window::Event::RedrawRequested(_)) = event else {
+            return None;
+        };
+
+        let size = Size::new((bounds.width - GUTTER_WIDTH).max(0.0), bounds.height);
+
+        if *state == size {
+            return None;
+        }
+
+        *state = size;
+        Some(Action::publish(Message::RollResized(size)))
+    }
 
     fn draw(
         &self,
@@ -42,60 +120,245 @@ impl canvas::Program<Message> for PianoRollGrid {
         bounds: Rectangle,
         _cursor: mouse::Cursor,
     ) -> Vec<Geometry> {
-        let grid = self.grid_cache.draw(renderer, bounds.size(), |frame| {
-            // Background — zinc-950
-            frame.fill_rectangle(Point::ORIGIN, bounds.size(), theme::ZINC_950);
-
-            // Horizontal lane dividers — alternating subtle stripe
-            let mut lane: u32 = 0;
-            loop {
-                let y = lane as f32 * LANE_HEIGHT;
-                if y > bounds.height {
-                    break;
-                }
-
-                // Alternate lane backgrounds for depth
-                if lane % 2 == 1 {
-                    frame.fill_rectangle(
-                        Point::new(0.0, y),
-                        iced::Size::new(bounds.width, LANE_HEIGHT),
-                        theme::ZINC_900,
-                    );
-                }
-
-                let line = Path::line(Point::new(0.0, y), Point::new(bounds.width, y));
-                frame.stroke(
-                    &line,
-                    Stroke::default()
-                        .with_width(1.0)
-                        .with_color(theme::ZINC_800),
-                );
-
-                lane += 1;
-            }
-
-            // Vertical beat lines
-            let mut beat: u32 = 0;
-            loop {
-                let x = beat as f32 * PIXELS_PER_BEAT;
-                if x > bounds.width {
-                    break;
-                }
-
-                let is_bar = beat.is_multiple_of(BEATS_PER_BAR);
-                let (color, width) = if is_bar {
-                    (theme::ZINC_700, 1.0)
-                } else {
-                    (theme::ZINC_800, 1.0)
-                };
-
-                let line = Path::line(Point::new(x, 0.0), Point::new(x, bounds.height));
-                frame.stroke(&line, Stroke::default().with_width(width).with_color(color));
-
-                beat += 1;
-            }
+        let viewport = &self.grid.viewport;
+        let grid = self.grid.grid_cache.draw(renderer, bounds.size(), |frame| {
+            draw_grid(frame, viewport);
+            draw_gutter(frame, viewport);
         });
 
-        vec![grid]
+        let mut frame = Frame::new(renderer, bounds.size());
+        let mut selected = HashSet::new();
+
+        if let Some((selection, color)) = self.selection {
+            draw_selection(&mut frame, viewport, &selection, self.step, color);
+            selected.extend(selection.range(self.clip, self.step).notes);
+        }
+
+        let accent = self.selection.map(|(_, color)| color);
+        draw_notes(&mut frame, viewport, self.clip, &selected, accent);
+        draw_cursor(&mut frame, viewport, self.cursor, self.step);
+
+        vec![grid, frame.into_geometry()]
+    }
+}
+
+fn draw_grid(frame: &mut Frame, viewport: &Viewport) {
+    let size = frame.size();
+    let width = size.width - GUTTER_WIDTH;
+
+    // Background — zinc-950
+    frame.fill_rectangle(Point::ORIGIN, size, theme::ZINC_950);
+
+    // Lanes — black keys shaded, a divider under each
+    for pitch in viewport.visible_pitches(size.height) {
+        let y = viewport.y(pitch);
+
+        if viewport::is_black_key(pitch) {
+            frame.fill_rectangle(
+                Point::new(GUTTER_WIDTH, y),
+                Size::new(width, viewport.lane_height),
+                theme::ZINC_900,
+            );
+        }
+
+        // Octave boundaries sit under each C
+        let color = if pitch % 12 == 0 {
+            theme::ZINC_700
+        } else {
+            theme::ZINC_800
+        };
+        let bottom = y + viewport.lane_height;
+        let line = Path::line(
+            Point::new(GUTTER_WIDTH, bottom),
+            Point::new(size.width, bottom),
+        );
+        frame.stroke(&line, Stroke::default().with_width(1.0).with_color(color));
+    }
+
+    // Vertical beat lines, starting from the first beat in view
+    let mut beat = viewport.left.as_raw().div_ceil(TICKS_PER_QUARTER);
+    loop {
+        let x = GUTTER_WIDTH + viewport.x(Tick::from_quarters(beat));
+        if x > size.width {
+            break;
+        }
+
+        let is_bar = beat.is_multiple_of(BEATS_PER_BAR);
+        let color = if is_bar {
+            theme::ZINC_700
+        } else {
+            theme::ZINC_800
+        };
+
+        let line = Path::line(Point::new(x, 0.0), Point::new(x, size.height));
+        frame.stroke(&line, Stroke::default().with_width(1.0).with_color(color));
+
+        beat += 1;
     }
 }
+
+fn draw_gutter(frame: &mut Frame, viewport: &Viewport) {
+    let height = frame.size().height;
+
+    frame.fill_rectangle(
+        Point::ORIGIN,
+        Size::new(GUTTER_WIDTH, height),
+        theme::ZINC_200,
+    );
+
+    for pitch in viewport.visible_pitches(height) {
+        let y = viewport.y(pitch);
+
+        if viewport::is_black_key(pitch) {
+            frame.fill_rectangle(
+                Point::new(0.0, y),
+                Size::new(GUTTER_WIDTH * 0.6, viewport.lane_height),
+                theme::ZINC_950,
+            );
+        }
+
+        let line = Path::line(
+            Point::new(0.0, y + viewport.lane_height),
+            Point::new(GUTTER_WIDTH, y + viewport.lane_height),
+        );
+        frame.stroke(
+            &line,
+            Stroke::default()
+                .with_width(1.0)
+                .with_color(theme::ZINC_400),
+        );
+
+        if pitch % 12 == 0 || viewport.lane_height >= MIN_LABELED_LANE {
+            let size = (viewport.lane_height * 0.5).clamp(8.0, 11.0);
+            let color = if viewport::is_black_key(pitch) {
+                theme::ZINC_200
+            } else {
+                theme::ZINC_950
+            };
+
+            frame.fill_text(Text {
+                content: viewport::pitch_name(pitch),
+                position: Point::new(4.0, y + (viewport.lane_height - size) / 2.0),
+                color,
+                size: size.into(),
+                font: Font::MONOSPACE,
+                ..Text::default()
+            });
+        }
+    }
+
+    let edge = Path::line(
+        Point::new(GUTTER_WIDTH, 0.0),
+        Point::new(GUTTER_WIDTH, height),
+    );
+    frame.stroke(
+        &edge,
+        Stroke::default()
+            .with_width(1.0)
+            .with_color(theme::ZINC_700),
+    );
+}
+
+fn draw_selection(
+    frame: &mut Frame,
+    viewport: &Viewport,
+    selection: &Selection,
+    step: u64,
+    color: Color,
+) {
+    let (start, end) = selection.ticks(step);
+    let (low, high) = selection.pitches();
+
+    let left = (GUTTER_WIDTH + viewport.x(start)).max(GUTTER_WIDTH);
+    let right = GUTTER_WIDTH + viewport.x(end);
+    let top = viewport.y(high).max(0.0);
+    let bottom = (viewport.y(low) + viewport.lane_height).min(frame.size().height);
+
+    if right <= left || bottom <= top {
+        return;
+    }
+
+    frame.fill_rectangle(
+        Point::new(left, top),
+        Size::new(right - left, bottom - top),
+        Color { a: 0.12, ..color },
+    );
+}
+
+/// Notes in `selected` are drawn in `accent` instead of the usual blue.
+fn draw_notes(
+    frame: &mut Frame,
+    viewport: &Viewport,
+    clip: &Clip,
+    selected: &HashSet<NoteId>,
+    accent: Option<Color>,
+) {
+    let size = frame.size();
+    let pitches = viewport.visible_pitches(size.height);
+
+    for (id, note) in &clip.notes {
+        let pitch = u8::from(note.note);
+        let x = GUTTER_WIDTH + viewport.x(note.start_tick);
+        let width = note.length_ticks as f32 * viewport.pixels_per_tick();
+
+        if !pitches.contains(&pitch) || x + width < GUTTER_WIDTH || x > size.width {
+            continue;
+        }
+
+        // Clip at the gutter so notes scroll under the keyboard.
+        let left = x.max(GUTTER_WIDTH);
+        let rectangle = Path::rectangle(
+            Point::new(left, viewport.y(pitch) + 1.0),
+            Size::new(
+                (x + width - left - 1.0).max(1.0),
+                viewport.lane_height - 2.0,
+            ),
+        );
+
+        let color = accent
+            .filter(|_| selected.contains(id))
+            .unwrap_or(theme::BLUE_500);
+
+        frame.fill(&rectangle, velocity_color(color, u8::from(note.velocity)));
+        frame.stroke(
+            &rectangle,
+            Stroke::default().with_width(1.0).with_color(color),
+        );
+    }
+}
+
+/// Note fill: quiet notes fade into the background, loud ones are solid.
+fn velocity_color(color: Color, velocity: u8) -> Color {
+    let strength = velocity as f32 / 127.0;
+
+    Color {
+        a: 0.25 + 0.75 * strength,
+        ..color
+    }
+}
+
+fn draw_cursor(frame: &mut Frame, viewport: &Viewport, cursor: Cursor, step: u64) {
+    let x = GUTTER_WIDTH + viewport.x(cursor.tick);
+    let width = step as f32 * viewport.pixels_per_tick();
+
+    if x + width < GUTTER_WIDTH || x > frame.size().width {
+        return;
+    }
+
+    let rectangle = Path::rectangle(
+        Point::new(x, viewport.y(cursor.pitch)),
+        Size::new(width, viewport.lane_height),
+    );
+
+    frame.fill(
+        &rectangle,
+        Color {
+            a: 0.15,
+            ..theme::ZINC_50
+        },
+    );
+    frame.stroke(
+        &rectangle,
+        Stroke::default().with_width(1.5).with_color(theme::ZINC_50),
+    );
+}
@@ -0,0 +1,592 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::CommandError;
+
+/// Runs a command against its context once the arguments have been checked
+/// against the schema.
+pub type Handler<C> = fn(&mut C, &Args) -> Result<(), CommandError>;
+
+/// What an argument accepts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgKind {
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    Number {
+        min: f64,
+        max: f64,
+    },
+    /// Any single word. Quote it to include spaces.
+    Text,
+    /// One of a fixed set of words. Completion offers them.
+    Choice(&'static [&'static str]),
+    /// Every remaining word, joined by single spaces. Only valid last.
+    Rest,
+}
+
+impl ArgKind {
+    fn parse(&self, raw: &str) -> Option<Value> {
+        match *self {
+            ArgKind::Integer { min, max } => raw
+                .parse()
+                .ok()
+                .filter(|v| (min..=max).contains(v))
+                .map(Value::Integer),
+            ArgKind::Number { min, max } => raw
+                .parse()
+                .ok()
+                .filter(|v| (min..=max).contains(v))
+                .map(Value::Number),
+            ArgKind::Text | ArgKind::Rest => Some(Value::Text(raw.to_string())),
+            ArgKind::Choice(choices) => {
+                choices.contains(&raw).then(|| Value::Text(raw.to_string()))
+            }
+        }
+    }
+
+    fn expected(&self) -> String {
+        match self {
+            ArgKind::Integer { min, max } => format!("an integer from {min} to {max}"),
+            ArgKind::Number { min, max } => format!("a number from {min} to {max}"),
+            ArgKind::Text | ArgKind::Rest => "text".to_string(),
+            ArgKind::Choice(choices) => format!("one of {}", choices.join(", ")),
+        }
+    }
+}
+
+/// One positional argument in a command's schema. Optional arguments must
+/// come after the required ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub kind: ArgKind,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    Number(f64),
+    Text(String),
+}
+
+/// Arguments after checking against the schema, in schema order. Trailing
+/// optional arguments that weren't given are absent.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Args(Vec<Value>);
+
+impl Args {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn integer(&self, index: usize) -> Option<i64> {
+        match self.0.get(index)? {
+            Value::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self, index: usize) -> Option<f64> {
+        match self.0.get(index)? {
+            Value::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self, index: usize) -> Option<&str> {
+        match self.0.get(index)? {
+            Value::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// A named action: what it's called, what it does, what it takes, and the
+/// function that runs it.
+pub struct Command<C> {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub args: &'static [ArgSpec],
+    pub handler: Handler<C>,
+}
+
+// Derives would require `C: Clone`.
+impl<C> Clone for Command<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for Command<C> {}
+
+impl<C> fmt::Debug for Command<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Command")
+            .field("name", &self.name)
+            .field("args", &self.args)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C> Command<C> {
+    /// Synopsis for help, e.g. `track <action> [kind]`.
+    pub fn usage(&self) -> String {
+        let mut usage = self.name.to_string();
+
+        for arg in self.args {
+            let (open, close) = if arg.required { ('<', '>') } else { ('[', ']') };
+            usage.push_str(&format!(" {open}{}{close}", arg.name));
+        }
+
+        usage
+    }
+
+    /// Check raw arguments against the schema.
+    pub fn parse_args(&self, raw: &[String]) -> Result<Args, CommandError> {
+        let rest = self
+            .args
+            .last()
+            .is_some_and(|spec| spec.kind == ArgKind::Rest);
+
+        if raw.len() > self.args.len() && !rest {
+            return Err(CommandError::TooManyArguments {
+                command: self.name,
+                usage: self.usage(),
+            });
+        }
+
+        let mut values = Vec::with_capacity(raw.len());
+
+        for (index, spec) in self.args.iter().enumerate() {
+            let Some(word) = raw.get(index) else {
+                if spec.required {
+                    return Err(CommandError::MissingArgument {
+                        command: self.name,
+                        argument: spec.name,
+                    });
+                }
+
+                break;
+            };
+
+            if spec.kind == ArgKind::Rest {
+                values.push(Value::Text(raw[index..].join(" ")));
+                break;
+            }
+
+            let value = spec
+                .kind
+                .parse(word)
+                .ok_or_else(|| CommandError::InvalidArgument {
+                    command: self.name,
+                    argument: spec.name,
+                    expected: spec.kind.expected(),
+                    value: word.clone(),
+                })?;
+
+            values.push(value);
+        }
+
+        Ok(Args(values))
+    }
+}
+
+/// A command call by name with unchecked arguments, as typed on the command
+/// line, bound to a key or written in a script. Plain data, so it can be
+/// stored and replayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+impl Invocation {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Split a line into a name and arguments on whitespace. Double quotes
+    /// group words into one argument. A leading `:` is ignored.
+    pub fn parse(line: &str) -> Result<Self, CommandError> {
+        let line = line.trim();
+        let line = line.strip_prefix(':').unwrap_or(line);
+
+        let mut words = Vec::new();
+        let mut word = String::new();
+        let mut quoted = false;
+        let mut started = false;
+
+        for char in line.chars() {
+            match char {
+                '"' => {
+                    quoted = !quoted;
+                    started = true;
+                }
+                c if c.is_whitespace() && !quoted => {
+                    if started {
+                        words.push(std::mem::take(&mut word));
+                        started = false;
+                    }
+                }
+                c => {
+                    word.push(c);
+                    started = true;
+                }
+            }
+        }
+
+        if quoted {
+            return Err(CommandError::UnterminatedQuote);
+        }
+
+        if started {
+            words.push(word);
+        }
+
+        let mut words = words.into_iter();
+        let name = words.next().ok_or(CommandError::Empty)?;
+
+        Ok(Self {
+            name,
+            args: words.collect(),
+        })
+    }
+}
+
+impl FromStr for Invocation {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Invocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+
+        for arg in &self.args {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                write!(f, " \"{arg}\"")?;
+            } else {
+                write!(f, " {arg}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Every command available against a context `C`, by name. All input —
+/// keys, the command line and scripts — runs through `dispatch`.
+pub struct Registry<C> {
+    commands: BTreeMap<&'static str, Command<C>>,
+}
+
+impl<C> Default for Registry<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> Registry<C> {
+    pub fn new() -> Self {
+        Self {
+            commands: BTreeMap::new(),
+        }
+    }
+
+    /// Add a command. Names are unique; registering one twice is a bug in
+    /// the command table.
+    pub fn register(&mut self, command: Command<C>) {
+        let previous = self.commands.insert(command.name, command);
+
+        assert!(
+            previous.is_none(),
+            "command registered twice: {}",
+            command.name
+        );
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Command<C>> {
+        self.commands.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.commands.contains_key(name)
+    }
+
+    /// All commands, alphabetically.
+    pub fn iter(&self) -> impl Iterator<Item = &Command<C>> {
+        self.commands.values()
+    }
+
+    /// Commands whose names start with `prefix`, alphabetically.
+    pub fn complete<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a Command<C>> {
+        self.commands
+            .range(prefix..)
+            .take_while(move |(name, _)| name.starts_with(prefix))
+            .map(|(_, command)| command)
+    }
+
+    /// Choices for argument `index` of `name` that start with `prefix`.
+    /// Empty unless that argument is a `Choice`.
+    pub fn complete_argument(&self, name: &str, index: usize, prefix: &str) -> Vec<&'static str> {
+        let Some(ArgKind::Choice(choices)) = self
+            .get(name)
+            .and_then(|command| command.args.get(index))
+            .map(|spec| spec.kind)
+        else {
+            return Vec::new();
+        };
+
+        choices
+            .iter()
+            .copied()
+            .filter(|choice| choice.starts_with(prefix))
+            .collect()
+    }
+
+    /// Look up, check and run an invocation.
+    pub fn dispatch(&self, context: &mut C, invocation: &Invocation) -> Result<(), CommandError> {
+        let command = self
+            .get(&invocation.name)
+            .ok_or_else(|| CommandError::UnknownCommand(invocation.name.clone()))?;
+
+        let args = command.parse_args(&invocation.args)?;
+
+        (command.handler)(context, &args)
+    }
+
+    /// Run one command per line, stopping at the first failure. Blank lines
+    /// and lines starting with `#` are skipped.
+    pub fn run_script(&self, context: &mut C, source: &str) -> Result<(), CommandError> {
+        for (index, line) in source.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            Invocation::parse(line)
+                .and_then(|invocation| self.dispatch(context, &invocation))
+                .map_err(|error| CommandError::Script {
+                    line: index + 1,
+                    error: Box::new(error),
+                })?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Counter {
+        value: i64,
+    }
+
+    fn add(counter: &mut Counter, args: &Args) -> Result<(), CommandError> {
+        counter.value += args.integer(0).unwrap_or(1);
+        Ok(())
+    }
+
+    fn reset(counter: &mut Counter, _args: &Args) -> Result<(), CommandError> {
+        if counter.value == 0 {
+            return Err(CommandError::Failed("already zero".to_string()));
+        }
+
+        counter.value = 0;
+        Ok(())
+    }
+
+    fn registry() -> Registry<Counter> {
+        let mut registry = Registry::new();
+
+        registry.register(Command {
+            name: "add",
+            description: "Add to the counter",
+            args: &[ArgSpec {
+                name: "amount",
+                kind: ArgKind::Integer { min: -10, max: 10 },
+                required: false,
+            }],
+            handler: add,
+        });
+        registry.register(Command {
+            name: "reset",
+            description: "Zero the counter",
+            args: &[ArgSpec {
+                name: "how",
+                kind: ArgKind::Choice(&["hard", "soft"]),
+                required: false,
+            }],
+            handler: reset,
+        });
+
+        registry
+    }
+
+    #[test]
+    fn parse_splits_words_and_quotes() {
+        let invocation = Invocation::parse(r#":w  "my song.motif" now"#).unwrap();
+
+        assert_eq!(invocation.name, "w");
+        assert_eq!(invocation.args, ["my song.motif", "now"]);
+        assert_eq!(invocation.to_string(), r#"w "my song.motif" now"#);
+    }
+
+    #[test]
+    fn parse_rejects_empty_and_unterminated() {
+        assert_eq!(Invocation::parse("  :"), Err(CommandError::Empty));
+        assert_eq!(
+            Invocation::parse(r#"w "song"#),
+            Err(CommandError::UnterminatedQuote)
+        );
+    }
+
+    #[test]
+    fn dispatch_checks_schema_then_runs() {
+        let registry = registry();
+        let mut counter = Counter::default();
+
+        registry
+            .dispatch(&mut counter, &Invocation::new("add").arg("5"))
+            .unwrap();
+        registry
+            .dispatch(&mut counter, &Invocation::new("add"))
+            .unwrap();
+
+        assert_eq!(counter.value, 6);
+    }
+
+    #[test]
+    fn dispatch_reports_bad_input() {
+        let registry = registry();
+        let mut counter = Counter::default();
+
+        assert_eq!(
+            registry.dispatch(&mut counter, &Invocation::new("nope")),
+            Err(CommandError::UnknownCommand("nope".to_string()))
+        );
+        assert!(matches!(
+            registry.dispatch(&mut counter, &Invocation::new("add").arg("11")),
+            Err(CommandError::InvalidArgument {
+                argument: "amount",
+                ..
+            })
+        ));
+        assert!(matches!(
+            registry.dispatch(&mut counter, &Invocation::new("add").arg("1").arg("2")),
+            Err(CommandError::TooManyArguments { .. })
+        ));
+        assert_eq!(counter.value, 0);
+    }
+
+    #[test]
+    fn missing_required_argument() {
+        let command: Command<Counter> = Command {
+            name: "bpm",
+            description: "",
+            args: &[ArgSpec {
+                name: "tempo",
+                kind: ArgKind::Number {
+                    min: 20.0,
+                    max: 300.0,
+                },
+                required: true,
+            }],
+            handler: add,
+        };
+
+        assert_eq!(
+            command.parse_args(&[]),
+            Err(CommandError::MissingArgument {
+                command: "bpm",
+                argument: "tempo"
+            })
+        );
+        assert_eq!(command.usage(), "bpm <tempo>");
+    }
+
+    #[test]
+    fn rest_takes_remaining_words() {
+        let command: Command<Counter> = Command {
+            name: "map",
+            description: "",
+            args: &[
+                ArgSpec {
+                    name: "keys",
+                    kind: ArgKind::Text,
+                    required: true,
+                },
+                ArgSpec {
+                    name: "action",
+                    kind: ArgKind::Rest,
+                    required: true,
+                },
+            ],
+            handler: add,
+        };
+        let raw = ["gg", ":velocity", "100"].map(String::from);
+
+        let args = command.parse_args(&raw).unwrap();
+
+        assert_eq!(args.text(0), Some("gg"));
+        assert_eq!(args.text(1), Some(":velocity 100"));
+    }
+
+    #[test]
+    fn completion_by_prefix() {
+        let registry = registry();
+
+        let names: Vec<_> = registry.complete("re").map(|c| c.name).collect();
+        assert_eq!(names, ["reset"]);
+
+        let all: Vec<_> = registry.complete("").map(|c| c.name).collect();
+        assert_eq!(all, ["add", "reset"]);
+
+        assert_eq!(registry.complete_argument("reset", 0, "h"), ["hard"]);
+        assert!(registry.complete_argument("add", 0, "").is_empty());
+    }
+
+    #[test]
+    fn script_stops_at_first_error_with_line() {
+        let registry = registry();
+        let mut counter = Counter::default();
+        let script = "# warm up\nadd 2\n\nadd 3\nbogus\nadd 4\n";
+
+        let error = registry.run_script(&mut counter, script).unwrap_err();
+
+        assert_eq!(counter.value, 5);
+        assert_eq!(error.to_string(), "Line 5: Unknown command: bogus");
+    }
+
+    #[test]
+    #[should_panic(expected = "registered twice")]
+    fn duplicate_names_panic() {
+        let mut registry = registry();
+        let add = *registry.get("add").unwrap();
+
+        registry.register(add);
+    }
+}
@@ -0,0 +1,411 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use iced::keyboard::key::Named;
+
+use crate::command::Registry;
+use crate::keymap::{Chord, KeyCode};
+
+/// Lines kept in the history file.
+pub const HISTORY_LIMIT: usize = 500;
+
+/// Lines entered on the command line, oldest first, without repeats.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct History {
+    entries: Vec<String>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// One entry per line; blank lines are skipped.
+    pub fn from_text(text: &str) -> Self {
+        let mut history = Self::new();
+
+        for line in text.lines() {
+            history.push(line);
+        }
+
+        history
+    }
+
+    pub fn to_text(&self) -> String {
+        self.entries.iter().fold(String::new(), |mut text, entry| {
+            text.push_str(entry);
+            text.push('\n');
+            text
+        })
+    }
+
+    /// A missing file is an empty history.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(Self::from_text(&text)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, self.to_text())
+    }
+
+    /// Record a line as the newest entry, moving it up if it was already
+    /// there and dropping the oldest past `HISTORY_LIMIT`.
+    pub fn push(&mut self, line: &str) {
+        let line = line.trim();
+
+        if line.is_empty() {
+            return;
+        }
+
+        self.entries.retain(|entry| entry != line);
+        self.entries.push(line.to_string());
+
+        let excess = self.entries.len().saturating_sub(HISTORY_LIMIT);
+        self.entries.drain(..excess);
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+}
+
+/// What a key did to the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Editing,
+    Cancel,
+    Submit(String),
+}
+
+/// The `:` prompt while it is open. Edits happen at the end of the line.
+#[derive(Debug, Clone, Default)]
+pub struct CommandLine {
+    text: String,
+    /// History entry shown by Up/Down, with the line typed before browsing.
+    browsing: Option<(usize, String)>,
+    completion: Option<Completion>,
+}
+
+/// Tab cycles through `candidates`, each replacing the last word.
+#[derive(Debug, Clone)]
+struct Completion {
+    stem: String,
+    candidates: Vec<String>,
+    index: usize,
+}
+
+impl CommandLine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn key<C>(&mut self, chord: &Chord, registry: &Registry<C>, history: &History) -> Outcome {
+        if chord.key != KeyCode::Named(Named::Tab) {
+            self.completion = None;
+        }
+
+        if !matches!(chord.key, KeyCode::Named(Named::ArrowUp | Named::ArrowDown)) {
+            self.browsing = None;
+        }
+
+        if chord.ctrl {
+            match chord.key {
+                KeyCode::Char('u') => self.text.clear(),
+                KeyCode::Char('w') => self.delete_word(),
+                KeyCode::Char('c') => return Outcome::Cancel,
+                _ => {}
+            }
+
+            return Outcome::Editing;
+        }
+
+        if let Some(c) = chord.as_char() {
+            self.text.push(c);
+            return Outcome::Editing;
+        }
+
+        match chord.key {
+            KeyCode::Named(Named::Enter) => return Outcome::Submit(std::mem::take(&mut self.text)),
+            KeyCode::Named(Named::Escape) => return Outcome::Cancel,
+            KeyCode::Named(Named::Backspace) if self.text.is_empty() => return Outcome::Cancel,
+            KeyCode::Named(Named::Backspace) => {
+                self.text.pop();
+            }
+            KeyCode::Named(Named::Space) => self.text.push(' '),
+            KeyCode::Named(Named::Tab) => self.complete(registry, !chord.shift),
+            KeyCode::Named(Named::ArrowUp) => self.browse(history, true),
+            KeyCode::Named(Named::ArrowDown) => self.browse(history, false),
+            _ => {}
+        }
+
+        Outcome::Editing
+    }
+
+    fn delete_word(&mut self) {
+        let kept = self.text.trim_end().len();
+        self.text.truncate(kept);
+
+        let start = self
+            .text
+            .rfind(char::is_whitespace)
+            .map_or(0, |index| index + 1);
+        self.text.truncate(start);
+    }
+
+    /// Replace the last word with the next (or previous) candidate. A
+    /// single candidate is accepted outright, with a space after it.
+    fn complete<C>(&mut self, registry: &Registry<C>, forward: bool) {
+        if let Some(completion) = &mut self.completion {
+            let count = completion.candidates.len();
+            completion.index = if forward {
+                (completion.index + 1) % count
+            } else {
+                (completion.index + count - 1) % count
+            };
+            self.text = format!(
+                "{}{}",
+                completion.stem, completion.candidates[completion.index]
+            );
+            return;
+        }
+
+        let (stem, candidates) = candidates(registry, &self.text);
+
+        match candidates.as_slice() {
+            [] => {}
+            [only] => self.text = format!("{stem}{only} "),
+            _ => {
+                let index = if forward { 0 } else { candidates.len() - 1 };
+                self.text = format!("{stem}{}", candidates[index]);
+                self.completion = Some(Completion {
+                    stem,
+                    candidates,
+                    index,
+                });
+            }
+        }
+    }
+
+    /// Step through history entries that start with what was typed
+    /// before browsing began.
+    fn browse(&mut self, history: &History, older: bool) {
+        let entries = history.entries();
+        let (from, draft) = match self.browsing.take() {
+            Some(browsing) => browsing,
+            None => (entries.len(), self.text.clone()),
+        };
+
+        let matches = |index: &usize| entries[*index].starts_with(&draft);
+        let next = if older {
+            (0..from).rev().find(matches)
+        } else {
+            (from + 1..entries.len()).find(matches)
+        };
+
+        match next {
+            Some(index) => {
+                self.text = entries[index].clone();
+                self.browsing = Some((index, draft));
+            }
+            // Past the newest entry: back to what was typed.
+            None if !older => self.text = draft,
+            None => self.browsing = Some((from, draft)),
+        }
+    }
+}
+
+/// Completions for the last word of `line`: command names for the first
+/// word, choices for later ones. Returns the text before that word too.
+pub fn candidates<C>(registry: &Registry<C>, line: &str) -> (String, Vec<String>) {
+    let start = line.rfind(char::is_whitespace).map_or(0, |index| index + 1);
+    let (stem, prefix) = line.split_at(start);
+    let words: Vec<&str> = stem.split_whitespace().collect();
+
+    let candidates = match words.split_first() {
+        None => registry
+            .complete(prefix)
+            .map(|command| command.name.to_string())
+            .collect(),
+        Some((name, args)) => registry
+            .complete_argument(name, args.len(), prefix)
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+    };
+
+    (stem.to_string(), candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{ArgKind, ArgSpec, Args, Command};
+    use crate::error::CommandError;
+
+    fn noop(_: &mut (), _: &Args) -> Result<(), CommandError> {
+        Ok(())
+    }
+
+    fn registry() -> Registry<()> {
+        let mut registry = Registry::new();
+
+        for name in ["track", "tempo", "write"] {
+            registry.register(Command {
+                name,
+                description: "",
+                args: &[ArgSpec {
+                    name: "action",
+                    kind: ArgKind::Choice(&["new", "next", "prev"]),
+                    required: false,
+                }],
+                handler: noop,
+            });
+        }
+
+        registry
+    }
+
+    fn type_line(line: &mut CommandLine, text: &str) {
+        for c in text.chars() {
+            line.key(&Chord::char(c), &registry(), &History::new());
+        }
+    }
+
+    fn press(line: &mut CommandLine, named: Named, history: &History) -> Outcome {
+        line.key(&Chord::named(named), &registry(), history)
+    }
+
+    #[test]
+    fn enter_submits_typed_text() {
+        let mut line = CommandLine::new();
+        type_line(&mut line, "bpm");
+        press(&mut line, Named::Space, &History::new());
+        type_line(&mut line, "140");
+
+        let outcome = press(&mut line, Named::Enter, &History::new());
+
+        assert_eq!(outcome, Outcome::Submit("bpm 140".to_string()));
+    }
+
+    #[test]
+    fn backspace_on_empty_line_cancels() {
+        let mut line = CommandLine::new();
+        type_line(&mut line, "w");
+
+        assert_eq!(
+            press(&mut line, Named::Backspace, &History::new()),
+            Outcome::Editing
+        );
+        assert_eq!(
+            press(&mut line, Named::Backspace, &History::new()),
+            Outcome::Cancel
+        );
+    }
+
+    #[test]
+    fn ctrl_w_deletes_last_word() {
+        let mut line = CommandLine::new();
+        type_line(&mut line, "track new ");
+
+        line.key(
+            &Chord::new(KeyCode::Char('w'), true, false, false, false),
+            &registry(),
+            &History::new(),
+        );
+
+        assert_eq!(line.text(), "track ");
+    }
+
+    #[test]
+    fn candidates_complete_names_then_choices() {
+        let registry = registry();
+
+        assert_eq!(
+            candidates(&registry, "t"),
+            (
+                String::new(),
+                vec!["tempo".to_string(), "track".to_string()]
+            )
+        );
+        assert_eq!(
+            candidates(&registry, "track ne"),
+            (
+                "track ".to_string(),
+                vec!["new".to_string(), "next".to_string()]
+            )
+        );
+        assert_eq!(candidates(&registry, "track new x").1, Vec::<String>::new());
+    }
+
+    #[test]
+    fn tab_accepts_single_candidate_and_cycles_several() {
+        let history = History::new();
+        let mut line = CommandLine::new();
+        type_line(&mut line, "wr");
+        press(&mut line, Named::Tab, &history);
+        assert_eq!(line.text(), "write ");
+
+        let mut line = CommandLine::new();
+        type_line(&mut line, "t");
+        press(&mut line, Named::Tab, &history);
+        assert_eq!(line.text(), "tempo");
+        press(&mut line, Named::Tab, &history);
+        assert_eq!(line.text(), "track");
+        press(&mut line, Named::Tab, &history);
+        assert_eq!(line.text(), "tempo");
+    }
+
+    #[test]
+    fn history_browses_matching_entries() {
+        let history = History::from_text("bpm 120\nw song.motif\nbpm 140\n");
+        let mut line = CommandLine::new();
+        type_line(&mut line, "bpm");
+
+        press(&mut line, Named::ArrowUp, &history);
+        assert_eq!(line.text(), "bpm 140");
+        press(&mut line, Named::ArrowUp, &history);
+        assert_eq!(line.text(), "bpm 120");
+        press(&mut line, Named::ArrowUp, &history);
+        assert_eq!(line.text(), "bpm 120");
+        press(&mut line, Named::ArrowDown, &history);
+        assert_eq!(line.text(), "bpm 140");
+        press(&mut line, Named::ArrowDown, &history);
+        assert_eq!(line.text(), "bpm");
+    }
+
+    #[test]
+    fn history_push_moves_repeats_to_the_end() {
+        let mut history = History::from_text("a\nb\n\nc\n");
+
+        history.push("a");
+        history.push("  ");
+
+        assert_eq!(history.entries(), ["b", "c", "a"]);
+        assert_eq!(History::from_text(&history.to_text()), history);
+    }
+
+    #[test]
+    fn history_keeps_newest_entries_within_limit() {
+        let mut history = History::new();
+
+        for index in 0..HISTORY_LIMIT + 5 {
+            history.push(&index.to_string());
+        }
+
+        assert_eq!(history.entries().len(), HISTORY_LIMIT);
+        assert_eq!(history.entries()[0], "5");
+    }
+}
@@ -0,0 +1,901 @@
+use std::path::{Path, PathBuf};
+
+use motif_core::project::{MAX_BPM, MIN_BPM};
+use wmidi::Velocity;
+
+use crate::app::{App, Mode};
+use crate::command::{ArgKind, ArgSpec, Args, Command, Registry};
+use crate::editor::{Motion, Operator, TextObject};
+use crate::error::{CommandError, KeymapError};
+use crate::keymap::Action;
+use crate::options;
+use crate::paths;
+
+/// `Mode::name` for every mode, for arguments that take one.
+const MODE_NAMES: &[&str] = &[
+    "normal",
+    "play",
+    "step",
+    "operator",
+    "visual",
+    "visual-block",
+];
+
+/// Instruments a track can be created with.
+const INSTRUMENTS: &[&str] = &["drums", "fm", "pluck", "pulse", "sampler", "wavetable"];
+
+/// Velocity change per `:velocity-step`.
+const VELOCITY_STEP: i32 = 8;
+
+/// Zoom factor per `:zoom` step.
+const ZOOM_STEP: f32 = 1.25;
+
+const WRITE_ARGS: &[ArgSpec] = &[ArgSpec {
+    name: "path",
+    kind: ArgKind::Text,
+    required: false,
+}];
+
+const MAP_ARGS: &[ArgSpec] = &[
+    ArgSpec {
+        name: "mode",
+        kind: ArgKind::Choice(MODE_NAMES),
+        required: true,
+    },
+    ArgSpec {
+        name: "keys",
+        kind: ArgKind::Text,
+        required: true,
+    },
+    ArgSpec {
+        name: "action",
+        kind: ArgKind::Rest,
+        required: true,
+    },
+];
+
+/// The built-in command table. Every user action is one of these, reached
+/// by name.
+pub fn registry() -> Registry<App> {
+    let mut registry = Registry::new();
+
+    for command in [
+        Command {
+            name: "bpm",
+            description: "Set the project tempo in beats per minute",
+            args: &[ArgSpec {
+                name: "tempo",
+                kind: ArgKind::Number {
+                    min: MIN_BPM,
+                    max: MAX_BPM,
+                },
+                required: true,
+            }],
+            handler: bpm,
+        },
+        Command {
+            name: "command-line",
+            description: "Open the : prompt to type a command",
+            args: &[],
+            handler: command_line,
+        },
+        Command {
+            name: "help",
+            description: "List commands, or describe one",
+            args: &[ArgSpec {
+                name: "command",
+                kind: ArgKind::Text,
+                required: false,
+            }],
+            handler: help,
+        },
+        Command {
+            name: "keymap-load",
+            description: "Replace the keymap with a file, or the user's keymap by default",
+            args: &[ArgSpec {
+                name: "path",
+                kind: ArgKind::Text,
+                required: false,
+            }],
+            handler: keymap_load,
+        },
+        Command {
+            name: "map",
+            description: "Bind keys to a :command or to keys, expanding mappings in them",
+            args: MAP_ARGS,
+            handler: map,
+        },
+        Command {
+            name: "move",
+            description: "Move the cursor, or mark what a pending operator acts on",
+            args: &[ArgSpec {
+                name: "motion",
+                kind: ArgKind::Choice(Motion::NAMES),
+                required: true,
+            }],
+            handler: move_cursor,
+        },
+        Command {
+            name: "noremap",
+            description: "Bind keys to a :command or to keys, without expanding mappings in them",
+            args: MAP_ARGS,
+            handler: noremap,
+        },
+        Command {
+            name: "normal",
+            description: "Return to Normal mode, releasing held notes",
+            args: &[],
+            handler: normal,
+        },
+        Command {
+            name: "object",
+            description: "Apply the pending operator to a group of notes (times the count)",
+            args: &[ArgSpec {
+                name: "object",
+                kind: ArgKind::Choice(TextObject::NAMES),
+                required: true,
+            }],
+            handler: object,
+        },
+        Command {
+            name: "octave",
+            description: "Shift the piano keys an octave (times the count), or back to their layout",
+            args: &[ArgSpec {
+                name: "direction",
+                kind: ArgKind::Choice(&["down", "reset", "up"]),
+                required: true,
+            }],
+            handler: octave,
+        },
+        Command {
+            name: "operator",
+            description: "Start an operator, applied by the next motion or object",
+            args: &[ArgSpec {
+                name: "operator",
+                kind: ArgKind::Choice(Operator::NAMES),
+                required: true,
+            }],
+            handler: operator,
+        },
+        Command {
+            name: "other-end",
+            description: "Move the cursor to the other end of the selection",
+            args: &[],
+            handler: other_end,
+        },
+        Command {
+            name: "paste",
+            description: "Paste a register at the cursor, count times, and move past it",
+            args: &[],
+            handler: paste,
+        },
+        Command {
+            name: "paste-before",
+            description: "Paste a register at the cursor, count times, leaving the cursor",
+            args: &[],
+            handler: paste_before,
+        },
+        Command {
+            name: "play",
+            description: "Enter Play mode, where the keyboard plays the instrument",
+            args: &[],
+            handler: play,
+        },
+        Command {
+            name: "preset-next",
+            description: "Switch to the next preset, wrapping at the end",
+            args: &[],
+            handler: preset_next,
+        },
+        Command {
+            name: "preset-prev",
+            description: "Switch to the previous preset, wrapping at the start",
+            args: &[],
+            handler: preset_prev,
+        },
+        Command {
+            name: "quantize",
+            description: "Snap note starts to the grid, over a motion or the selection",
+            args: &[],
+            handler: quantize,
+        },
+        Command {
+            name: "record",
+            description: "Record commands into a macro, or stop recording; the next key names it if not given",
+            args: &[ArgSpec {
+                name: "name",
+                kind: ArgKind::Text,
+                required: false,
+            }],
+            handler: record,
+        },
+        Command {
+            name: "redo",
+            description: "Redo the last undone edit",
+            args: &[],
+            handler: redo,
+        },
+        Command {
+            name: "register",
+            description: "Use a register for the next delete, yank or paste; the next key names it if not given",
+            args: &[ArgSpec {
+                name: "name",
+                kind: ArgKind::Text,
+                required: false,
+            }],
+            handler: register,
+        },
+        Command {
+            name: "registers",
+            description: "Show which registers hold notes",
+            args: &[],
+            handler: registers,
+        },
+        Command {
+            name: "repeat",
+            description: "Repeat the last change at the cursor, count times",
+            args: &[],
+            handler: repeat,
+        },
+        Command {
+            name: "replay",
+            description: "Replay a macro count times, @ for the last one; the next key names it if not given",
+            args: &[ArgSpec {
+                name: "name",
+                kind: ArgKind::Text,
+                required: false,
+            }],
+            handler: replay,
+        },
+        Command {
+            name: "scroll",
+            description: "Scroll the piano roll by bars (left, right) or semitones (up, down)",
+            args: &[
+                ArgSpec {
+                    name: "direction",
+                    kind: ArgKind::Choice(&["down", "left", "right", "up"]),
+                    required: true,
+                },
+                ArgSpec {
+                    name: "amount",
+                    kind: ArgKind::Integer { min: 1, max: 128 },
+                    required: false,
+                },
+            ],
+            handler: scroll,
+        },
+        Command {
+            name: "semitone",
+            description: "Shift the piano keys a semitone (times the count)",
+            args: &[ArgSpec {
+                name: "direction",
+                kind: ArgKind::Choice(&["down", "up"]),
+                required: true,
+            }],
+            handler: semitone,
+        },
+        Command {
+            name: "set",
+            description: "Change an option with name=value, or show options",
+            args: &[ArgSpec {
+                name: "option",
+                kind: ArgKind::Text,
+                required: false,
+            }],
+            handler: set,
+        },
+        Command {
+            name: "source",
+            description: "Run each line of a file as a command",
+            args: &[ArgSpec {
+                name: "path",
+                kind: ArgKind::Text,
+                required: true,
+            }],
+            handler: source,
+        },
+        Command {
+            name: "step",
+            description: "Enter Step mode, where piano keys write notes at the cursor",
+            args: &[],
+            handler: step,
+        },
+        Command {
+            name: "step-note",
+            description: "Write a note at the cursor and move on a step, or add it to the last chord",
+            args: &[
+                ArgSpec {
+                    name: "pitch",
+                    kind: ArgKind::Integer { min: 0, max: 127 },
+                    required: true,
+                },
+                ArgSpec {
+                    name: "chord",
+                    kind: ArgKind::Choice(&["chord"]),
+                    required: false,
+                },
+            ],
+            handler: step_note,
+        },
+        Command {
+            name: "step-rest",
+            description: "Move the cursor on a step without writing a note",
+            args: &[],
+            handler: step_rest,
+        },
+        Command {
+            name: "step-tie",
+            description: "Hold the last written chord for another step",
+            args: &[],
+            handler: step_tie,
+        },
+        Command {
+            name: "track",
+            description: "Add a track with an instrument, or switch tracks",
+            args: &[
+                ArgSpec {
+                    name: "action",
+                    kind: ArgKind::Choice(&["new", "next", "prev"]),
+                    required: true,
+                },
+                ArgSpec {
+                    name: "instrument",
+                    kind: ArgKind::Choice(INSTRUMENTS),
+                    required: false,
+                },
+            ],
+            handler: track,
+        },
+        Command {
+            name: "transpose",
+            description: "Move notes by semitones (times the count), over a motion or the selection",
+            args: &[ArgSpec {
+                name: "semitones",
+                kind: ArgKind::Integer {
+                    min: -127,
+                    max: 127,
+                },
+                required: true,
+            }],
+            handler: transpose,
+        },
+        Command {
+            name: "undo",
+            description: "Undo the last edit",
+            args: &[],
+            handler: undo,
+        },
+        Command {
+            name: "unmap",
+            description: "Remove a key binding",
+            args: &[
+                ArgSpec {
+                    name: "mode",
+                    kind: ArgKind::Choice(MODE_NAMES),
+                    required: true,
+                },
+                ArgSpec {
+                    name: "keys",
+                    kind: ArgKind::Text,
+                    required: true,
+                },
+            ],
+            handler: unmap,
+        },
+        Command {
+            name: "velocity",
+            description: "Set the velocity of notes played in Play mode",
+            args: &[ArgSpec {
+                name: "level",
+                kind: ArgKind::Integer { min: 1, max: 127 },
+                required: true,
+            }],
+            handler: velocity,
+        },
+        Command {
+            name: "velocity-scale",
+            description: "Scale note velocities by a percentage, over a motion or the selection",
+            args: &[ArgSpec {
+                name: "percent",
+                kind: ArgKind::Integer { min: 0, max: 1000 },
+                required: true,
+            }],
+            handler: velocity_scale,
+        },
+        Command {
+            name: "velocity-step",
+            description: "Raise or lower the Play mode velocity (times the count)",
+            args: &[ArgSpec {
+                name: "direction",
+                kind: ArgKind::Choice(&["down", "up"]),
+                required: true,
+            }],
+            handler: velocity_step,
+        },
+        Command {
+            name: "visual",
+            description: "Toggle Visual mode, selecting a time range",
+            args: &[],
+            handler: visual,
+        },
+        Command {
+            name: "visual-block",
+            description: "Toggle Visual Block mode, selecting time and pitch",
+            args: &[],
+            handler: visual_block,
+        },
+        Command {
+            name: "w",
+            description: "Save the project, to a new path if given",
+            args: WRITE_ARGS,
+            handler: write,
+        },
+        Command {
+            name: "write",
+            description: "Save the project, to a new path if given",
+            args: WRITE_ARGS,
+            handler: write,
+        },
+        Command {
+            name: "zoom",
+            description: "Zoom the piano roll in time, or in pitch",
+            args: &[
+                ArgSpec {
+                    name: "direction",
+                    kind: ArgKind::Choice(&["in", "out"]),
+                    required: true,
+                },
+                ArgSpec {
+                    name: "axis",
+                    kind: ArgKind::Choice(&["pitch", "time"]),
+                    required: false,
+                },
+            ],
+            handler: zoom,
+        },
+    ] {
+        registry.register(command);
+    }
+
+    registry
+}
+
+fn failed(error: KeymapError) -> CommandError {
+    CommandError::Failed(error.to_string())
+}
+
+/// The optional single-character register argument of `command`.
+fn register_name(command: &'static str, args: &Args) -> Result<Option<char>, CommandError> {
+    let Some(text) = args.text(0) else {
+        return Ok(None);
+    };
+
+    let mut chars = text.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(name), None) => Ok(Some(name)),
+        _ => Err(CommandError::InvalidArgument {
+            command,
+            argument: "name",
+            expected: "one character".to_string(),
+            value: text.to_string(),
+        }),
+    }
+}
+
+fn bpm(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    // UNWRAP SAFETY: the schema requires a tempo within MIN_BPM–MAX_BPM.
+    app.project_mut().bpm = args.number(0).unwrap();
+    Ok(())
+}
+
+fn command_line(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    app.open_command_line();
+    Ok(())
+}
+
+fn help(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    let commands = app.commands();
+
+    let text = match args.text(0) {
+        Some(name) => {
+            let command = commands
+                .get(name)
+                .ok_or_else(|| CommandError::UnknownCommand(name.to_string()))?;
+
+            format!("{} — {}", command.usage(), command.description)
+        }
+        None => {
+            let names: Vec<_> = commands.iter().map(|command| command.name).collect();
+            names.join(" ")
+        }
+    };
+
+    app.show_message(text);
+    Ok(())
+}
+
+fn keymap_load(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    let path: PathBuf = match args.text(0) {
+        Some(path) => path.into(),
+        None => paths::keymap_file()
+            .ok_or_else(|| CommandError::Failed("No config directory".to_string()))?,
+    };
+
+    *app.keymap_mut() = App::read_keymap(&path).map_err(failed)?;
+
+    Ok(())
+}
+
+fn map(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    bind(app, args, true)
+}
+
+fn noremap(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    bind(app, args, false)
+}
+
+fn bind(app: &mut App, args: &Args, remap: bool) -> Result<(), CommandError> {
+    // UNWRAP SAFETY: the schema requires a mode from `MODE_NAMES`, keys and
+    // an action.
+    let mode = Mode::from_name(args.text(0).unwrap()).unwrap();
+    let keys = app
+        .keymap()
+        .parse_keys(args.text(1).unwrap())
+        .map_err(failed)?;
+    let action =
+        Action::parse(args.text(2).unwrap(), remap, app.keymap().leader()).map_err(failed)?;
+
+    if let Action::Command(invocation) = &action {
+        let commands = app.commands();
+        let command = commands
+            .get(&invocation.name)
+            .ok_or_else(|| CommandError::UnknownCommand(invocation.name.clone()))?;
+
+        command.parse_args(&invocation.args)?;
+    }
+
+    app.keymap_mut().bind(mode, keys, action).map_err(failed)
+}
+
+fn unmap(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    // UNWRAP SAFETY: the schema requires a mode from `MODE_NAMES` and keys.
+    let mode = Mode::from_name(args.text(0).unwrap()).unwrap();
+    let keys = app
+        .keymap()
+        .parse_keys(args.text(1).unwrap())
+        .map_err(failed)?;
+
+    app.keymap_mut().unbind(mode, &keys).map_err(failed)?;
+
+    Ok(())
+}
+
+fn move_cursor(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    // UNWRAP SAFETY: the schema requires a motion from `Motion::NAMES`.
+    let motion = Motion::from_name(args.text(0).unwrap()).unwrap();
+    let count = app.take_count();
+
+    app.move_cursor(motion, count)
+}
+
+fn normal(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    app.enter_mode(Mode::Normal);
+    Ok(())
+}
+
+fn object(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    // UNWRAP SAFETY: the schema requires an object from `TextObject::NAMES`.
+    let object = TextObject::from_name(args.text(0).unwrap()).unwrap();
+    let count = app.take_count();
+
+    app.operate_on_object(object, count)
+}
+
+fn octave(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    let count = app.take_count() as i32;
+
+    // UNWRAP SAFETY: the schema requires a direction from the choices.
+    match args.text(0).unwrap() {
+        "down" => app.shift_keys(-12 * count),
+        "up" => app.shift_keys(12 * count),
+        "reset" => app.reset_keys(),
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
+
+fn operator(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    // UNWRAP SAFETY: the schema requires an operator from `Operator::NAMES`.
+    let operator = Operator::from_name(args.text(0).unwrap()).unwrap();
+    let count = app.take_count();
+
+    app.begin_operator(operator, count)
+}
+
+fn other_end(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    app.swap_selection_ends();
+    Ok(())
+}
+
+fn paste(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    let count = app.take_count();
+    app.paste(count, false)
+}
+
+fn paste_before(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    let count = app.take_count();
+    app.paste(count, true)
+}
+
+fn play(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    app.enter_mode(Mode::Play);
+    Ok(())
+}
+
+fn preset_next(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    app.cycle_preset(1);
+    Ok(())
+}
+
+fn preset_prev(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    app.cycle_preset(-1);
+    Ok(())
+}
+
+fn quantize(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    let count = app.take_count();
+    app.begin_operator(Operator::Quantize, count)
+}
+
+fn record(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    let name = register_name("record", args)?;
+    app.record(name)
+}
+
+fn redo(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    let count = app.take_count();
+    app.undo(count, true)
+}
+
+fn register(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    let name = register_name("register", args)?;
+    app.select_register(name)
+}
+
+fn registers(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    let summary: Vec<_> = app
+        .registers()
+        .list()
+        .iter()
+        .map(|(name, notes)| format!("\"{name} {}", notes.len()))
+        .collect();
+
+    if summary.is_empty() {
+        app.show_message("No registers in use");
+    } else {
+        app.show_message(summary.join("  "));
+    }
+
+    Ok(())
+}
+
+fn repeat(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    let count = app.take_count();
+    app.repeat(count)
+}
+
+fn replay(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    let name = register_name("replay", args)?;
+    let count = app.take_count();
+
+    app.replay(name, count)
+}
+
+fn scroll(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    let amount = args.integer(1).unwrap_or(1);
+
+    // UNWRAP SAFETY: the schema requires a direction from the choices.
+    match args.text(0).unwrap() {
+        "left" => app.update_viewport(|viewport| viewport.scroll_time(-amount * 4)),
+        "right" => app.update_viewport(|viewport| viewport.scroll_time(amount * 4)),
+        "up" => app.update_viewport(|viewport| viewport.scroll_pitch(amount as i32)),
+        "down" => app.update_viewport(|viewport| viewport.scroll_pitch(-amount as i32)),
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
+
+fn semitone(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    let count = app.take_count() as i32;
+
+    // UNWRAP SAFETY: the schema requires a direction from the choices.
+    let sign = if args.text(0).unwrap() == "down" {
+        -1
+    } else {
+        1
+    };
+
+    app.shift_keys(sign * count);
+    Ok(())
+}
+
+fn set(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    let Some(option) = args.text(0) else {
+        let values: Vec<_> = options::NAMES
+            .iter()
+            .filter_map(|name| Some(format!("{name}={}", app.options().get(name)?)))
+            .collect();
+
+        app.show_message(values.join(" "));
+        return Ok(());
+    };
+
+    if option.contains('=') {
+        return app.options_mut().set(option);
+    }
+
+    // `:set grid` and `:set grid?` both show the value.
+    let name = option.strip_suffix('?').unwrap_or(option);
+    let value = app
+        .options()
+        .get(name)
+        .ok_or_else(|| CommandError::InvalidArgument {
+            command: "set",
+            argument: "option",
+            expected: options::NAMES.join(", "),
+            value: name.to_string(),
+        })?;
+
+    app.show_message(format!("{name}={value}"));
+    Ok(())
+}
+
+fn source(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    // UNWRAP SAFETY: the schema requires a path.
+    let path = args.text(0).unwrap();
+
+    app.source(path)
+}
+
+fn step(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    app.enter_mode(Mode::Step);
+    Ok(())
+}
+
+fn step_note(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    // UNWRAP SAFETY: the schema requires a pitch within 0–127.
+    let pitch = args.integer(0).unwrap() as u8;
+
+    app.step_note(pitch, args.text(1).is_some())
+}
+
+fn step_rest(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    app.step_rest();
+    Ok(())
+}
+
+fn step_tie(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    app.step_tie()
+}
+
+fn track(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    // UNWRAP SAFETY: the schema requires an action from the choices.
+    match args.text(0).unwrap() {
+        "new" => {
+            let instrument = args.text(1).unwrap_or("pulse");
+            let track = app.project_mut().add_track(instrument);
+
+            app.select_track(track);
+        }
+        "next" => app.cycle_track(1),
+        "prev" => app.cycle_track(-1),
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
+
+fn transpose(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    // UNWRAP SAFETY: the schema requires semitones within ±127.
+    let semitones = args.integer(0).unwrap() as i32;
+    let count = app.take_count();
+
+    // The count multiplies the interval, as it does vim's <C-a>.
+    app.begin_operator(Operator::Transpose(semitones * count as i32), 1)
+}
+
+fn undo(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    let count = app.take_count();
+    app.undo(count, false)
+}
+
+fn velocity(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    // UNWRAP SAFETY: the schema requires a level within 1–127.
+    let level = args.integer(0).unwrap() as u8;
+
+    // UNWRAP SAFETY: 1–127 is a valid 7-bit velocity.
+    app.set_velocity(Velocity::new(level).unwrap());
+    Ok(())
+}
+
+fn velocity_scale(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    // UNWRAP SAFETY: the schema requires a percentage within 0–1000.
+    let percent = args.integer(0).unwrap() as u32;
+    let count = app.take_count();
+
+    app.begin_operator(Operator::ScaleVelocity(percent), count)
+}
+
+fn velocity_step(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    let count = app.take_count() as i32;
+
+    // UNWRAP SAFETY: the schema requires a direction from the choices.
+    let sign = if args.text(0).unwrap() == "down" {
+        -1
+    } else {
+        1
+    };
+
+    app.adjust_velocity(sign * VELOCITY_STEP * count);
+    Ok(())
+}
+
+fn visual(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    app.visual(Mode::Visual);
+    Ok(())
+}
+
+fn visual_block(app: &mut App, _args: &Args) -> Result<(), CommandError> {
+    app.visual(Mode::VisualBlock);
+    Ok(())
+}
+
+fn write(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    let path = app.save_project(args.text(0).map(Path::new))?;
+    let tracks = app.project().tracks.len();
+
+    app.show_message(format!("\"{}\" written, {tracks} tracks", path.display()));
+    Ok(())
+}
+
+fn zoom(app: &mut App, args: &Args) -> Result<(), CommandError> {
+    // UNWRAP SAFETY: the schema requires a direction from the choices.
+    let factor = match args.text(0).unwrap() {
+        "in" => ZOOM_STEP,
+        _ => ZOOM_STEP.recip(),
+    };
+
+    match args.text(1).unwrap_or("time") {
+        "pitch" => app.update_viewport(|viewport| viewport.zoom_pitch(factor)),
+        _ => app.update_viewport(|viewport| viewport.zoom_time(factor)),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mode_names_cover_every_mode() {
+        let names: Vec<_> = Mode::ALL.iter().map(Mode::name).collect();
+
+        assert_eq!(names, MODE_NAMES);
+    }
+
+    #[test]
+    fn track_instruments_are_workspace_crates() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../motif-instruments");
+
+        for instrument in INSTRUMENTS {
+            assert!(
+                dir.join(format!("motif-{instrument}")).is_dir(),
+                "{instrument}"
+            );
+        }
+    }
+}
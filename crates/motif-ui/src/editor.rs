@@ -0,0 +1,684 @@
+use motif_core::edit::Edit;
+use motif_core::id::{NoteId, TrackId};
+use motif_core::note::NoteEvent;
+use motif_core::project::Clip;
+use motif_core::tick::{TICKS_PER_QUARTER, Tick};
+use wmidi::{Note, Velocity};
+
+const BEATS_PER_BAR: u64 = 4;
+const TICKS_PER_BAR: u64 = TICKS_PER_QUARTER * BEATS_PER_BAR;
+const HIGHEST_PITCH: u8 = 127;
+
+/// Where edits happen: a tick on the grid and a pitch lane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub tick: Tick,
+    pub pitch: u8,
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Self {
+            tick: Tick::ZERO,
+            pitch: 60,
+        }
+    }
+}
+
+/// Cursor movements, which also mark out what an operator acts on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    /// Back one grid step.
+    Left,
+    /// Forward one grid step.
+    Right,
+    /// Up a semitone.
+    Up,
+    /// Down a semitone.
+    Down,
+    /// To the start of the next note, at any pitch.
+    NextNote,
+    /// To the start of the previous note, at any pitch.
+    PrevNote,
+}
+
+impl Motion {
+    pub const NAMES: &[&str] = &["down", "left", "next-note", "prev-note", "right", "up"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "left" => Motion::Left,
+            "right" => Motion::Right,
+            "up" => Motion::Up,
+            "down" => Motion::Down,
+            "next-note" => Motion::NextNote,
+            "prev-note" => Motion::PrevNote,
+            _ => return None,
+        })
+    }
+
+    /// Where `count` repeats of the motion take the cursor.
+    pub fn target(self, cursor: Cursor, count: u32, grid: u64, clip: &Clip) -> Cursor {
+        let count = count.max(1);
+        let steps = grid * count as u64;
+
+        match self {
+            Motion::Left => Cursor {
+                tick: Tick::from_raw(cursor.tick.as_raw().saturating_sub(steps)),
+                ..cursor
+            },
+            Motion::Right => Cursor {
+                tick: cursor.tick + Tick::from_raw(steps),
+                ..cursor
+            },
+            Motion::Up => Cursor {
+                pitch: cursor
+                    .pitch
+                    .saturating_add(count.min(127) as u8)
+                    .min(HIGHEST_PITCH),
+                ..cursor
+            },
+            Motion::Down => Cursor {
+                pitch: cursor.pitch.saturating_sub(count.min(127) as u8),
+                ..cursor
+            },
+            Motion::NextNote | Motion::PrevNote => {
+                let mut target = cursor;
+
+                for _ in 0..count {
+                    match self.adjacent_note(target, clip) {
+                        Some(next) => target = next,
+                        None => break,
+                    }
+                }
+
+                target
+            }
+        }
+    }
+
+    fn adjacent_note(self, cursor: Cursor, clip: &Clip) -> Option<Cursor> {
+        let here = (cursor.tick, cursor.pitch);
+        let starts = clip
+            .notes
+            .values()
+            .map(|note| (note.start_tick, u8::from(note.note)));
+
+        let found = if self == Motion::NextNote {
+            starts.filter(|start| *start > here).min()
+        } else {
+            starts.filter(|start| *start < here).max()
+        };
+
+        found.map(|(tick, pitch)| Cursor { tick, pitch })
+    }
+
+    /// Vertical motions cover whole pitch lanes, like vim's linewise
+    /// motions cover whole lines.
+    pub fn is_linewise(self) -> bool {
+        matches!(self, Motion::Up | Motion::Down)
+    }
+}
+
+/// Note groups an operator can act on without a motion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextObject {
+    /// The note under the cursor.
+    InnerNote,
+    /// Notes starting together with the one under the cursor.
+    InnerChord,
+    /// Notes starting in the cursor's bar.
+    InnerBar,
+    /// Notes sounding anywhere in the cursor's bar.
+    ABar,
+}
+
+impl TextObject {
+    pub const NAMES: &[&str] = &["a-bar", "inner-bar", "inner-chord", "inner-note"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "inner-note" => TextObject::InnerNote,
+            "inner-chord" => TextObject::InnerChord,
+            "inner-bar" => TextObject::InnerBar,
+            "a-bar" => TextObject::ABar,
+            _ => return None,
+        })
+    }
+}
+
+/// What an operator changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Delete,
+    Yank,
+    /// Delete, leaving the cursor where the notes started to write new ones.
+    Change,
+    /// Move later by one grid step.
+    ShiftRight,
+    /// Move earlier by one grid step.
+    ShiftLeft,
+    /// Move by semitones. Notes that would leave the MIDI range stay put.
+    Transpose(i32),
+    /// Snap starts to the nearest grid line.
+    Quantize,
+    /// Scale velocities by a percentage, keeping them within 1–127.
+    ScaleVelocity(u32),
+}
+
+impl Operator {
+    /// Operators without a parameter, by name.
+    pub const NAMES: &[&str] = &[
+        "change",
+        "delete",
+        "quantize",
+        "shift-left",
+        "shift-right",
+        "yank",
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "delete" => Operator::Delete,
+            "yank" => Operator::Yank,
+            "change" => Operator::Change,
+            "shift-right" => Operator::ShiftRight,
+            "shift-left" => Operator::ShiftLeft,
+            "quantize" => Operator::Quantize,
+            _ => return None,
+        })
+    }
+
+    /// Whether the notes go to the yank buffer.
+    pub fn yanks(self) -> bool {
+        matches!(self, Operator::Delete | Operator::Yank | Operator::Change)
+    }
+
+    /// Edits performing the operator on `notes`. Notes it leaves as they
+    /// were get no edit.
+    pub fn edits(self, track: TrackId, clip: &Clip, notes: &[NoteId], grid: u64) -> Vec<Edit> {
+        notes
+            .iter()
+            .filter_map(|id| Some((*id, *clip.notes.get(id)?)))
+            .filter_map(|(id, note)| {
+                if matches!(self, Operator::Delete | Operator::Change) {
+                    return Some(Edit::RemoveNote { track, id, note });
+                }
+
+                let after = self.change(note, grid)?;
+
+                (after != note).then_some(Edit::ChangeNote {
+                    track,
+                    id,
+                    before: note,
+                    after,
+                })
+            })
+            .collect()
+    }
+
+    /// The note after the operator, for operators that keep it.
+    fn change(self, note: NoteEvent, grid: u64) -> Option<NoteEvent> {
+        let start = note.start_tick.as_raw();
+
+        match self {
+            Operator::Delete | Operator::Change | Operator::Yank => None,
+            Operator::ShiftRight => Some(NoteEvent {
+                start_tick: Tick::from_raw(start + grid),
+                ..note
+            }),
+            Operator::ShiftLeft => Some(NoteEvent {
+                start_tick: Tick::from_raw(start.saturating_sub(grid)),
+                ..note
+            }),
+            Operator::Transpose(semitones) => {
+                let pitch = u8::try_from(u8::from(note.note) as i32 + semitones)
+                    .ok()
+                    .filter(|pitch| *pitch <= HIGHEST_PITCH)?;
+
+                Some(NoteEvent {
+                    note: Note::from_u8_lossy(pitch),
+                    ..note
+                })
+            }
+            Operator::Quantize => Some(NoteEvent {
+                start_tick: note.start_tick.snap_to_grid(grid),
+                ..note
+            }),
+            Operator::ScaleVelocity(percent) => {
+                let velocity = (u8::from(note.velocity) as u32 * percent + 50) / 100;
+
+                Some(NoteEvent {
+                    velocity: Velocity::from_u8_lossy(velocity.clamp(1, 127) as u8),
+                    ..note
+                })
+            }
+        }
+    }
+}
+
+/// Which notes a Visual mode selects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionKind {
+    /// Every pitch, between two ticks.
+    Time,
+    /// Between two ticks and two pitches.
+    Block,
+}
+
+/// The area between where Visual mode started and the cursor. Both ends
+/// include their whole grid cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    pub anchor: Cursor,
+    pub cursor: Cursor,
+    pub kind: SelectionKind,
+}
+
+impl Selection {
+    /// First tick selected and the tick after the last.
+    pub fn ticks(&self, grid: u64) -> (Tick, Tick) {
+        let start = self.anchor.tick.min(self.cursor.tick);
+        let end = self.anchor.tick.max(self.cursor.tick) + Tick::from_raw(grid);
+
+        (start, end)
+    }
+
+    /// Lowest and highest pitch selected.
+    pub fn pitches(&self) -> (u8, u8) {
+        match self.kind {
+            SelectionKind::Time => (0, HIGHEST_PITCH),
+            SelectionKind::Block => (
+                self.anchor.pitch.min(self.cursor.pitch),
+                self.anchor.pitch.max(self.cursor.pitch),
+            ),
+        }
+    }
+
+    /// Notes starting inside the selection.
+    pub fn range(&self, clip: &Clip, grid: u64) -> Range {
+        let (start, end) = self.ticks(grid);
+        let (low, high) = self.pitches();
+
+        Range::matching(clip, Some(start), |_, note| {
+            (start..end).contains(&note.start_tick) && (low..=high).contains(&u8::from(note.note))
+        })
+    }
+}
+
+/// Notes picked out by a motion or text object, and where they begin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Range {
+    pub start: Tick,
+    pub notes: Vec<NoteId>,
+}
+
+impl Range {
+    /// Notes between the cursor and where a motion took it. Horizontal
+    /// motions cover the cursor's lane from the earlier tick up to (not
+    /// including) the later one; vertical ones cover every lane in between.
+    pub fn motion(clip: &Clip, from: Cursor, to: Cursor, motion: Motion) -> Self {
+        if motion.is_linewise() {
+            let low = from.pitch.min(to.pitch);
+            let high = from.pitch.max(to.pitch);
+
+            return Self::matching(clip, None, |_, note| {
+                (low..=high).contains(&u8::from(note.note))
+            });
+        }
+
+        let start = from.tick.min(to.tick);
+        let end = from.tick.max(to.tick);
+
+        Self::matching(clip, Some(start), |_, note| {
+            u8::from(note.note) == from.pitch && note.start_tick >= start && note.start_tick < end
+        })
+    }
+
+    /// `count` objects from the cursor: the note under it and the ones
+    /// after, in next-note order, or as many chords or bars.
+    pub fn object(clip: &Clip, cursor: Cursor, object: TextObject, count: u32) -> Self {
+        let count = count.max(1);
+        let bar = Tick::from_raw(cursor.tick.as_raw() / TICKS_PER_BAR * TICKS_PER_BAR);
+        let bar_end = bar + Tick::from_raw(TICKS_PER_BAR * count as u64);
+
+        match object {
+            TextObject::InnerNote => {
+                let under = note_under(clip, cursor);
+                let start = under.map(|(_, note)| note.start_tick);
+                let notes: Vec<_> = match under {
+                    Some((under, _)) => clip
+                        .sorted()
+                        .into_iter()
+                        .map(|(id, _)| id)
+                        .skip_while(|id| *id != under)
+                        .take(count as usize)
+                        .collect(),
+                    None => Vec::new(),
+                };
+
+                Self::matching(clip, start, |id, _| notes.contains(&id))
+            }
+            TextObject::InnerChord => {
+                let start = note_under(clip, cursor).map_or(cursor.tick, |(_, n)| n.start_tick);
+                let mut starts: Vec<_> = clip
+                    .notes
+                    .values()
+                    .map(|note| note.start_tick)
+                    .filter(|tick| *tick >= start)
+                    .collect();
+                starts.sort();
+                starts.dedup();
+                starts.truncate(count as usize);
+                let last = starts.last().copied();
+
+                Self::matching(clip, Some(start), |_, note| {
+                    last.is_some_and(|last| (start..=last).contains(&note.start_tick))
+                })
+            }
+            TextObject::InnerBar => Self::matching(clip, Some(bar), |_, note| {
+                note.start_tick >= bar && note.start_tick < bar_end
+            }),
+            TextObject::ABar => Self::matching(clip, Some(bar), |_, note| {
+                note.start_tick < bar_end && note.end_tick() > bar
+            }),
+        }
+    }
+
+    /// Notes matching `filter`, starting at `start` or else the first of them.
+    fn matching(
+        clip: &Clip,
+        start: Option<Tick>,
+        filter: impl Fn(NoteId, &NoteEvent) -> bool,
+    ) -> Self {
+        let notes: Vec<_> = clip
+            .sorted()
+            .into_iter()
+            .filter(|(id, note)| filter(*id, note))
+            .collect();
+
+        let start = start
+            .or_else(|| notes.first().map(|(_, note)| note.start_tick))
+            .unwrap_or(Tick::ZERO);
+
+        Self {
+            start,
+            notes: notes.into_iter().map(|(id, _)| id).collect(),
+        }
+    }
+
+    /// The notes, with start ticks made relative to `self.start`.
+    pub fn relative_notes(&self, clip: &Clip) -> Vec<NoteEvent> {
+        self.notes
+            .iter()
+            .filter_map(|id| clip.notes.get(id))
+            .map(|note| NoteEvent {
+                start_tick: note.start_tick.saturating_sub(self.start),
+                ..*note
+            })
+            .collect()
+    }
+}
+
+/// The note at the cursor's pitch sounding at its tick, latest start first.
+fn note_under(clip: &Clip, cursor: Cursor) -> Option<(NoteId, &NoteEvent)> {
+    clip.sorted().into_iter().rev().find(|(_, note)| {
+        note.note == Note::from_u8_lossy(cursor.pitch)
+            && note.start_tick <= cursor.tick
+            && note.end_tick() > cursor.tick
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRID: u64 = 120;
+
+    fn event(start: u64, length: u64, pitch: u8) -> NoteEvent {
+        NoteEvent {
+            start_tick: Tick::from_raw(start),
+            length_ticks: length,
+            note: Note::from_u8_lossy(pitch),
+            velocity: Velocity::MAX,
+        }
+    }
+
+    /// Notes 0–3: C4 at 0, E4 at 0, C4 at 480, G4 in the second bar.
+    fn clip() -> Clip {
+        let mut clip = Clip::default();
+
+        for (id, note) in [
+            event(0, 240, 60),
+            event(0, 240, 64),
+            event(480, 240, 60),
+            event(1920, 480, 67),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            clip.notes.insert(NoteId(id as u64), note);
+        }
+
+        clip
+    }
+
+    fn at(tick: u64, pitch: u8) -> Cursor {
+        Cursor {
+            tick: Tick::from_raw(tick),
+            pitch,
+        }
+    }
+
+    #[test]
+    fn grid_and_semitone_motions_take_counts() {
+        let clip = clip();
+
+        assert_eq!(Motion::Right.target(at(0, 60), 4, GRID, &clip), at(480, 60));
+        assert_eq!(Motion::Left.target(at(120, 60), 4, GRID, &clip), at(0, 60));
+        assert_eq!(Motion::Up.target(at(0, 126), 3, GRID, &clip), at(0, 127));
+        assert_eq!(Motion::Down.target(at(0, 60), 12, GRID, &clip), at(0, 48));
+    }
+
+    #[test]
+    fn note_motions_jump_between_starts() {
+        let clip = clip();
+
+        assert_eq!(
+            Motion::NextNote.target(at(0, 60), 1, GRID, &clip),
+            at(0, 64)
+        );
+        assert_eq!(
+            Motion::NextNote.target(at(0, 60), 2, GRID, &clip),
+            at(480, 60)
+        );
+        assert_eq!(
+            Motion::NextNote.target(at(0, 60), 9, GRID, &clip),
+            at(1920, 67)
+        );
+        assert_eq!(
+            Motion::PrevNote.target(at(100, 50), 1, GRID, &clip),
+            at(0, 64)
+        );
+        assert_eq!(
+            Motion::PrevNote.target(at(0, 60), 1, GRID, &clip),
+            at(0, 60)
+        );
+    }
+
+    #[test]
+    fn horizontal_motion_range_stays_in_lane() {
+        let clip = clip();
+        let from = at(0, 60);
+        let to = Motion::Right.target(from, 4, GRID, &clip);
+
+        let range = Range::motion(&clip, from, to, Motion::Right);
+
+        assert_eq!(range.notes, [NoteId(0)]);
+        assert_eq!(range.start, Tick::ZERO);
+
+        let to = Motion::Right.target(from, 5, GRID, &clip);
+        let range = Range::motion(&clip, from, to, Motion::Right);
+
+        assert_eq!(range.notes, [NoteId(0), NoteId(2)]);
+    }
+
+    #[test]
+    fn vertical_motion_range_covers_lanes() {
+        let clip = clip();
+        let from = at(0, 60);
+        let to = Motion::Up.target(from, 4, GRID, &clip);
+
+        let range = Range::motion(&clip, from, to, Motion::Up);
+
+        assert_eq!(range.notes, [NoteId(0), NoteId(1), NoteId(2)]);
+    }
+
+    #[test]
+    fn text_objects() {
+        let clip = clip();
+        let ids = |object| Range::object(&clip, at(120, 60), object, 1).notes;
+
+        assert_eq!(ids(TextObject::InnerNote), [NoteId(0)]);
+        assert_eq!(ids(TextObject::InnerChord), [NoteId(0), NoteId(1)]);
+        assert_eq!(ids(TextObject::InnerBar), [NoteId(0), NoteId(1), NoteId(2)]);
+
+        let range = Range::object(&clip, at(2000, 60), TextObject::InnerBar, 1);
+        assert_eq!(range.start, Tick::from_raw(1920));
+        assert_eq!(range.notes, [NoteId(3)]);
+    }
+
+    #[test]
+    fn text_objects_take_a_count() {
+        let clip = clip();
+        let ids = |object| Range::object(&clip, at(120, 60), object, 2).notes;
+
+        assert_eq!(ids(TextObject::InnerNote), [NoteId(0), NoteId(1)]);
+        assert_eq!(
+            ids(TextObject::InnerChord),
+            [NoteId(0), NoteId(1), NoteId(2)]
+        );
+        assert_eq!(ids(TextObject::InnerBar).len(), 4);
+    }
+
+    #[test]
+    fn a_bar_includes_notes_sounding_into_it() {
+        let mut clip = clip();
+        clip.notes.insert(NoteId(4), event(1800, 240, 72));
+
+        let inner = Range::object(&clip, at(1920, 60), TextObject::InnerBar, 1);
+        let around = Range::object(&clip, at(1920, 60), TextObject::ABar, 1);
+
+        assert_eq!(inner.notes, [NoteId(3)]);
+        assert_eq!(around.notes, [NoteId(4), NoteId(3)]);
+    }
+
+    #[test]
+    fn operators_produce_edits() {
+        let clip = clip();
+        let track = TrackId(0);
+        let notes = [NoteId(0), NoteId(2)];
+
+        let deleted = Operator::Delete.edits(track, &clip, &notes, GRID);
+        assert_eq!(
+            deleted[1],
+            Edit::RemoveNote {
+                track,
+                id: NoteId(2),
+                note: event(480, 240, 60)
+            }
+        );
+
+        let shifted = Operator::ShiftLeft.edits(track, &clip, &notes, GRID);
+        assert_eq!(
+            shifted,
+            [Edit::ChangeNote {
+                track,
+                id: NoteId(2),
+                before: event(480, 240, 60),
+                after: event(360, 240, 60),
+            }]
+        );
+
+        assert!(Operator::Yank.edits(track, &clip, &notes, GRID).is_empty());
+    }
+
+    #[test]
+    fn transpose_quantize_and_scale_velocity() {
+        let mut clip = clip();
+        clip.notes.insert(NoteId(4), event(500, 240, 126));
+        let track = TrackId(0);
+        let after = |operator: Operator| -> Vec<_> {
+            operator
+                .edits(track, &clip, &[NoteId(2), NoteId(4)], GRID)
+                .into_iter()
+                .map(|edit| match edit {
+                    Edit::ChangeNote { id, after, .. } => (id, after),
+                    edit => panic!("{edit:?}"),
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            after(Operator::Transpose(2)),
+            [(NoteId(2), event(480, 240, 62))]
+        );
+        assert_eq!(
+            after(Operator::Quantize),
+            [(NoteId(4), event(480, 240, 126))]
+        );
+
+        let scaled = after(Operator::ScaleVelocity(50));
+        assert_eq!(u8::from(scaled[0].1.velocity), 64);
+
+        let silent = Operator::ScaleVelocity(0)
+            .change(event(0, 1, 60), GRID)
+            .unwrap();
+        assert_eq!(u8::from(silent.velocity), 1);
+    }
+
+    #[test]
+    fn selections_include_both_end_cells() {
+        let clip = clip();
+        let time = Selection {
+            anchor: at(480, 90),
+            cursor: at(0, 90),
+            kind: SelectionKind::Time,
+        };
+
+        assert_eq!(time.ticks(GRID), (Tick::ZERO, Tick::from_raw(600)));
+        assert_eq!(
+            time.range(&clip, GRID).notes,
+            [NoteId(0), NoteId(1), NoteId(2)]
+        );
+
+        let block = Selection {
+            anchor: at(0, 60),
+            cursor: at(480, 62),
+            kind: SelectionKind::Block,
+        };
+
+        assert_eq!(block.range(&clip, GRID).notes, [NoteId(0), NoteId(2)]);
+    }
+
+    #[test]
+    fn relative_notes_start_from_range() {
+        let clip = clip();
+        let range = Range::object(&clip, at(480, 60), TextObject::InnerNote, 1);
+
+        assert_eq!(range.relative_notes(&clip), [event(0, 240, 60)]);
+    }
+
+    #[test]
+    fn names_parse() {
+        for name in Motion::NAMES {
+            assert!(Motion::from_name(name).is_some(), "{name}");
+        }
+        for name in TextObject::NAMES {
+            assert!(TextObject::from_name(name).is_some(), "{name}");
+        }
+        for name in Operator::NAMES {
+            assert!(Operator::from_name(name).is_some(), "{name}");
+        }
+    }
+}
@@ -0,0 +1,83 @@
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum CommandError {
+    #[error("Empty command")]
+    Empty,
+    #[error("Unterminated quote")]
+    UnterminatedQuote,
+    #[error("Unknown command: {0}")]
+    UnknownCommand(String),
+    #[error("{command}: missing argument <{argument}>")]
+    MissingArgument {
+        command: &'static str,
+        argument: &'static str,
+    },
+    #[error("{command}: expected {expected} for <{argument}>, got \"{value}\"")]
+    InvalidArgument {
+        command: &'static str,
+        argument: &'static str,
+        expected: String,
+        value: String,
+    },
+    #[error("{command}: too many arguments (usage: {usage})")]
+    TooManyArguments {
+        command: &'static str,
+        usage: String,
+    },
+    #[error("{0}")]
+    Failed(String),
+    #[error("Line {line}: {error}")]
+    Script {
+        line: usize,
+        error: Box<CommandError>,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum KeymapError {
+    #[error("Keymap file error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid keymap: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("Invalid key notation \"{notation}\": {message}")]
+    InvalidKey { notation: String, message: String },
+    #[error("Piano key \"{key}\": {message}")]
+    InvalidPianoKey { key: String, message: String },
+    #[error("{mode} mode: \"{keys}\" conflicts with \"{other}\"")]
+    Conflict {
+        mode: &'static str,
+        keys: String,
+        other: String,
+    },
+    #[error("{mode} mode: \"{keys}\" runs unknown command \"{command}\"")]
+    UnknownCommand {
+        mode: &'static str,
+        keys: String,
+        command: String,
+    },
+    #[error("{mode} mode: \"{keys}\": {error}")]
+    InvalidCommand {
+        mode: &'static str,
+        keys: String,
+        error: CommandError,
+    },
+    #[error("{mode} mode: nothing is mapped to \"{keys}\"")]
+    NotMapped { mode: &'static str, keys: String },
+    #[error("Mapping \"{0}\" expands into itself")]
+    Recursive(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RegisterError {
+    #[error("Register file error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid register file: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("Failed to serialize registers: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("Invalid register name '{0}'")]
+    InvalidName(char),
+    #[error("Register '{0}' is read-only")]
+    ReadOnly(char),
+    #[error("Register '{name}': {message}")]
+    InvalidNote { name: char, message: String },
+}
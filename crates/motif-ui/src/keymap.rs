@@ -0,0 +1,694 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use iced::keyboard::{Key, Modifiers, key::Named};
+use serde::Deserialize;
+use wmidi::Note;
+
+use crate::app::Mode;
+use crate::command::{Invocation, Registry};
+use crate::error::KeymapError;
+
+/// The built-in keymap, and the template for a user's own.
+pub const DEFAULT_KEYMAP: &str = include_str!("../keymap.ron");
+
+/// Longest chain of mappings typing other mappings before giving up.
+pub const MAX_DEPTH: usize = 100;
+
+/// Modes where unmodified piano keys play notes instead of running bindings.
+const PIANO_MODES: &[Mode] = &[Mode::Play, Mode::Step];
+
+/// Named keys by notation, the form used for display first.
+const NAMED_KEYS: &[(&str, Named)] = &[
+    ("Esc", Named::Escape),
+    ("Escape", Named::Escape),
+    ("CR", Named::Enter),
+    ("Enter", Named::Enter),
+    ("Return", Named::Enter),
+    ("Tab", Named::Tab),
+    ("Space", Named::Space),
+    ("BS", Named::Backspace),
+    ("Backspace", Named::Backspace),
+    ("Del", Named::Delete),
+    ("Delete", Named::Delete),
+    ("Up", Named::ArrowUp),
+    ("Down", Named::ArrowDown),
+    ("Left", Named::ArrowLeft),
+    ("Right", Named::ArrowRight),
+    ("Home", Named::Home),
+    ("End", Named::End),
+    ("PageUp", Named::PageUp),
+    ("PageDown", Named::PageDown),
+    ("F1", Named::F1),
+    ("F2", Named::F2),
+    ("F3", Named::F3),
+    ("F4", Named::F4),
+    ("F5", Named::F5),
+    ("F6", Named::F6),
+    ("F7", Named::F7),
+    ("F8", Named::F8),
+    ("F9", Named::F9),
+    ("F10", Named::F10),
+    ("F11", Named::F11),
+    ("F12", Named::F12),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KeyCode {
+    Char(char),
+    Named(Named),
+}
+
+/// One key press with its modifiers. Shift is folded into characters
+/// (`N`, not `<S-n>`), and characters under Ctrl are lowercase, so each
+/// press has exactly one chord.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Chord {
+    pub key: KeyCode,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub logo: bool,
+}
+
+impl Chord {
+    pub fn new(key: KeyCode, ctrl: bool, alt: bool, shift: bool, logo: bool) -> Self {
+        match key {
+            KeyCode::Char(c) => Self {
+                key: KeyCode::Char(if ctrl { c.to_ascii_lowercase() } else { c }),
+                ctrl,
+                alt,
+                shift: false,
+                logo,
+            },
+            KeyCode::Named(_) => Self {
+                key,
+                ctrl,
+                alt,
+                shift,
+                logo,
+            },
+        }
+    }
+
+    pub fn char(c: char) -> Self {
+        Self::new(KeyCode::Char(c), false, false, false, false)
+    }
+
+    pub fn named(named: Named) -> Self {
+        Self::new(KeyCode::Named(named), false, false, false, false)
+    }
+
+    /// Chord for a key event's `modified_key`. `None` for keys without a
+    /// notation, such as modifiers pressed alone.
+    pub fn from_key(key: &Key, modifiers: Modifiers) -> Option<Self> {
+        let code = match key.as_ref() {
+            Key::Character(text) => {
+                let mut chars = text.chars();
+                let c = chars.next()?;
+
+                if chars.next().is_some() {
+                    return None;
+                }
+
+                if c == ' ' {
+                    KeyCode::Named(Named::Space)
+                } else {
+                    KeyCode::Char(c)
+                }
+            }
+            Key::Named(named) if NAMED_KEYS.iter().any(|(_, n)| *n == named) => {
+                KeyCode::Named(named)
+            }
+            _ => return None,
+        };
+
+        Some(Self::new(
+            code,
+            modifiers.control(),
+            modifiers.alt(),
+            modifiers.shift(),
+            modifiers.logo(),
+        ))
+    }
+
+    /// The character typed, when no modifier beyond Shift is held.
+    pub fn as_char(&self) -> Option<char> {
+        match self.key {
+            KeyCode::Char(c) if !self.ctrl && !self.alt && !self.logo => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Parse the inside of `<...>`: modifiers then a key, e.g. `C-S-Tab`.
+    fn parse_bracket(inner: &str) -> Option<Self> {
+        let mut parts: Vec<&str> = inner.split('-').collect();
+
+        // `<C-->` is Ctrl and the minus key.
+        if inner.ends_with("--") {
+            parts.truncate(parts.len() - 2);
+            parts.push("-");
+        }
+
+        let (name, modifiers) = parts.split_last()?;
+        let (mut ctrl, mut alt, mut shift, mut logo) = (false, false, false, false);
+
+        for modifier in modifiers {
+            match modifier.to_ascii_uppercase().as_str() {
+                "C" => ctrl = true,
+                "A" | "M" => alt = true,
+                "S" => shift = true,
+                "D" => logo = true,
+                _ => return None,
+            }
+        }
+
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if shift => KeyCode::Char(c.to_ascii_uppercase()),
+            (Some(c), None) => KeyCode::Char(c),
+            _ if name.eq_ignore_ascii_case("lt") => KeyCode::Char('<'),
+            _ => NAMED_KEYS
+                .iter()
+                .find(|(notation, _)| notation.eq_ignore_ascii_case(name))
+                .map(|(_, named)| KeyCode::Named(*named))?,
+        };
+
+        Some(Self::new(code, ctrl, alt, shift, logo))
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plain = !self.ctrl && !self.alt && !self.shift && !self.logo;
+
+        let name = match self.key {
+            KeyCode::Char('<') => "lt".to_string(),
+            KeyCode::Char(c) if plain => return write!(f, "{c}"),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Named(named) => NAMED_KEYS.iter().find(|(_, n)| *n == named).map_or_else(
+                || format!("{named:?}"),
+                |(notation, _)| notation.to_string(),
+            ),
+        };
+
+        f.write_str("<")?;
+
+        for (held, prefix) in [
+            (self.ctrl, "C-"),
+            (self.alt, "A-"),
+            (self.shift, "S-"),
+            (self.logo, "D-"),
+        ] {
+            if held {
+                f.write_str(prefix)?;
+            }
+        }
+
+        write!(f, "{name}>")
+    }
+}
+
+/// A key sequence in notation, e.g. `gg<C-w>`.
+pub fn format_keys(keys: &[Chord]) -> String {
+    keys.iter().map(Chord::to_string).collect()
+}
+
+/// Parse key notation. `<leader>` expands to `leader`. A `<` that doesn't
+/// open a bracketed name (`<<`, `<>`) is the `<` key itself.
+pub fn parse_keys(notation: &str, leader: &[Chord]) -> Result<Vec<Chord>, KeymapError> {
+    let invalid = |message: &str| KeymapError::InvalidKey {
+        notation: notation.to_string(),
+        message: message.to_string(),
+    };
+
+    let mut keys = Vec::new();
+    let mut rest = notation;
+
+    while let Some(c) = rest.chars().next() {
+        let bracket = rest
+            .strip_prefix('<')
+            .and_then(|after| after.find('>').map(|end| &after[..end]))
+            .filter(|inner| inner.len() > 1 && !inner.contains('<'));
+
+        match bracket {
+            Some(inner) if inner.eq_ignore_ascii_case("leader") => {
+                if leader.is_empty() {
+                    return Err(invalid("no leader key is set"));
+                }
+
+                keys.extend_from_slice(leader);
+                rest = &rest[inner.len() + 2..];
+            }
+            Some(inner) => {
+                let chord = Chord::parse_bracket(inner)
+                    .ok_or_else(|| invalid(&format!("unknown key <{inner}>")))?;
+
+                keys.push(chord);
+                rest = &rest[inner.len() + 2..];
+            }
+            None => {
+                keys.push(Chord::char(c));
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    if keys.is_empty() {
+        return Err(invalid("no keys"));
+    }
+
+    Ok(keys)
+}
+
+/// What a key sequence does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Command(Invocation),
+    /// Typed back in as if pressed. With `remap`, mappings among them
+    /// expand too; without, only command bindings run.
+    Keys {
+        keys: Vec<Chord>,
+        remap: bool,
+    },
+}
+
+impl Action {
+    /// `:name args` is a command; anything else is keys.
+    pub fn parse(source: &str, remap: bool, leader: &[Chord]) -> Result<Self, KeymapError> {
+        let source = source.trim();
+
+        if source.starts_with(':') {
+            let invocation =
+                Invocation::parse(source).map_err(|error| KeymapError::InvalidKey {
+                    notation: source.to_string(),
+                    message: error.to_string(),
+                })?;
+
+            return Ok(Action::Command(invocation));
+        }
+
+        Ok(Action::Keys {
+            keys: parse_keys(source, leader)?,
+            remap,
+        })
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Command(invocation) => write!(f, ":{invocation}"),
+            Action::Keys { keys, .. } => f.write_str(&format_keys(keys)),
+        }
+    }
+}
+
+/// Result of looking up the keys typed so far.
+#[derive(Debug, PartialEq)]
+pub enum Lookup<'a> {
+    /// A longer binding starts with these keys; wait for more.
+    Pending,
+    Unbound,
+    Bound(&'a Action),
+}
+
+/// The keymap file as written.
+#[derive(Deserialize)]
+struct KeymapFile {
+    #[serde(default)]
+    leader: String,
+    #[serde(default)]
+    modes: HashMap<Mode, BTreeMap<String, String>>,
+    #[serde(default)]
+    piano: BTreeMap<String, u8>,
+}
+
+/// Key bindings per mode, plus the piano layout. No binding in a mode is a
+/// prefix of another, so a sequence is never ambiguous and never needs a
+/// timeout to resolve.
+#[derive(Debug, Default)]
+pub struct Keymap {
+    leader: Vec<Chord>,
+    modes: HashMap<Mode, BTreeMap<Vec<Chord>, Action>>,
+    piano: HashMap<char, Note>,
+}
+
+impl Keymap {
+    /// The built-in keymap.
+    pub fn builtin() -> Self {
+        // UNWRAP SAFETY: the built-in keymap is checked by a test.
+        Self::from_ron(DEFAULT_KEYMAP).unwrap()
+    }
+
+    pub fn from_ron(source: &str) -> Result<Self, KeymapError> {
+        let file: KeymapFile = ron::from_str(source)?;
+
+        let mut keymap = Keymap {
+            leader: if file.leader.is_empty() {
+                Vec::new()
+            } else {
+                parse_keys(&file.leader, &[])?
+            },
+            ..Keymap::default()
+        };
+
+        for (key, number) in &file.piano {
+            let invalid = |message: &str| KeymapError::InvalidPianoKey {
+                key: key.clone(),
+                message: message.to_string(),
+            };
+
+            let mut chars = key.chars();
+            let (Some(c), None) = (chars.next(), chars.next()) else {
+                return Err(invalid("must be a single character"));
+            };
+
+            let note = Note::try_from(*number).map_err(|_| invalid("note must be 0–127"))?;
+            keymap.piano.insert(c, note);
+        }
+
+        for (mode, bindings) in &file.modes {
+            for (keys, action) in bindings {
+                let keys = keymap.parse_keys(keys)?;
+                let action = Action::parse(action, false, &keymap.leader)?;
+
+                keymap.bind(*mode, keys, action)?;
+            }
+        }
+
+        Ok(keymap)
+    }
+
+    /// Read a keymap file. Blocking I/O.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, KeymapError> {
+        Self::from_ron(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse notation with this keymap's leader.
+    pub fn parse_keys(&self, notation: &str) -> Result<Vec<Chord>, KeymapError> {
+        parse_keys(notation, &self.leader)
+    }
+
+    pub fn leader(&self) -> &[Chord] {
+        &self.leader
+    }
+
+    /// Bind `keys` in `mode`, replacing any binding for exactly those keys.
+    /// Fails if the keys would make another binding unreachable or are
+    /// themselves unreachable: one a prefix of the other, or starting with
+    /// a piano key in a mode that plays notes.
+    pub fn bind(
+        &mut self,
+        mode: Mode,
+        keys: Vec<Chord>,
+        action: Action,
+    ) -> Result<(), KeymapError> {
+        let conflict = |other: String| KeymapError::Conflict {
+            mode: mode.name(),
+            keys: format_keys(&keys),
+            other,
+        };
+
+        if PIANO_MODES.contains(&mode)
+            && let Some(c) = keys[0].as_char()
+            && self.piano.contains_key(&c)
+        {
+            return Err(conflict(format!("piano key {c}")));
+        }
+
+        let bindings = self.modes.entry(mode).or_default();
+
+        if let Some(other) = bindings
+            .keys()
+            .find(|other| *other != &keys && (other.starts_with(&keys) || keys.starts_with(other)))
+        {
+            return Err(conflict(format_keys(other)));
+        }
+
+        bindings.insert(keys, action);
+
+        Ok(())
+    }
+
+    pub fn unbind(&mut self, mode: Mode, keys: &[Chord]) -> Result<Action, KeymapError> {
+        self.modes
+            .get_mut(&mode)
+            .and_then(|bindings| bindings.remove(keys))
+            .ok_or_else(|| KeymapError::NotMapped {
+                mode: mode.name(),
+                keys: format_keys(keys),
+            })
+    }
+
+    pub fn lookup(&self, mode: Mode, keys: &[Chord]) -> Lookup<'_> {
+        let Some(bindings) = self.modes.get(&mode) else {
+            return Lookup::Unbound;
+        };
+
+        // Bindings are prefix-free, so the first at or after `keys` is
+        // either an exact match, the only kind of longer match, or neither.
+        match bindings.range(keys.to_vec()..).next() {
+            Some((bound, action)) if bound == keys => Lookup::Bound(action),
+            Some((bound, _)) if bound.starts_with(keys) => Lookup::Pending,
+            _ => Lookup::Unbound,
+        }
+    }
+
+    /// Every binding in `mode`, in key order. For help and listing.
+    pub fn bindings(&self, mode: Mode) -> impl Iterator<Item = (&[Chord], &Action)> {
+        self.modes
+            .get(&mode)
+            .into_iter()
+            .flatten()
+            .map(|(keys, action)| (keys.as_slice(), action))
+    }
+
+    /// The piano key a chord presses and the note the layout gives it, in
+    /// modes that play notes. The key is lowercase, so shift doesn't
+    /// change which key is released.
+    pub fn note(&self, mode: Mode, chord: &Chord) -> Option<(char, Note)> {
+        if !PIANO_MODES.contains(&mode) {
+            return None;
+        }
+
+        let c = chord.as_char()?.to_ascii_lowercase();
+
+        self.piano.get(&c).map(|note| (c, *note))
+    }
+
+    /// Lowest and highest notes of the piano layout.
+    pub fn piano_range(&self) -> Option<RangeInclusive<u8>> {
+        let notes = self.piano.values().map(|note| u8::from(*note));
+
+        Some(notes.clone().min()?..=notes.max()?)
+    }
+
+    /// Check every command binding names a registered command with valid
+    /// arguments.
+    pub fn validate<C>(&self, registry: &Registry<C>) -> Result<(), KeymapError> {
+        for (mode, bindings) in &self.modes {
+            for (keys, action) in bindings {
+                let Action::Command(invocation) = action else {
+                    continue;
+                };
+
+                let Some(command) = registry.get(&invocation.name) else {
+                    return Err(KeymapError::UnknownCommand {
+                        mode: mode.name(),
+                        keys: format_keys(keys),
+                        command: invocation.name.clone(),
+                    });
+                };
+
+                command.parse_args(&invocation.args).map_err(|error| {
+                    KeymapError::InvalidCommand {
+                        mode: mode.name(),
+                        keys: format_keys(keys),
+                        error,
+                    }
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::commands;
+
+    fn keys(notation: &str) -> Vec<Chord> {
+        parse_keys(notation, &[Chord::named(Named::Space)]).unwrap()
+    }
+
+    fn command(name: &str) -> Action {
+        Action::Command(Invocation::new(name))
+    }
+
+    #[test]
+    fn builtin_keymap_is_valid() {
+        let keymap = Keymap::builtin();
+
+        keymap.validate(&commands::registry()).unwrap();
+        assert_eq!(
+            keymap.note(Mode::Play, &Chord::char('A')),
+            Some(('a', Note::C4))
+        );
+        assert_eq!(
+            keymap.note(Mode::Step, &Chord::char('a')),
+            Some(('a', Note::C4))
+        );
+        assert_eq!(keymap.piano_range(), Some(60..=80));
+        assert_eq!(keymap.note(Mode::Normal, &Chord::char('a')), None);
+    }
+
+    #[test]
+    fn notation_round_trips() {
+        for notation in ["gg", "<C-s>", "<S-Tab>", "<Esc>", "<A-x>j", "<lt>", "N"] {
+            assert_eq!(format_keys(&keys(notation)), notation);
+        }
+    }
+
+    #[test]
+    fn notation_normalizes() {
+        assert_eq!(keys("<esc>"), keys("<Esc>"));
+        assert_eq!(keys("<S-n>"), keys("N"));
+        assert_eq!(keys("<C-S-a>"), keys("<C-a>"));
+        assert_eq!(keys("<Enter>"), keys("<CR>"));
+        assert_eq!(keys("<leader>p"), keys("<Space>p"));
+    }
+
+    #[test]
+    fn bare_angle_brackets_are_keys() {
+        assert_eq!(keys("<<"), [Chord::char('<'), Chord::char('<')]);
+        assert_eq!(keys(">"), [Chord::char('>')]);
+    }
+
+    #[test]
+    fn unknown_names_are_errors() {
+        let error = parse_keys("<Ecs>", &[]).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Invalid key notation \"<Ecs>\": unknown key <Ecs>"
+        );
+        assert!(parse_keys("<leader>x", &[]).is_err());
+    }
+
+    #[test]
+    fn chord_from_key_event() {
+        let shifted = Chord::from_key(&Key::Character("N".into()), Modifiers::SHIFT);
+        let control = Chord::from_key(&Key::Character("S".into()), Modifiers::CTRL);
+        let space = Chord::from_key(&Key::Named(Named::Space), Modifiers::empty());
+
+        assert_eq!(shifted, Some(Chord::char('N')));
+        assert_eq!(control.map(|c| c.to_string()), Some("<C-s>".to_string()));
+        assert_eq!(space, Some(Chord::named(Named::Space)));
+        assert_eq!(
+            Chord::from_key(&Key::Named(Named::Shift), Modifiers::SHIFT),
+            None
+        );
+    }
+
+    #[test]
+    fn lookup_waits_for_sequences() {
+        let mut keymap = Keymap::default();
+        keymap
+            .bind(Mode::Normal, keys("gg"), command("top"))
+            .unwrap();
+
+        assert_eq!(keymap.lookup(Mode::Normal, &keys("g")), Lookup::Pending);
+        assert_eq!(
+            keymap.lookup(Mode::Normal, &keys("gg")),
+            Lookup::Bound(&command("top"))
+        );
+        assert_eq!(keymap.lookup(Mode::Normal, &keys("gx")), Lookup::Unbound);
+        assert_eq!(keymap.lookup(Mode::Play, &keys("gg")), Lookup::Unbound);
+    }
+
+    #[test]
+    fn prefixes_conflict() {
+        let mut keymap = Keymap::default();
+        keymap
+            .bind(Mode::Normal, keys("dd"), command("delete"))
+            .unwrap();
+
+        let error = keymap
+            .bind(Mode::Normal, keys("d"), command("other"))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "normal mode: \"d\" conflicts with \"dd\""
+        );
+        assert!(
+            keymap
+                .bind(Mode::Normal, keys("ddx"), command("x"))
+                .is_err()
+        );
+
+        // Rebinding the same keys replaces them.
+        keymap
+            .bind(Mode::Normal, keys("dd"), command("cut"))
+            .unwrap();
+        assert_eq!(
+            keymap.lookup(Mode::Normal, &keys("dd")),
+            Lookup::Bound(&command("cut"))
+        );
+    }
+
+    #[test]
+    fn piano_keys_conflict_in_play_mode() {
+        let source = r#"(modes: { Play: { "a": ":normal" } }, piano: { "a": 60 })"#;
+
+        let error = Keymap::from_ron(source).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "play mode: \"a\" conflicts with \"piano key a\""
+        );
+    }
+
+    #[test]
+    fn validate_reports_unknown_commands() {
+        let source = r#"(modes: { Normal: { "x": ":explode" } })"#;
+        let keymap = Keymap::from_ron(source).unwrap();
+
+        let error = keymap.validate(&commands::registry()).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "normal mode: \"x\" runs unknown command \"explode\""
+        );
+    }
+
+    #[test]
+    fn validate_checks_arguments() {
+        let source = r#"(modes: { Play: { "0": ":velocity 0" } })"#;
+        let keymap = Keymap::from_ron(source).unwrap();
+
+        assert!(matches!(
+            keymap.validate(&commands::registry()),
+            Err(KeymapError::InvalidCommand { .. })
+        ));
+    }
+
+    #[test]
+    fn file_bindings_type_keys_without_remapping() {
+        let source = r#"(leader: "\\", modes: { Normal: { "<leader>p": "]]" } })"#;
+        let keymap = Keymap::from_ron(source).unwrap();
+
+        assert_eq!(
+            keymap.lookup(Mode::Normal, &parse_keys("\\p", &[]).unwrap()),
+            Lookup::Bound(&Action::Keys {
+                keys: keys("]]"),
+                remap: false
+            })
+        );
+    }
+}
@@ -1,6 +1,17 @@
 pub mod app;
 pub mod canvas;
+pub mod command;
+pub mod command_line;
+pub mod commands;
+pub mod editor;
+pub mod error;
+pub mod keymap;
+pub mod macros;
+pub mod options;
+pub mod paths;
+pub mod registers;
 pub mod status_bar;
 pub mod theme;
+pub mod viewport;
 
 pub use app::run;
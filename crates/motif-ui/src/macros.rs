@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use crate::command::Invocation;
+use crate::error::RegisterError;
+
+/// One command as the user ran it, with the count typed before it. Replaying
+/// steps reruns the same commands, wherever the cursor now is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub count: Option<u32>,
+    pub invocation: Invocation,
+}
+
+/// Command sequences recorded with `q{a-z}` and replayed with `@{a-z}`.
+#[derive(Debug, Default)]
+pub struct Macros {
+    macros: BTreeMap<char, Vec<Step>>,
+    recording: Option<(char, Vec<Step>)>,
+    /// Last macro replayed, for `@@`.
+    last: Option<char>,
+}
+
+impl Macros {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start recording into `name`. An uppercase name appends to the macro
+    /// already there.
+    pub fn start(&mut self, name: char) -> Result<(), RegisterError> {
+        if !name.is_ascii_alphabetic() {
+            return Err(RegisterError::InvalidName(name));
+        }
+
+        let steps = if name.is_ascii_uppercase() {
+            self.macros
+                .get(&name.to_ascii_lowercase())
+                .cloned()
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        self.recording = Some((name.to_ascii_lowercase(), steps));
+
+        Ok(())
+    }
+
+    /// Finish recording, returning the macro's name.
+    pub fn stop(&mut self) -> Option<char> {
+        let (name, steps) = self.recording.take()?;
+        self.macros.insert(name, steps);
+
+        Some(name)
+    }
+
+    /// The macro being recorded, if any.
+    pub fn recording(&self) -> Option<char> {
+        self.recording.as_ref().map(|(name, _)| *name)
+    }
+
+    pub fn push(&mut self, step: Step) {
+        if let Some((_, steps)) = &mut self.recording {
+            steps.push(step);
+        }
+    }
+
+    /// Steps to replay for `name`, where `@` means the last macro replayed.
+    pub fn get(&mut self, name: char) -> Result<Option<Vec<Step>>, RegisterError> {
+        let name = match name {
+            '@' => match self.last {
+                Some(last) => last,
+                None => return Ok(None),
+            },
+            name if name.is_ascii_alphabetic() => name.to_ascii_lowercase(),
+            name => return Err(RegisterError::InvalidName(name)),
+        };
+
+        self.last = Some(name);
+
+        Ok(self.macros.get(&name).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(name: &str) -> Step {
+        Step {
+            count: None,
+            invocation: Invocation::new(name),
+        }
+    }
+
+    #[test]
+    fn records_appends_and_replays_last() {
+        let mut macros = Macros::new();
+
+        macros.start('a').unwrap();
+        macros.push(step("paste"));
+        assert_eq!(macros.stop(), Some('a'));
+
+        macros.start('A').unwrap();
+        assert_eq!(macros.recording(), Some('a'));
+        macros.push(step("undo"));
+        macros.stop();
+
+        assert_eq!(macros.get('@').unwrap(), None);
+        assert_eq!(
+            macros.get('a').unwrap(),
+            Some(vec![step("paste"), step("undo")])
+        );
+        assert_eq!(macros.get('@').unwrap().map(|steps| steps.len()), Some(2));
+        assert!(matches!(
+            macros.start('1'),
+            Err(RegisterError::InvalidName('1'))
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+use motif_core::tick::TICKS_PER_QUARTER;
+
+use crate::error::CommandError;
+
+/// Names accepted by `:set`.
+pub const NAMES: &[&str] = &["grid", "step"];
+
+const TICKS_PER_WHOLE: u64 = TICKS_PER_QUARTER * 4;
+
+/// Longest grid or step, in whole notes. Keeps cursor and paste arithmetic
+/// far from overflowing.
+const MAX_WHOLE_NOTES: u64 = 4;
+
+/// Editor settings changed with `:set name=value`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    /// Snap and step size in ticks, written as a note fraction (`1/16`).
+    pub grid: u64,
+    /// Length of notes written in Step mode, also a note fraction.
+    pub step: u64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Options {
+    pub fn new() -> Self {
+        Self {
+            grid: TICKS_PER_WHOLE / 16,
+            step: TICKS_PER_WHOLE / 16,
+        }
+    }
+
+    /// Apply a `name=value` assignment.
+    pub fn set(&mut self, assignment: &str) -> Result<(), CommandError> {
+        let (name, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| invalid("option", "name=value", assignment))?;
+
+        match name.trim() {
+            "grid" => self.grid = parse_fraction("grid", value.trim())?,
+            "step" => self.step = parse_fraction("step", value.trim())?,
+            _ => return Err(invalid("option", NAMES.join(", ").as_str(), name)),
+        }
+
+        Ok(())
+    }
+
+    /// The current value of an option, as `:set` would accept it.
+    pub fn get(&self, name: &str) -> Option<String> {
+        match name {
+            "grid" => Some(format_fraction(self.grid)),
+            "step" => Some(format_fraction(self.step)),
+            _ => None,
+        }
+    }
+}
+
+fn invalid(argument: &'static str, expected: &str, value: &str) -> CommandError {
+    CommandError::InvalidArgument {
+        command: "set",
+        argument,
+        expected: expected.to_string(),
+        value: value.to_string(),
+    }
+}
+
+/// Ticks for a note fraction such as `1/16` or `3/8`. Must come out to a
+/// whole number of ticks, no longer than `MAX_WHOLE_NOTES`.
+fn parse_fraction(option: &'static str, value: &str) -> Result<u64, CommandError> {
+    let expected = "a note fraction like 1/16";
+    let parse = |part: &str| part.trim().parse::<u64>().ok().filter(|n| *n > 0);
+
+    let (numerator, denominator) = match value.split_once('/') {
+        Some((numerator, denominator)) => (parse(numerator), parse(denominator)),
+        None => (parse(value), Some(1)),
+    };
+
+    let (Some(numerator), Some(denominator)) = (numerator, denominator) else {
+        return Err(invalid(option, expected, value));
+    };
+
+    let too_long = || {
+        invalid(
+            option,
+            &format!("at most {MAX_WHOLE_NOTES} whole notes"),
+            value,
+        )
+    };
+    let ticks = numerator
+        .checked_mul(TICKS_PER_WHOLE)
+        .ok_or_else(too_long)?;
+
+    if !ticks.is_multiple_of(denominator) {
+        return Err(invalid(
+            option,
+            "a fraction that divides 480 ticks per quarter",
+            value,
+        ));
+    }
+
+    let ticks = ticks / denominator;
+
+    if ticks > MAX_WHOLE_NOTES * TICKS_PER_WHOLE {
+        return Err(too_long());
+    }
+
+    Ok(ticks)
+}
+
+/// Lowest-terms fraction of a whole note.
+fn format_fraction(ticks: u64) -> String {
+    let gcd = gcd(ticks, TICKS_PER_WHOLE);
+
+    format!("{}/{}", ticks / gcd, TICKS_PER_WHOLE / gcd)
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_grid_from_fraction() {
+        let mut options = Options::new();
+
+        options.set("grid=1/8").unwrap();
+        assert_eq!(options.grid, 240);
+
+        options.set("grid=1/12").unwrap();
+        assert_eq!(options.grid, 160);
+        assert_eq!(options.get("grid").unwrap(), "1/12");
+
+        options.set("step=3/8").unwrap();
+        assert_eq!(options.step, 720);
+        assert_eq!(options.grid, 160);
+    }
+
+    #[test]
+    fn set_rejects_bad_assignments() {
+        let mut options = Options::new();
+
+        assert!(options.set("grid").is_err());
+        assert!(options.set("grid=0/4").is_err());
+        assert!(options.set("grid=1/7").is_err());
+        assert!(options.set("swing=1/8").is_err());
+        assert_eq!(options, Options::new());
+    }
+
+    #[test]
+    fn set_rejects_fractions_longer_than_the_limit() {
+        let mut options = Options::new();
+
+        options.set("grid=4").unwrap();
+        assert_eq!(options.grid, 4 * TICKS_PER_WHOLE);
+
+        assert!(options.set("grid=10000000000000000").is_err());
+        assert!(options.set("step=9/2").is_err());
+        assert_eq!(options.step, Options::new().step);
+    }
+}
@@ -0,0 +1,43 @@
+use std::env;
+use std::path::PathBuf;
+
+/// Where user configuration lives: `$XDG_CONFIG_HOME/motif`, falling back
+/// to `~/.config/motif`. `None` when neither variable is set.
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// User keymap, loaded in place of the built-in one when present.
+pub fn keymap_file() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("keymap.ron"))
+}
+
+/// Where state kept between sessions lives: `$XDG_STATE_HOME/motif`,
+/// falling back to `~/.local/state/motif`.
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
+
+/// Lines entered on the command line, oldest first.
+pub fn history_file() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("history"))
+}
+
+/// Yank registers, kept between sessions.
+pub fn registers_file() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("registers.ron"))
+}
+
+fn xdg_dir(variable: &str, fallback: &str) -> Option<PathBuf> {
+    // Tests never read or write the user's files.
+    if cfg!(test) {
+        return None;
+    }
+
+    let base = env::var_os(variable)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))?;
+
+    Some(base.join("motif"))
+}
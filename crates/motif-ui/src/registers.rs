@@ -0,0 +1,306 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::Path;
+
+use motif_core::note::NoteEvent;
+use motif_core::tick::Tick;
+use serde::{Deserialize, Serialize};
+use wmidi::{Note, Velocity};
+
+use crate::error::RegisterError;
+
+/// Yanks kept in the numbered registers `1`–`9`, newest first.
+pub const HISTORY_LEN: usize = 9;
+
+/// The register used when none is named.
+pub const UNNAMED: char = '"';
+
+/// Yanked and deleted notes, by register. Start ticks are relative to
+/// where the yanked range began, so material can be pasted anywhere.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Registers {
+    unnamed: Vec<NoteEvent>,
+    named: BTreeMap<char, Vec<NoteEvent>>,
+    history: VecDeque<Vec<NoteEvent>>,
+}
+
+impl Registers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `name` can follow `"`: the unnamed register, `a`–`z` (or
+    /// `A`–`Z` to append) or history `1`–`9`.
+    pub fn is_valid(name: char) -> bool {
+        name == UNNAMED || name.is_ascii_alphabetic() || ('1'..='9').contains(&name)
+    }
+
+    /// Record a yank or delete. It always lands in the unnamed register and
+    /// history; a letter also stores it there, and an uppercase letter
+    /// appends after what the register held.
+    pub fn store(&mut self, name: char, notes: Vec<NoteEvent>) -> Result<(), RegisterError> {
+        if !Self::is_valid(name) {
+            return Err(RegisterError::InvalidName(name));
+        }
+
+        if name.is_ascii_digit() {
+            return Err(RegisterError::ReadOnly(name));
+        }
+
+        let stored = if name.is_ascii_uppercase() {
+            let register = self.named.entry(name.to_ascii_lowercase()).or_default();
+            let offset = Tick::from_raw(span(register));
+
+            register.extend(notes.into_iter().map(|note| NoteEvent {
+                start_tick: note.start_tick + offset,
+                ..note
+            }));
+            register.clone()
+        } else {
+            if name != UNNAMED {
+                self.named.insert(name, notes.clone());
+            }
+            notes
+        };
+
+        self.history.push_front(stored.clone());
+        self.history.truncate(HISTORY_LEN);
+        self.unnamed = stored;
+
+        Ok(())
+    }
+
+    /// What pasting from `name` inserts. `None` for an empty register.
+    pub fn get(&self, name: char) -> Result<Option<&[NoteEvent]>, RegisterError> {
+        if !Self::is_valid(name) {
+            return Err(RegisterError::InvalidName(name));
+        }
+
+        let notes = match name {
+            UNNAMED => Some(&self.unnamed),
+            '1'..='9' => self.history.get(name as usize - '1' as usize),
+            _ => self.named.get(&name.to_ascii_lowercase()),
+        };
+
+        Ok(notes.map(Vec::as_slice).filter(|notes| !notes.is_empty()))
+    }
+
+    /// Non-empty registers, unnamed first, for `:registers`.
+    pub fn list(&self) -> Vec<(char, &[NoteEvent])> {
+        let numbered = self
+            .history
+            .iter()
+            .zip('1'..='9')
+            .map(|(notes, name)| (name, notes.as_slice()));
+        let named = self
+            .named
+            .iter()
+            .map(|(name, notes)| (*name, notes.as_slice()));
+
+        std::iter::once((UNNAMED, self.unnamed.as_slice()))
+            .chain(numbered)
+            .chain(named)
+            .filter(|(_, notes)| !notes.is_empty())
+            .collect()
+    }
+
+    pub fn from_ron(src: &str) -> Result<Self, RegisterError> {
+        let file: RegisterFile = ron::from_str(src)?;
+        let read = |name: char, notes: Vec<StoredNote>| -> Result<_, RegisterError> {
+            notes
+                .into_iter()
+                .map(|note| note.into_event(name))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let mut named = BTreeMap::new();
+
+        for (name, notes) in file.named {
+            if !name.is_ascii_lowercase() {
+                return Err(RegisterError::InvalidName(name));
+            }
+
+            named.insert(name, read(name, notes)?);
+        }
+
+        let mut history = VecDeque::new();
+
+        for (notes, name) in file.history.into_iter().zip('1'..='9') {
+            history.push_back(read(name, notes)?);
+        }
+
+        Ok(Self {
+            unnamed: read(UNNAMED, file.unnamed)?,
+            named,
+            history,
+        })
+    }
+
+    pub fn to_ron(&self) -> Result<String, RegisterError> {
+        let write = |notes: &[NoteEvent]| notes.iter().map(StoredNote::from).collect();
+        let file = RegisterFile {
+            unnamed: write(&self.unnamed),
+            named: self
+                .named
+                .iter()
+                .map(|(name, notes)| (*name, write(notes)))
+                .collect(),
+            history: self.history.iter().map(|notes| write(notes)).collect(),
+        };
+
+        Ok(ron::ser::to_string_pretty(
+            &file,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    /// A missing file means empty registers.
+    pub fn load(path: &Path) -> Result<Self, RegisterError> {
+        match fs::read_to_string(path) {
+            Ok(src) => Self::from_ron(&src),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), RegisterError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, self.to_ron()?)?;
+
+        Ok(())
+    }
+}
+
+/// Ticks from the start of the material to the end of its last note.
+pub fn span(notes: &[NoteEvent]) -> u64 {
+    notes
+        .iter()
+        .map(|note| note.end_tick().as_raw())
+        .max()
+        .unwrap_or(0)
+}
+
+// On-disk shape. wmidi types have no serde support.
+
+#[derive(Serialize, Deserialize)]
+struct RegisterFile {
+    #[serde(default)]
+    unnamed: Vec<StoredNote>,
+    #[serde(default)]
+    named: BTreeMap<char, Vec<StoredNote>>,
+    #[serde(default)]
+    history: Vec<Vec<StoredNote>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredNote {
+    start: Tick,
+    length: u64,
+    note: u8,
+    velocity: u8,
+}
+
+impl From<&NoteEvent> for StoredNote {
+    fn from(event: &NoteEvent) -> Self {
+        Self {
+            start: event.start_tick,
+            length: event.length_ticks,
+            note: u8::from(event.note),
+            velocity: u8::from(event.velocity),
+        }
+    }
+}
+
+impl StoredNote {
+    fn into_event(self, name: char) -> Result<NoteEvent, RegisterError> {
+        let invalid = |message: &str| RegisterError::InvalidNote {
+            name,
+            message: message.to_string(),
+        };
+
+        Ok(NoteEvent {
+            start_tick: self.start,
+            length_ticks: self.length,
+            note: Note::try_from(self.note).map_err(|_| invalid("pitch must be 0–127"))?,
+            velocity: Velocity::try_from(self.velocity)
+                .map_err(|_| invalid("velocity must be 0–127"))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(start: u64, pitch: u8) -> NoteEvent {
+        NoteEvent {
+            start_tick: Tick::from_raw(start),
+            length_ticks: 240,
+            note: Note::from_u8_lossy(pitch),
+            velocity: Velocity::MAX,
+        }
+    }
+
+    #[test]
+    fn store_fills_unnamed_and_history() {
+        let mut registers = Registers::new();
+
+        registers.store(UNNAMED, vec![note(0, 60)]).unwrap();
+        registers.store('a', vec![note(0, 62)]).unwrap();
+
+        assert_eq!(registers.get(UNNAMED).unwrap(), Some(&[note(0, 62)][..]));
+        assert_eq!(registers.get('a').unwrap(), Some(&[note(0, 62)][..]));
+        assert_eq!(registers.get('1').unwrap(), Some(&[note(0, 62)][..]));
+        assert_eq!(registers.get('2').unwrap(), Some(&[note(0, 60)][..]));
+        assert_eq!(registers.get('b').unwrap(), None);
+    }
+
+    #[test]
+    fn uppercase_appends_after_existing_material() {
+        let mut registers = Registers::new();
+
+        registers.store('a', vec![note(0, 60)]).unwrap();
+        registers.store('A', vec![note(0, 64)]).unwrap();
+
+        assert_eq!(
+            registers.get('a').unwrap(),
+            Some(&[note(0, 60), note(240, 64)][..])
+        );
+        assert_eq!(registers.get(UNNAMED).unwrap(), registers.get('A').unwrap());
+    }
+
+    #[test]
+    fn history_is_bounded_and_read_only() {
+        let mut registers = Registers::new();
+
+        for pitch in 0..HISTORY_LEN as u8 + 3 {
+            registers.store(UNNAMED, vec![note(0, pitch)]).unwrap();
+        }
+
+        assert_eq!(registers.list().len(), HISTORY_LEN + 1);
+        assert!(matches!(
+            registers.store('3', Vec::new()),
+            Err(RegisterError::ReadOnly('3'))
+        ));
+        assert!(matches!(
+            registers.get('?'),
+            Err(RegisterError::InvalidName('?'))
+        ));
+    }
+
+    #[test]
+    fn round_trips_through_ron() {
+        let mut registers = Registers::new();
+        registers
+            .store('q', vec![note(0, 60), note(480, 67)])
+            .unwrap();
+        registers.store(UNNAMED, vec![note(120, 72)]).unwrap();
+
+        let loaded = Registers::from_ron(&registers.to_ron().unwrap()).unwrap();
+
+        assert_eq!(loaded, registers);
+    }
+}
@@ -1,10 +1,84 @@
 use iced::widget::{container, row, text};
 use iced::{Background, Border, Element, Fill, Font, Theme};
 
+use motif_core::tick::{TICKS_PER_QUARTER, Tick};
+use wmidi::Velocity;
+
 use crate::app::{Message, Mode};
 use crate::theme;
 
-pub fn view(mode: &Mode) -> Element<'_, Message> {
+/// A line of feedback from the last command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusMessage {
+    pub text: String,
+    pub error: bool,
+}
+
+impl StatusMessage {
+    pub fn info(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            error: false,
+        }
+    }
+
+    pub fn error(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            error: true,
+        }
+    }
+}
+
+/// Everything the status bar shows.
+pub struct Status<'a> {
+    pub mode: &'a Mode,
+    pub bpm: f64,
+    /// The cursor's tick, shown as bar : beat : tick.
+    pub position: Tick,
+    pub track: &'a str,
+    pub preset: &'a str,
+    /// Shown only when set (Play and Step mode).
+    pub velocity: Option<Velocity>,
+    /// Notes the piano keys play, lowest to highest, in Play and Step mode.
+    pub keys: Option<String>,
+    /// Note length written in Step mode, as a fraction.
+    pub step: Option<String>,
+    /// Macro being recorded with `q`.
+    pub recording: Option<char>,
+    /// Text being typed after `:`. Replaces the rest of the bar while open.
+    pub command_line: Option<&'a str>,
+    pub message: Option<&'a StatusMessage>,
+}
+
+pub fn view(status: Status<'_>) -> Element<'_, Message> {
+    let bar = match status.command_line {
+        Some(line) => row![
+            text(format!(":{line}▏"))
+                .font(Font::MONOSPACE)
+                .size(12)
+                .color(theme::ZINC_200)
+        ],
+        None => info_row(status),
+    };
+
+    container(bar.spacing(12).align_y(iced::Alignment::Center))
+        .width(Fill)
+        .padding([6, 12])
+        .style(|_theme: &Theme| container::Style {
+            background: Some(Background::Color(theme::ZINC_900)),
+            border: Border {
+                color: theme::ZINC_800,
+                width: 1.0,
+                ..Border::default()
+            },
+            ..Default::default()
+        })
+        .into()
+}
+
+fn info_row(status: Status<'_>) -> iced::widget::Row<'_, Message> {
+    let mode = status.mode;
     let mode_badge = container(
         text(mode.label())
             .font(Font::MONOSPACE)
@@ -21,31 +95,96 @@ pub fn view(mode: &Mode) -> Element<'_, Message> {
         ..Default::default()
     });
 
-    let bpm = text("♩ 120")
+    let bpm = text(format!("♩ {}", status.bpm))
         .font(Font::MONOSPACE)
         .size(12)
         .color(theme::ZINC_500);
 
-    let position = text("001 : 1 : 000")
+    let position = text(format_position(status.position))
         .font(Font::MONOSPACE)
         .size(12)
         .color(theme::ZINC_400);
 
-    let bar = row![mode_badge, bpm, position]
-        .spacing(12)
-        .align_y(iced::Alignment::Center);
+    let track = text(status.track)
+        .font(Font::MONOSPACE)
+        .size(12)
+        .color(theme::ZINC_200);
 
-    container(bar)
-        .width(Fill)
-        .padding([6, 12])
-        .style(|_theme: &Theme| container::Style {
-            background: Some(Background::Color(theme::ZINC_900)),
-            border: Border {
-                color: theme::ZINC_800,
-                width: 1.0,
-                ..Border::default()
-            },
-            ..Default::default()
-        })
-        .into()
+    let preset = text(status.preset)
+        .font(Font::MONOSPACE)
+        .size(12)
+        .color(theme::ZINC_400);
+
+    let velocity = status.velocity.map(|velocity| {
+        text(format!("vel {:3}", u8::from(velocity)))
+            .font(Font::MONOSPACE)
+            .size(12)
+            .color(theme::ZINC_500)
+    });
+
+    let keys = status.keys.map(|keys| {
+        text(format!("keys {keys}"))
+            .font(Font::MONOSPACE)
+            .size(12)
+            .color(theme::ZINC_500)
+    });
+
+    let step = status.step.map(|step| {
+        text(format!("step {step}"))
+            .font(Font::MONOSPACE)
+            .size(12)
+            .color(theme::ZINC_500)
+    });
+
+    let recording = status.recording.map(|name| {
+        text(format!("recording @{name}"))
+            .font(Font::MONOSPACE)
+            .size(12)
+            .color(theme::ROSE_500)
+    });
+
+    let message = status.message.map(|message| {
+        text(message.text.as_str())
+            .font(Font::MONOSPACE)
+            .size(12)
+            .color(if message.error {
+                theme::ROSE_500
+            } else {
+                theme::ZINC_200
+            })
+    });
+
+    row![mode_badge, bpm, position, track, preset]
+        .push(keys)
+        .push(velocity)
+        .push(step)
+        .push(recording)
+        .push(message)
+}
+
+/// Bar, beat and tick, counted from 1 like a sequencer's transport (4/4).
+fn format_position(position: Tick) -> String {
+    let ticks = position.as_raw();
+    let beat = ticks / TICKS_PER_QUARTER;
+
+    format!(
+        "{:03} : {} : {:03}",
+        beat / 4 + 1,
+        beat % 4 + 1,
+        ticks % TICKS_PER_QUARTER
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_counts_from_one() {
+        assert_eq!(format_position(Tick::ZERO), "001 : 1 : 000");
+        assert_eq!(
+            format_position(Tick::from_raw(9 * TICKS_PER_QUARTER + 120)),
+            "003 : 2 : 120"
+        );
+    }
 }
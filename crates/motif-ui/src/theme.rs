@@ -15,6 +15,7 @@ pub const BLUE_500: Color = Color::from_rgb(0.400, 0.620, 0.980); // #669ef9
 pub const GREEN_500: Color = Color::from_rgb(0.367, 0.812, 0.620); // #5dcf9e
 pub const AMBER_500: Color = Color::from_rgb(0.929, 0.722, 0.319); // #edb851
 pub const PURPLE_500: Color = Color::from_rgb(0.612, 0.557, 0.871); // #9c8eda
+pub const CYAN_500: Color = Color::from_rgb(0.345, 0.780, 0.859); // #58c7db
 pub const ROSE_500: Color = Color::from_rgb(0.914, 0.463, 0.576); // #e97693
 
 use crate::app::Mode;
@@ -24,6 +25,10 @@ impl Mode {
         match self {
             Mode::Normal => "NORMAL",
             Mode::Play => "PLAY",
+            Mode::Step => "STEP",
+            Mode::Operator => "OPERATOR",
+            Mode::Visual => "VISUAL",
+            Mode::VisualBlock => "V-BLOCK",
         }
     }
 
@@ -31,6 +36,10 @@ impl Mode {
         match self {
             Mode::Normal => BLUE_500,
             Mode::Play => GREEN_500,
+            Mode::Step => CYAN_500,
+            Mode::Operator => BLUE_500,
+            Mode::Visual => AMBER_500,
+            Mode::VisualBlock => PURPLE_500,
         }
     }
 }
@@ -0,0 +1,222 @@
+use std::ops::RangeInclusive;
+
+use motif_core::tick::{TICKS_PER_QUARTER, Tick};
+
+pub const MIN_PIXELS_PER_BEAT: f32 = 12.5;
+pub const MAX_PIXELS_PER_BEAT: f32 = 800.0;
+pub const MIN_LANE_HEIGHT: f32 = 6.0;
+pub const MAX_LANE_HEIGHT: f32 = 48.0;
+
+const HIGHEST_PITCH: u8 = 127;
+
+/// The part of the piano roll on screen: where it starts and how zoomed
+/// in it is. Positions are relative to the note area, right of the gutter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Viewport {
+    /// Tick at the left edge.
+    pub left: Tick,
+    /// Pitch of the lane at the top edge.
+    pub top: u8,
+    /// Width of one quarter note.
+    pub pixels_per_beat: f32,
+    /// Height of one semitone lane.
+    pub lane_height: f32,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Viewport {
+    pub fn new() -> Self {
+        Self {
+            left: Tick::ZERO,
+            // C6, so the typing keyboard's octave sits mid-screen.
+            top: 84,
+            pixels_per_beat: 100.0,
+            lane_height: 24.0,
+        }
+    }
+
+    pub fn pixels_per_tick(&self) -> f32 {
+        self.pixels_per_beat / TICKS_PER_QUARTER as f32
+    }
+
+    /// Horizontal position of a tick. Negative when left of the view.
+    pub fn x(&self, tick: Tick) -> f32 {
+        (tick.as_raw() as f64 - self.left.as_raw() as f64) as f32 * self.pixels_per_tick()
+    }
+
+    /// Tick under a horizontal position, never before zero.
+    pub fn tick_at(&self, x: f32) -> Tick {
+        let ticks = self.left.as_raw() as f64 + (x / self.pixels_per_tick()) as f64;
+
+        Tick::from_raw(ticks.max(0.0) as u64)
+    }
+
+    /// Top edge of a pitch's lane. Negative when above the view.
+    pub fn y(&self, pitch: u8) -> f32 {
+        (self.top as f32 - pitch as f32) * self.lane_height
+    }
+
+    /// Pitch of the lane under a vertical position.
+    pub fn pitch_at(&self, y: f32) -> Option<u8> {
+        let lanes = (y / self.lane_height).floor();
+        let pitch = self.top as f32 - lanes;
+
+        (0.0..=HIGHEST_PITCH as f32)
+            .contains(&pitch)
+            .then_some(pitch as u8)
+    }
+
+    /// Pitches with any part of their lane within `height`, top first.
+    pub fn visible_pitches(&self, height: f32) -> RangeInclusive<u8> {
+        let lanes = (height / self.lane_height).ceil() as u8;
+
+        self.top.saturating_sub(lanes.saturating_sub(1))..=self.top
+    }
+
+    /// Move by whole beats; stops at the start of the song.
+    pub fn scroll_time(&mut self, beats: i64) {
+        let left = self.left.as_raw() as i64 + beats * TICKS_PER_QUARTER as i64;
+
+        self.left = Tick::from_raw(left.max(0) as u64);
+    }
+
+    /// Move by semitones, positive upwards; stops at the top MIDI note.
+    pub fn scroll_pitch(&mut self, semitones: i32) {
+        self.top = (self.top as i32 + semitones).clamp(0, HIGHEST_PITCH as i32) as u8;
+    }
+
+    pub fn zoom_time(&mut self, factor: f32) {
+        self.pixels_per_beat =
+            (self.pixels_per_beat * factor).clamp(MIN_PIXELS_PER_BEAT, MAX_PIXELS_PER_BEAT);
+    }
+
+    pub fn zoom_pitch(&mut self, factor: f32) {
+        self.lane_height = (self.lane_height * factor).clamp(MIN_LANE_HEIGHT, MAX_LANE_HEIGHT);
+    }
+
+    /// Scroll the least needed to bring a tick and pitch into a view of the
+    /// given size.
+    pub fn reveal(&mut self, tick: Tick, pitch: u8, width: f32, height: f32) {
+        if tick < self.left {
+            self.left = tick;
+        } else if self.x(tick) >= width {
+            let beats = (width / self.pixels_per_beat).floor().max(1.0) as u64;
+            let span = (beats - 1) * TICKS_PER_QUARTER;
+
+            let left = tick.as_raw().saturating_sub(span);
+
+            // Whole beats, so the beat lines don't shift by fractions.
+            self.left = Tick::from_raw(left - left % TICKS_PER_QUARTER);
+        }
+
+        let pitches = self.visible_pitches(height);
+
+        if pitch > *pitches.end() {
+            self.top = pitch;
+        } else if pitch < *pitches.start() {
+            self.top = pitch.saturating_add(pitches.end() - pitches.start());
+        }
+    }
+}
+
+/// Note name with octave, where 60 is C4.
+pub fn pitch_name(pitch: u8) -> String {
+    const NAMES: [&str; 12] = [
+        "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+    ];
+
+    format!(
+        "{}{}",
+        NAMES[(pitch % 12) as usize],
+        (pitch / 12) as i32 - 1
+    )
+}
+
+/// Whether a pitch is a black key on a piano.
+pub fn is_black_key(pitch: u8) -> bool {
+    matches!(pitch % 12, 1 | 3 | 6 | 8 | 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_round_trip() {
+        let mut viewport = Viewport::new();
+        viewport.left = Tick::from_quarters(4);
+
+        assert_eq!(viewport.x(Tick::from_quarters(5)), 100.0);
+        assert_eq!(viewport.x(Tick::from_quarters(3)), -100.0);
+        assert_eq!(viewport.tick_at(150.0), Tick::from_raw(4 * 480 + 720));
+        assert_eq!(viewport.tick_at(-1000.0), Tick::ZERO);
+
+        assert_eq!(viewport.y(84), 0.0);
+        assert_eq!(viewport.y(83), 24.0);
+        assert_eq!(viewport.pitch_at(30.0), Some(83));
+        assert_eq!(viewport.pitch_at(-1.0), Some(85));
+    }
+
+    #[test]
+    fn scrolling_stops_at_edges() {
+        let mut viewport = Viewport::new();
+
+        viewport.scroll_time(-4);
+        assert_eq!(viewport.left, Tick::ZERO);
+        viewport.scroll_time(8);
+        assert_eq!(viewport.left, Tick::from_quarters(8));
+
+        viewport.scroll_pitch(100);
+        assert_eq!(viewport.top, 127);
+        viewport.scroll_pitch(-200);
+        assert_eq!(viewport.top, 0);
+    }
+
+    #[test]
+    fn zoom_is_clamped() {
+        let mut viewport = Viewport::new();
+
+        viewport.zoom_time(1000.0);
+        assert_eq!(viewport.pixels_per_beat, MAX_PIXELS_PER_BEAT);
+        viewport.zoom_pitch(0.0);
+        assert_eq!(viewport.lane_height, MIN_LANE_HEIGHT);
+    }
+
+    #[test]
+    fn visible_pitches_cover_partial_lanes() {
+        let viewport = Viewport::new();
+
+        assert_eq!(viewport.visible_pitches(24.0), 84..=84);
+        assert_eq!(viewport.visible_pitches(50.0), 82..=84);
+    }
+
+    #[test]
+    fn reveal_scrolls_only_when_needed() {
+        let mut viewport = Viewport::new();
+
+        viewport.reveal(Tick::from_quarters(2), 80, 800.0, 240.0);
+        assert_eq!(viewport, Viewport::new());
+
+        viewport.reveal(Tick::from_quarters(10), 60, 800.0, 240.0);
+        assert_eq!(viewport.left, Tick::from_quarters(3));
+        assert_eq!(viewport.top, 69);
+
+        viewport.reveal(Tick::from_quarters(1), 90, 800.0, 240.0);
+        assert_eq!(viewport.left, Tick::from_quarters(1));
+        assert_eq!(viewport.top, 90);
+    }
+
+    #[test]
+    fn names_and_colors_of_pitches() {
+        assert_eq!(pitch_name(60), "C4");
+        assert_eq!(pitch_name(61), "C#4");
+        assert_eq!(pitch_name(0), "C-1");
+        assert!(is_black_key(61));
+        assert!(!is_black_key(64));
+    }
+}
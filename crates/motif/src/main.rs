@@ -5,7 +5,7 @@ use motif_engine::{
     events::{RoutedEvent, ScheduledEvent},
     graph::evaluate_node,
 };
-use motif_pulse::synth::Pulse;
+use motif_pulse::{preset, synth::Pulse};
 use rtrb::{Consumer, RingBuffer};
 
 struct AudioRuntime {
@@ -19,7 +19,12 @@ fn main() -> iced::Result {
     let playback = PlaybackControl::new(producer);
     let _audio = start_audio(consumer);
 
-    motif_ui::run(playback)
+    let presets = preset::factory()
+        .into_iter()
+        .map(|preset| preset.name)
+        .collect();
+
+    motif_ui::run(playback, presets)
 }
 
 fn start_audio(consumer: Consumer<RoutedEvent>) -> AudioRuntime {
@@ -55,11 +60,7 @@ fn start_audio(consumer: Consumer<RoutedEvent>) -> AudioRuntime {
 
                 evaluate_node(&mut synth, &[], &mut buffer, &scheduled, sample_rate);
 
-                for f in 0..frames {
-                    for ch in 0..channels {
-                        out[f * channels + ch] = if ch < 2 { buffer.channel(ch)[f] } else { 0.0 };
-                    }
-                }
+                buffer.write_interleaved(out, channels);
             },
             |err| eprintln!("audio error: {err}"),
             None,